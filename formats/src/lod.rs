@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::ops::Deref;
 use std::path::Path;
 
@@ -48,6 +48,10 @@ impl LodIndex {
         }
     }
 
+    pub fn contains(&self, filename: &str) -> bool {
+        self.registry.contains_key(filename)
+    }
+
     pub fn read_file(&mut self, filename: &str) -> Box<[u8]> {
         let LodFileInfo {
             offset,
@@ -61,7 +65,7 @@ impl LodIndex {
         } else {
             Box::new(&self.handle)
         };
-        reader
+        BufReader::new(reader)
             .bytes()
             .take(size as usize)
             .map(Result::unwrap)
@@ -40,6 +40,10 @@ impl SndIndex {
         }
     }
 
+    pub fn contains(&self, filename: &str) -> bool {
+        self.registry.contains_key(filename)
+    }
+
     pub fn read_file(&mut self, filename: &str) -> Box<[u8]> {
         let SndFileInfo { offset, size } = *self.registry.get(filename).unwrap();
         self.handle.seek(SeekFrom::Start(offset as u64)).unwrap();
@@ -0,0 +1,56 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use logic::ai;
+use logic::command::Command;
+use logic::gamestate::{GameState, StackHandle};
+
+// Компьютер думает в отдельном потоке над копией состояния,
+// чтобы не останавливать отрисовку
+#[derive(Default)]
+pub struct AiPlayer {
+    // Расчёт привязан к отряду, для которого он запущен
+    pending: Option<(StackHandle, Receiver<Option<Command>>)>,
+}
+
+impl AiPlayer {
+    // Запускает расчёт при первом вызове и возвращает команду, когда она готова
+    pub fn poll(&mut self, state: &GameState) -> Option<Command> {
+        let current = state.get_current_stack_handle();
+
+        // Ход перешёл к другому отряду, пока компьютер думал
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|&(stack, _)| stack != current)
+        {
+            self.cancel();
+        }
+
+        let (_, receiver) = self.pending.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            let state = state.clone();
+
+            thread::spawn(move || {
+                let _ = sender.send(ai::choose_command(&state));
+            });
+
+            (current, receiver)
+        });
+
+        match receiver.try_recv() {
+            Ok(command) => {
+                self.pending = None;
+                command
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => panic!("AI thread has crashed"),
+        }
+    }
+
+    // Забывает расчёт для состояния, которое уже изменилось
+    // Поток доработает сам, его ответ просто некому будет принять
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::time::Duration;
 
 use gamedata::creatures;
-use gamedata::creatures::Creature;
+use gamedata::creatures::{Creature, Missile};
 use gamedata::spells::SpellAnimation;
 use logic::event::Event;
 use logic::gamestate::{GameState, StackHandle};
@@ -28,14 +28,27 @@ use self::time_progress::TimeProgress;
 pub struct Animations {
     pub creature: HashMap<StackHandle, AnimationState>,
     pub entity: Vec<EntityAnimation>,
+    pub projectiles: Vec<ProjectileAnimation>,
 }
 
 pub struct EntityAnimation {
     pub position: (i32, i32),
+    // Смещение к концу анимации, снаряды летят от героя к цели
+    pub shift: (i32, i32),
     pub progress: TimeProgress,
     pub spell_animation: SpellAnimation,
 }
 
+// Снаряд стрелка, летящий по прямой от стрелка к цели
+pub struct ProjectileAnimation {
+    pub missile: Missile,
+    pub start: Point,
+    pub end: Point,
+    // Снаряд вылетает на середине анимации выстрела
+    pub delay: TimeProgress,
+    pub progress: TimeProgress,
+}
+
 pub struct AnimationState {
     creature: Creature,
     event_queue: VecDeque<AnimationEventByGroup>,
@@ -57,6 +70,14 @@ pub fn process_events(
     animations: &mut Animations,
     rr: &mut ResourceRegistry,
 ) {
+    // Заклинания могут призывать новые отряды
+    for handle in state.units() {
+        animations.creature.entry(handle).or_insert_with(|| {
+            let stack = state.get_stack(handle);
+            AnimationState::new(stack.creature, stack.head, rr)
+        });
+    }
+
     for event in events {
         match event {
             Event::Attack(attack) => choreographer::animate_attack(attack, state, animations, rr),
@@ -65,6 +86,19 @@ pub fn process_events(
                 choreographer::animate_movement(movement, state, animations, rr)
             }
             Event::Cast(cast) => choreographer::animate_cast(cast, state, animations, rr),
+            Event::Defend(defend) => choreographer::animate_defend(defend, state, animations, rr),
+            Event::Heal(heal) => choreographer::animate_heal(heal, state, animations, rr),
+            Event::Bombard(bombard) => {
+                choreographer::animate_bombard(bombard, state, animations, rr)
+            }
+            Event::TowerShot(shot) => {
+                choreographer::animate_tower_shot(shot, state, animations, rr)
+            }
+            Event::MoatDamage(moat) => {
+                choreographer::animate_moat_damage(moat, state, animations, rr)
+            }
+            // Окончание эффекта пока никак не отображается
+            Event::Morale(_) | Event::StatusExpired(_) => {}
         }
     }
 }
@@ -85,6 +119,7 @@ impl Animations {
         Self {
             creature: creature_animations,
             entity: Vec::new(),
+            projectiles: Vec::new(),
         }
     }
 
@@ -96,11 +131,22 @@ impl Animations {
         self.entity.retain_mut(|a| {
             a.progress.update(dt);
             !a.progress.is_finished()
+        });
+
+        self.projectiles.retain_mut(|p| {
+            if p.delay.is_finished() {
+                p.progress.update(dt);
+            } else {
+                p.delay.update(dt);
+            }
+            !p.progress.is_finished()
         })
     }
 
     pub fn is_animating(&self) -> bool {
-        !self.entity.is_empty() || self.creature.values().any(|a| a.is_animating())
+        !self.entity.is_empty()
+            || !self.projectiles.is_empty()
+            || self.creature.values().any(|a| a.is_animating())
     }
 }
 
@@ -200,8 +246,9 @@ impl AnimationState {
         self.current_event.time_left() + queue_duration
     }
 
+    // Последнее событие уже извлечено из очереди, но ещё проигрывается
     pub fn is_animating(&self) -> bool {
-        !self.event_queue.is_empty()
+        !self.event_queue.is_empty() || !self.current_event.is_finished()
     }
 
     fn push_event(&mut self, event: AnimationEvent) {
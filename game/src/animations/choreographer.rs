@@ -3,18 +3,38 @@ use std::time::Duration;
 
 use gamedata::creatures;
 use gamedata::creatures::Creature;
-use gamedata::spells::{Spell, SpellAnimation};
-use logic::event::{Attack, Cast, Movement, Shot};
+use gamedata::spells::{AnimationPlacement, Spell, SpellAnimation};
+use logic::event::{
+    Attack, Bombard, Cast, Defend, Heal, MoatDamage, Movement, Shot, SpellHit, TowerShot,
+};
 use logic::gamestate::{GameState, Side, StackHandle};
-use logic::grid::GridPos;
+use logic::grid::{AttackDirection, GridPos};
 use logic::stack::Stack;
+use sdl2::rect::Point;
 
 use super::animation::Animation;
 use super::events::{AnimationEvent, Sound};
 use super::movement::Movement as MovementEvent;
 use super::time_progress::TimeProgress;
-use super::{AnimationState, Animations, EntityAnimation};
+use super::{AnimationState, Animations, EntityAnimation, ProjectileAnimation};
+use crate::graphics::siege;
 use crate::resources::ResourceRegistry;
+use crate::{gridpos, sound};
+
+// Длительность одного кадра анимации заклинания
+const SPELL_FRAME_DURATION: Duration = Duration::from_millis(70);
+
+// Откуда герой выпускает снаряды: у атакующего и у защищающегося
+const HERO_CAST_POINT: [(i32, i32); 2] = [(90, 130), (710, 130)];
+
+// Эффект поднимается с центра клетки к середине фигуры существа
+const STACK_EFFECT_OFFSET: i32 = -30;
+
+// Скорость полёта снаряда в пикселях в секунду
+const MISSILE_SPEED: f32 = 1200.;
+
+// Выстрелы круче этого угла (в радианах) направлены вверх или вниз
+const SHOOT_STRAIGHT_ANGLE: f32 = std::f32::consts::PI / 8.;
 
 struct StackWithAnimation<'a> {
     stack: &'a Stack,
@@ -70,8 +90,20 @@ pub fn animate_attack(
             (&mut attacker, &mut defender)
         };
 
-        animate_strike(attacker, defender, strike.lethal, rr);
+        let animation_type = attack_animation(
+            attacker.stack.creature,
+            attack.direction,
+            strike.retaliation,
+            rr,
+        );
+        animate_strike(attacker, defender, animation_type, strike.lethal, rr);
         equalize([attacker.animation, defender.animation]);
+
+        // Огненный щит обжигает бьющего после удара
+        if strike.reflected > 0 {
+            animate_get_hit(attacker, strike.reflected_lethal, rr);
+            equalize([attacker.animation, defender.animation]);
+        }
     }
 
     if needs_turning {
@@ -95,7 +127,11 @@ pub fn animate_shot(
 
     equalize([attacker.animation, target.animation]);
 
-    let animation_type = creatures::Animation::ShootStraight;
+    let start = attacker.animation.position.offset(0, STACK_EFFECT_OFFSET);
+    let end = target.animation.position.offset(0, STACK_EFFECT_OFFSET);
+    let shot_start = attacker.animation.total_duration();
+
+    let animation_type = shoot_animation(attacker.stack.creature, start, end, rr);
     let animation = Animation::new(animation_type, attacker.stack.creature, rr);
     let duration = animation.progress().time_left();
 
@@ -106,8 +142,35 @@ pub fn animate_shot(
         rr,
     );
 
-    target.animation.push_event(AnimationEvent::Delay(duration));
+    // Без снаряда попадание совпадает с концом анимации выстрела
+    let mut hit_delay = duration;
+    let mut projectile = None;
+
+    if let Some(missile) = attacker
+        .stack
+        .creature
+        .missile()
+        .filter(|&missile| rr.has_missile(missile))
+    {
+        let launch = duration / 2;
+        let flight = flight_duration(start, end);
+        hit_delay = launch + flight;
+
+        projectile = Some(ProjectileAnimation {
+            missile,
+            start,
+            end,
+            delay: TimeProgress::new(shot_start + launch),
+            progress: TimeProgress::new(flight),
+        });
+    }
+
+    target
+        .animation
+        .push_event(AnimationEvent::Delay(hit_delay));
     animate_get_hit(&mut target, shot.lethal, rr);
+
+    animations.projectiles.extend(projectile);
 }
 
 pub fn animate_movement(
@@ -172,27 +235,184 @@ pub fn animate_movement(
 
 pub fn animate_cast(
     cast: Cast,
-    _state: &GameState,
+    state: &GameState,
     animations: &mut Animations,
     rr: &mut ResourceRegistry,
 ) {
-    if cast.spell == Spell::Armageddon {
-        let sprite = rr
-            .get_spell_animation(SpellAnimation::Armageddon)
-            .get_frame(0)
-            .unwrap();
-
-        for row in 0..4 {
-            for column in 0..3 {
-                let animation = EntityAnimation {
-                    position: (sprite.width as i32 * row, sprite.height as i32 * column),
-                    progress: TimeProgress::new(Duration::from_secs(1)),
-                    spell_animation: SpellAnimation::Armageddon,
-                };
-                animations.entity.push(animation);
-            }
+    if let Some(chunk) = rr.get_spell_sound(cast.spell) {
+        sound::play_sound(chunk, false).unwrap();
+    }
+
+    let duration = cast
+        .spell
+        .animation()
+        .filter(|&spell_animation| rr.has_spell_animation(spell_animation))
+        .map_or(Duration::ZERO, |spell_animation| {
+            animate_spell_effect(spell_animation, &cast, animations, rr)
+        });
+
+    for hit in cast.affected.iter() {
+        let [mut victim] = StackWithAnimation::create_many([hit.stack], state, animations);
+
+        // Отряд реагирует, когда анимация заклинания закончится
+        let delay = duration.saturating_sub(victim.animation.total_duration());
+        victim.animation.push_event(AnimationEvent::Delay(delay));
+
+        if cast.spell.damage(None).is_some() {
+            animate_get_hit(&mut victim, hit.lethal, rr);
+        }
+
+        // Телепортированный отряд переносится на новое место
+        if victim.stack.is_alive()
+            && victim.animation.position != gridpos::center(victim.stack.head)
+        {
+            victim
+                .animation
+                .push_event(AnimationEvent::Teleport(victim.stack.head));
+        }
+    }
+}
+
+// Возвращает длительность анимации
+fn animate_spell_effect(
+    spell_animation: SpellAnimation,
+    cast: &Cast,
+    animations: &mut Animations,
+    rr: &mut ResourceRegistry,
+) -> Duration {
+    let spritesheet = rr.get_spell_animation(spell_animation);
+    let duration = SPELL_FRAME_DURATION * spritesheet.frames_count() as u32;
+    let sprite = spritesheet.get_frame(0).unwrap();
+    let (width, height) = (sprite.width as i32, sprite.height as i32);
+
+    // Анимация рисуется с центром в указанной точке
+    let centered = |point: Point| (point.x() - width / 2, point.y() - height / 2);
+
+    let stack_points: Vec<Point> = cast
+        .affected
+        .iter()
+        .map(|hit| animations.creature[&hit.stack].position)
+        .map(|point| point.offset(0, STACK_EFFECT_OFFSET))
+        .collect();
+
+    let placements: Vec<((i32, i32), (i32, i32))> = match spell_animation.placement() {
+        AnimationPlacement::OnStack => stack_points
+            .into_iter()
+            .map(|point| (centered(point), (0, 0)))
+            .collect(),
+        AnimationPlacement::Area => cast
+            .target
+            .map(|target| (centered(gridpos::center(target)), (0, 0)))
+            .into_iter()
+            .collect(),
+        AnimationPlacement::FullScreen => (0..4)
+            .flat_map(|row| (0..3).map(move |column| ((width * row, height * column), (0, 0))))
+            .collect(),
+        AnimationPlacement::Projectile => {
+            let start = Point::from(HERO_CAST_POINT[cast.side as usize]);
+            stack_points
+                .into_iter()
+                .map(|point| {
+                    let shift = point - start;
+                    (centered(start), (shift.x(), shift.y()))
+                })
+                .collect()
         }
+    };
+
+    for (position, shift) in placements {
+        animations.entity.push(EntityAnimation {
+            position,
+            shift,
+            progress: TimeProgress::new(duration),
+            spell_animation,
+        });
     }
+
+    duration
+}
+
+pub fn animate_defend(
+    defend: Defend,
+    state: &GameState,
+    animations: &mut Animations,
+    rr: &mut ResourceRegistry,
+) {
+    let [stack] = StackWithAnimation::create_many([defend.stack], state, animations);
+
+    put_animation_with_sound(
+        stack.animation,
+        creatures::Animation::Defend,
+        stack.stack.creature,
+        rr,
+    );
+}
+
+// Лечение палатки выглядит как заклинание Лечение на цели
+pub fn animate_heal(
+    heal: Heal,
+    state: &GameState,
+    animations: &mut Animations,
+    rr: &mut ResourceRegistry,
+) {
+    let healer = state.get_stack(heal.healer);
+    let target = state.get_stack(heal.target);
+
+    let cast = Cast {
+        spell: Spell::Cure,
+        side: healer.side,
+        target: Some(target.head),
+        affected: vec![SpellHit {
+            stack: heal.target,
+            damage: 0,
+            lethal: false,
+        }],
+        resisted: vec![],
+    };
+
+    animate_cast(cast, state, animations, rr);
+}
+
+// Катапульта стреляет в сторону выбранной части стены
+pub fn animate_bombard(
+    bombard: Bombard,
+    state: &GameState,
+    animations: &mut Animations,
+    rr: &mut ResourceRegistry,
+) {
+    let [catapult] = StackWithAnimation::create_many([bombard.catapult], state, animations);
+
+    let start = catapult.animation.position;
+    let end = siege::section_anchor(state, bombard.section);
+    let animation_type = shoot_animation(catapult.stack.creature, start, end, rr);
+
+    put_animation_with_sound(
+        catapult.animation,
+        animation_type,
+        catapult.stack.creature,
+        rr,
+    );
+}
+
+// Стрелы башен не рисуются, видно только попадание
+pub fn animate_tower_shot(
+    shot: TowerShot,
+    state: &GameState,
+    animations: &mut Animations,
+    rr: &mut ResourceRegistry,
+) {
+    let [mut target] = StackWithAnimation::create_many([shot.target], state, animations);
+    animate_get_hit(&mut target, shot.lethal, rr);
+}
+
+pub fn animate_moat_damage(
+    moat: MoatDamage,
+    state: &GameState,
+    animations: &mut Animations,
+    rr: &mut ResourceRegistry,
+) {
+    let [mut stack] = StackWithAnimation::create_many([moat.stack], state, animations);
+    animate_get_hit(&mut stack, moat.lethal, rr);
 }
 
 fn equalize<const N: usize>(animation_states: [&mut AnimationState; N]) {
@@ -210,10 +430,10 @@ fn equalize<const N: usize>(animation_states: [&mut AnimationState; N]) {
 fn animate_strike(
     attacker: &mut StackWithAnimation,
     defender: &mut StackWithAnimation,
+    animation_type: creatures::Animation,
     lethal: bool,
     rr: &mut ResourceRegistry,
 ) {
-    let animation_type = creatures::Animation::AttackStraight;
     let animation = Animation::new(animation_type, attacker.stack.creature, rr);
     let animation_duration = animation.progress().time_left();
 
@@ -242,6 +462,77 @@ fn animate_get_hit(victim: &mut StackWithAnimation, lethal: bool, rr: &mut Resou
     put_animation_with_sound(victim.animation, animation_type, victim.stack.creature, rr);
 }
 
+// Атакующий сверху бьёт вниз, отвечающий на удар - в обратную сторону
+// Существа с дыханием используют анимации атаки на две клетки
+fn attack_animation(
+    creature: Creature,
+    direction: AttackDirection,
+    retaliation: bool,
+    rr: &mut ResourceRegistry,
+) -> creatures::Animation {
+    use creatures::Animation::*;
+
+    let (strike, response) = match direction {
+        AttackDirection::TopLeft | AttackDirection::Top | AttackDirection::TopRight => {
+            (AttackDown, AttackUp)
+        }
+        AttackDirection::BottomLeft | AttackDirection::Bottom | AttackDirection::BottomRight => {
+            (AttackUp, AttackDown)
+        }
+        AttackDirection::Left | AttackDirection::Right => (AttackStraight, AttackStraight),
+    };
+
+    let animation_type = if retaliation { response } else { strike };
+
+    let animation_type = match (creature.has_reach(), animation_type) {
+        (true, AttackUp) => TwoHexAttackUp,
+        (true, AttackDown) => TwoHexAttackDown,
+        (true, _) => TwoHexAttackStraight,
+        (false, animation_type) => animation_type,
+    };
+
+    let spritesheet = rr.get_creature_spritesheet(creature);
+    [animation_type, AttackStraight]
+        .into_iter()
+        .find(|&animation_type| spritesheet.has_animation(animation_type))
+        .unwrap_or(AttackStraight)
+}
+
+// Направление выстрела выбирается по углу наклона траектории
+fn shoot_animation(
+    creature: Creature,
+    start: Point,
+    end: Point,
+    rr: &mut ResourceRegistry,
+) -> creatures::Animation {
+    let shift = end - start;
+    let angle = f32::atan2(shift.y() as f32, shift.x().abs() as f32);
+
+    let animation_type = if angle < -SHOOT_STRAIGHT_ANGLE {
+        creatures::Animation::ShootUp
+    } else if angle > SHOOT_STRAIGHT_ANGLE {
+        creatures::Animation::ShootDown
+    } else {
+        creatures::Animation::ShootStraight
+    };
+
+    if rr
+        .get_creature_spritesheet(creature)
+        .has_animation(animation_type)
+    {
+        animation_type
+    } else {
+        creatures::Animation::ShootStraight
+    }
+}
+
+fn flight_duration(start: Point, end: Point) -> Duration {
+    let shift = end - start;
+    let distance = f32::hypot(shift.x() as f32, shift.y() as f32);
+
+    Duration::from_secs_f32(distance / MISSILE_SPEED)
+}
+
 fn animate_turning(stack: &mut StackWithAnimation, rr: &mut ResourceRegistry) {
     put_animation_with_sound(
         stack.animation,
@@ -280,8 +571,18 @@ fn facing_side(pos: GridPos, target: GridPos) -> Side {
     }
 }
 
+// Широкие отряды разворачиваются по ближайшим друг к другу клеткам, а не по головам
 fn needs_turning(attacker: &Stack, defender: &Stack) -> bool {
-    facing_side(attacker.head, defender.head) != attacker.side
+    let defender_cells = defender.get_occupied_cells();
+
+    let (from, to) = attacker
+        .get_occupied_cells()
+        .into_iter()
+        .flat_map(|from| defender_cells.iter().map(move |&to| (from, to)))
+        .min_by_key(|&(from, to)| from.distance(to))
+        .unwrap();
+
+    facing_side(from, to) != attacker.side
 }
 
 fn put_animation_with_sound(
@@ -303,12 +604,19 @@ fn put_animation_with_sound(
 
 fn sound_for_animation(animation_type: creatures::Animation) -> Option<creatures::Sound> {
     match animation_type {
-        creatures::Animation::AttackStraight => Some(creatures::Sound::Attack),
+        creatures::Animation::AttackUp
+        | creatures::Animation::AttackStraight
+        | creatures::Animation::AttackDown
+        | creatures::Animation::TwoHexAttackUp
+        | creatures::Animation::TwoHexAttackStraight
+        | creatures::Animation::TwoHexAttackDown => Some(creatures::Sound::Attack),
         creatures::Animation::Defend => Some(creatures::Sound::Defend),
         creatures::Animation::StartMoving => Some(creatures::Sound::StartMoving),
         creatures::Animation::Moving => Some(creatures::Sound::Move),
         creatures::Animation::StopMoving => Some(creatures::Sound::EndMoving),
-        creatures::Animation::ShootStraight => Some(creatures::Sound::Shoot),
+        creatures::Animation::ShootUp
+        | creatures::Animation::ShootStraight
+        | creatures::Animation::ShootDown => Some(creatures::Sound::Shoot),
         creatures::Animation::GettingHit => Some(creatures::Sound::Wince),
         creatures::Animation::Death => Some(creatures::Sound::Killed),
         _ => None,
@@ -92,6 +92,6 @@ impl Idle {
     fn is_finished(&self) -> bool {
         self.delay
             .as_ref()
-            .map_or(true, |progress| progress.is_finished())
+            .is_none_or(|progress| progress.is_finished())
     }
 }
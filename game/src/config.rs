@@ -1,6 +1,7 @@
 use std::fs::File;
 
 use gamedata::battlefields::Battlefield;
+use logic::ai::Controller;
 use logic::gamestate::Army;
 use serde::Deserialize;
 
@@ -12,6 +13,15 @@ pub struct Config {
     pub music: bool,
     pub volume: i32,
     pub armies: [Army; 2],
+    // Без указания обеими сторонами управляют люди
+    #[serde(default)]
+    pub controllers: [Controller; 2],
+    // Битва сразу доигрывается компьютером и показывается только итог
+    #[serde(default)]
+    pub quick_combat: bool,
+    // Зерно случайности битвы, чтобы её можно было повторить
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 impl Config {
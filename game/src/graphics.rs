@@ -1,24 +1,27 @@
 use common::error::AnyWay;
 use gamedata::cursors::Cursor;
-use gamedata::gui::{ButtonState, Texture};
+use gamedata::gui::{Button, ButtonState, Texture};
 use gamedata::heroes;
+use gamedata::spells::{Spell, SpellTarget};
 use logic::command::Command;
-use logic::gamestate::{GameState, Side};
+use logic::gamestate::{BattleOutcome, GameState, Side, TurnOrder};
 use logic::grid::GridPos;
 use logic::pathfinding;
-use sdl2::rect::Rect;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
 use sdl2::render::{TextureCreator, WindowCanvas};
 use sdl2::video::WindowContext;
 use strum::IntoEnumIterator;
 
 use crate::animations::Animations;
-use crate::input::FrameData;
+use crate::input::{FrameData, Targeting};
 use crate::resources::ResourceRegistry;
-use crate::{animations, gridpos, Graphics, Stage};
+use crate::{animations, gridpos, gui, Graphics, Stage};
 
 pub mod creature;
 mod cursors;
 mod hero;
+pub mod siege;
 pub mod stack;
 pub mod statics;
 
@@ -42,21 +45,27 @@ pub fn draw(
 
     draw_battlefield(canvas, statics)?;
 
+    siege::draw_walls(canvas, statics, state)?;
+
     draw_heroes(canvas, tc, statics)?;
 
     let is_animating = animations.is_animating();
 
-    set_cursor(&statics.cursors, state, frame_data, is_animating);
+    set_cursor(&statics.cursors, state, frame_data, stage, is_animating);
 
     if !is_animating {
-        highlight_cells(canvas, statics, state, frame_data)?;
+        highlight_cells(canvas, statics, state, frame_data, stage)?;
     }
 
     draw_units(canvas, tc, statics, rr, state, animations)?;
 
     draw_entities(canvas, tc, rr, animations)?;
 
-    draw_gui(canvas, tc, statics, stage, shapes)?;
+    draw_projectiles(canvas, tc, rr, animations)?;
+
+    draw_turn_order(canvas, tc, statics, state)?;
+
+    draw_gui(canvas, tc, statics, state, stage, shapes)?;
 
     Ok(())
 }
@@ -100,7 +109,21 @@ fn highlight_cells(
     statics: &Statics,
     state: &GameState,
     frame_data: &FrameData,
+    stage: &Stage,
 ) -> AnyWay {
+    if let Stage::Targeting(targeting) = stage {
+        for cell in state.get_spell_target_cells(targeting.spell) {
+            highlight_cell(canvas, statics, cell)?;
+        }
+
+        // Повторная подсветка затемняет клетки, которые заденет заклинание
+        for cell in gather_spell_preview_cells(state, frame_data, targeting) {
+            highlight_cell(canvas, statics, cell)?;
+        }
+
+        return Ok(());
+    }
+
     for cell in state.reachable_cells() {
         highlight_cell(canvas, statics, *cell)?;
     }
@@ -112,6 +135,44 @@ fn highlight_cells(
     Ok(())
 }
 
+fn gather_spell_preview_cells(
+    state: &GameState,
+    frame_data: &FrameData,
+    targeting: &Targeting,
+) -> Vec<GridPos> {
+    let stack_cells = |cell| {
+        state
+            .find_unit_for_cell(cell)
+            .or_else(|| state.find_corpse_for_cell(cell))
+            .map_or(vec![cell], |handle| {
+                state.get_stack(handle).get_occupied_cells()
+            })
+    };
+
+    let mut cells = vec![];
+
+    if let Some(target) = targeting.target {
+        cells.extend(stack_cells(target));
+    }
+
+    let is_valid = frame_data.potential_lmb_command.is_some()
+        || targeting.is_valid_target(state, frame_data.current_hover);
+
+    if let Some(hover) = frame_data.current_hover.filter(|_| is_valid) {
+        match state.get_spell_target(targeting.spell) {
+            Some(SpellTarget::Area { .. }) => {
+                cells.extend(state.get_spell_area(targeting.spell, hover))
+            }
+            _ if targeting.target.is_some() => cells.push(hover),
+            _ => cells.extend(stack_cells(hover)),
+        }
+    }
+
+    cells.sort();
+    cells.dedup();
+    cells
+}
+
 fn highlight_cell(
     canvas: &mut WindowCanvas,
     statics: &Statics,
@@ -152,10 +213,81 @@ fn draw_units(
     Ok(())
 }
 
+// Очередь ходов вдоль нижнего края поля, раунды разделены промежутком
+fn draw_turn_order(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    statics: &Statics,
+    state: &GameState,
+) -> AnyWay {
+    const PORTRAIT_SIZE: (u32, u32) = (32, 32);
+    const BORDER: i32 = 2;
+    const STEP: i32 = PORTRAIT_SIZE.0 as i32 + BORDER * 2 + 1;
+    const ROUND_GAP: i32 = 12;
+    const TOP: i32 = 556 - PORTRAIT_SIZE.1 as i32 - BORDER * 2;
+
+    let TurnOrder {
+        current_round,
+        next_round,
+    } = state.get_turn_order();
+
+    let previous_color = canvas.draw_color();
+
+    let mut x = BORDER;
+    for (round, handles) in [current_round, next_round].into_iter().enumerate() {
+        if round > 0 {
+            x += ROUND_GAP;
+        }
+
+        for queued in handles {
+            if x + STEP > 800 {
+                break;
+            }
+
+            // Рамка цвета стороны, как у флагов героев
+            let stack = state.get_stack(queued.stack);
+            let color = match stack.side {
+                Side::Attacker => Color::RGB(200, 30, 30),
+                Side::Defender => Color::RGB(30, 60, 200),
+            };
+            canvas.set_draw_color(color);
+            canvas.fill_rect(Rect::new(
+                x,
+                TOP,
+                PORTRAIT_SIZE.0 + BORDER as u32 * 2,
+                PORTRAIT_SIZE.1 + BORDER as u32 * 2,
+            ))?;
+
+            let frame = stack.creature.portrait_index();
+            if let Some(sprite) = statics.portraits.get_frame(frame) {
+                let texture = sprite.surface.as_texture(tc)?;
+                canvas.copy(
+                    &texture,
+                    None,
+                    Rect::new(x + BORDER, TOP + BORDER, PORTRAIT_SIZE.0, PORTRAIT_SIZE.1),
+                )?;
+            }
+
+            // Золотой уголок: боевой дух может дать отряду ещё один ход
+            if queued.morale_chance {
+                canvas.set_draw_color(Color::RGB(230, 190, 40));
+                canvas.fill_rect(Rect::new(x + BORDER, TOP + BORDER, 6, 6))?;
+            }
+
+            x += STEP;
+        }
+    }
+
+    canvas.set_draw_color(previous_color);
+
+    Ok(())
+}
+
 fn draw_gui(
     canvas: &mut WindowCanvas,
     tc: &TextureCreator<WindowContext>,
     statics: &Statics,
+    state: &GameState,
     stage: &Stage,
     shapes: Vec<(Rect, Texture)>,
 ) -> AnyWay {
@@ -167,36 +299,425 @@ fn draw_gui(
 
     for (rect, texture) in shapes.iter() {
         match *texture {
+            // Кнопки диалога рисуются поверх его фона
+            Texture::Button(Button::Ok | Button::Cancel, _) => {}
             Texture::Button(button, _state) => {
                 let sprite = statics.ui.get(button).get(ButtonState::Base);
                 let texture = sprite.surface.as_texture(tc)?;
 
                 canvas.copy(&texture, None, *rect)?;
             }
-            Texture::Spell(_) => {}
+            Texture::Spell(_) | Texture::SpellBookTab(_) => {}
         }
     }
 
-    if matches!(stage, Stage::SpellBook) {
-        canvas.copy(
-            statics.textures.get(StaticTexture::SpellBook),
-            None,
-            sdl2::rect::Rect::new(400 - 310, 2, 620, 595),
+    if let Stage::Confirmation(command) = *stage {
+        draw_confirmation(canvas, tc, statics, state, command, &shapes)?;
+    }
+
+    if let Stage::HeroInfo(side) = *stage {
+        draw_hero_info(canvas, tc, statics, state, side, &shapes)?;
+    }
+
+    if matches!(stage, Stage::Results) {
+        draw_results(canvas, tc, statics, state, &shapes)?;
+    }
+
+    if matches!(stage, Stage::Tactics) {
+        let (x, y) = gui::TACTICS_BUTTON_POSITION;
+        draw_text(
+            canvas,
+            tc,
+            statics,
+            "Tactics: place your stacks and press OK",
+            Point::new(x as i32 + 32, y as i32 - 14),
+            Color::WHITE,
         )?;
+        draw_dialog_buttons(canvas, tc, statics, &shapes)?;
     }
 
+    if !matches!(stage, Stage::SpellBook(_)) {
+        return Ok(());
+    }
+
+    let (book_x, book_y) = gui::BOOK_POSITION;
+    canvas.copy(
+        statics.textures.get(StaticTexture::SpellBook),
+        None,
+        sdl2::rect::Rect::new(book_x, book_y, 620, 595),
+    )?;
+
+    let side = state.get_current_stack().side;
+    let text_color = Color::RGB(50, 30, 10);
+
     for (rect, texture) in shapes.iter() {
         match *texture {
             Texture::Button(..) => {}
             Texture::Spell(spell) => {
                 let sprite = statics.spells.get(spell);
-                let texture = sprite.surface.as_texture(tc)?;
+                let mut texture = sprite.surface.as_texture(tc)?;
+
+                // Недоступные сейчас заклинания затемнены
+                if !state.is_spell_available(spell) {
+                    texture.set_color_mod(96, 96, 96);
+                }
 
                 canvas.copy(&texture, None, *rect)?;
+
+                // Вместо цены нереализованных заклинаний пишем, почему их нельзя применить
+                let cost = if state.is_spell_supported(spell) {
+                    state
+                        .get_hero_spell_cost(side, spell)
+                        .unwrap_or(0)
+                        .to_string()
+                } else {
+                    "Not supported".to_string()
+                };
+                let label_center = rect.center().offset(0, rect.height() as i32 / 2 + 10);
+                draw_text(
+                    canvas,
+                    tc,
+                    statics,
+                    &format!("{spell:?}"),
+                    label_center,
+                    text_color,
+                )?;
+                draw_text(
+                    canvas,
+                    tc,
+                    statics,
+                    &cost,
+                    label_center.offset(0, 16),
+                    text_color,
+                )?;
+            }
+            Texture::SpellBookTab(tab) => {
+                let sprite = statics.spell_book_tabs.get(tab);
+                let texture = sprite.surface.as_texture(tc)?;
+
+                canvas.copy(
+                    &texture,
+                    None,
+                    Rect::new(rect.x(), rect.y(), sprite.width, sprite.height),
+                )?;
             }
         }
     }
 
+    let spell_points = state.get_hero_spell_points(side).unwrap_or(0);
+    draw_text(
+        canvas,
+        tc,
+        statics,
+        &format!("Mana: {spell_points}"),
+        Point::new(book_x + 435, book_y + 426),
+        text_color,
+    )?;
+
+    Ok(())
+}
+
+fn draw_confirmation(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    statics: &Statics,
+    state: &GameState,
+    command: Command,
+    shapes: &[(Rect, Texture)],
+) -> AnyWay {
+    let (x, y) = gui::DIALOG_POSITION;
+    let (width, height) = gui::DIALOG_SIZE;
+    let dialog = Rect::new(x as i32, y as i32, width as u32, height as u32);
+
+    let previous_color = canvas.draw_color();
+    canvas.set_draw_color(Color::RGB(180, 150, 90));
+    canvas.fill_rect(dialog)?;
+    canvas.set_draw_color(Color::RGB(60, 40, 20));
+    canvas.draw_rect(dialog)?;
+    canvas.set_draw_color(previous_color);
+
+    let text = match command {
+        Command::Surrender => {
+            let cost = state.get_surrender_cost();
+            format!("Surrender for {cost} gold?")
+        }
+        _ => "Retreat? The army will be lost.".to_owned(),
+    };
+    draw_text(
+        canvas,
+        tc,
+        statics,
+        &text,
+        Point::new(dialog.center().x(), dialog.y() + 40),
+        Color::RGB(50, 30, 10),
+    )?;
+
+    draw_dialog_buttons(canvas, tc, statics, shapes)
+}
+
+fn draw_hero_info(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    statics: &Statics,
+    state: &GameState,
+    side: Side,
+    shapes: &[(Rect, Texture)],
+) -> AnyWay {
+    const ARTIFACT_SIZE: u32 = 44;
+    const ARTIFACTS_PER_ROW: usize = 8;
+    const ARTIFACT_ROWS: usize = 3;
+
+    let Some(info) = state.get_hero_info(side) else {
+        return Ok(());
+    };
+
+    let (x, y) = gui::HERO_INFO_POSITION;
+    let (width, height) = gui::HERO_INFO_SIZE;
+    let window = Rect::new(x as i32, y as i32, width as u32, height as u32);
+
+    let previous_color = canvas.draw_color();
+    canvas.set_draw_color(Color::RGB(180, 150, 90));
+    canvas.fill_rect(window)?;
+    canvas.set_draw_color(Color::RGB(60, 40, 20));
+    canvas.draw_rect(window)?;
+    canvas.set_draw_color(previous_color);
+
+    let stats = info.stats;
+    let mut lines = vec![
+        format!("{:?}, level {}", info.hero, info.level),
+        format!(
+            "Attack {}  Defence {}  Power {}  Knowledge {}",
+            stats.attack, stats.defence, stats.power, stats.knowledge
+        ),
+        format!(
+            "Morale {:+}  Luck {:+}  Mana {}/{} ({:+} per day)",
+            info.morale, info.luck, info.spell_points, info.max_spell_points, info.daily_mana
+        ),
+    ];
+    lines.extend(
+        info.abilities
+            .iter()
+            .map(|(ability, level)| format!("{:?}: {:?}", ability, level)),
+    );
+
+    let text_color = Color::RGB(50, 30, 10);
+    for (i, line) in lines.iter().enumerate() {
+        let center = Point::new(window.center().x(), window.y() + 24 + i as i32 * 22);
+        draw_text(canvas, tc, statics, line, center, text_color)?;
+    }
+
+    // Значки артефактов рядами под навыками
+    let row_width = (ARTIFACTS_PER_ROW as u32 * ARTIFACT_SIZE) as i32;
+    let artifacts_x = window.center().x() - row_width / 2;
+    let artifacts_y = window.bottom() - 50 - (ARTIFACT_ROWS as u32 * ARTIFACT_SIZE) as i32;
+
+    let shown = info
+        .artifacts
+        .iter()
+        .take(ARTIFACTS_PER_ROW * ARTIFACT_ROWS);
+    for (i, artifact) in shown.enumerate() {
+        let Some(sprite) = statics.artifacts.get_frame(artifact.icon_index()) else {
+            continue;
+        };
+
+        let column = (i % ARTIFACTS_PER_ROW) as i32;
+        let row = (i / ARTIFACTS_PER_ROW) as i32;
+        let texture = sprite.surface.as_texture(tc)?;
+        canvas.copy(
+            &texture,
+            None,
+            Rect::new(
+                artifacts_x + column * ARTIFACT_SIZE as i32,
+                artifacts_y + row * ARTIFACT_SIZE as i32,
+                ARTIFACT_SIZE,
+                ARTIFACT_SIZE,
+            ),
+        )?;
+    }
+
+    draw_dialog_buttons(canvas, tc, statics, shapes)
+}
+
+// Как в оригинале: исход, опыт героя и портреты павших существ каждой стороны
+fn draw_results(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    statics: &Statics,
+    state: &GameState,
+    shapes: &[(Rect, Texture)],
+) -> AnyWay {
+    const PORTRAIT_SIZE: u32 = 32;
+    const PORTRAIT_STEP: i32 = 40;
+    const PORTRAITS_PER_ROW: usize = 12;
+    const LINE_HEIGHT: i32 = 22;
+
+    let Some(report) = state.get_battle_report() else {
+        return Ok(());
+    };
+
+    let (x, y) = gui::RESULTS_POSITION;
+    let (width, height) = gui::RESULTS_SIZE;
+    let window = Rect::new(x as i32, y as i32, width as u32, height as u32);
+
+    let previous_color = canvas.draw_color();
+    canvas.set_draw_color(Color::RGB(180, 150, 90));
+    canvas.fill_rect(window)?;
+    canvas.set_draw_color(Color::RGB(60, 40, 20));
+    canvas.draw_rect(window)?;
+    canvas.set_draw_color(previous_color);
+
+    let text_color = Color::RGB(50, 30, 10);
+    let center_x = window.center().x();
+    let mut line_y = window.y() + 24;
+
+    let title = match report.outcome {
+        BattleOutcome::Victory { winner } => format!("Victory: {:?} wins", winner),
+        BattleOutcome::Tie => "Both armies perished".to_string(),
+        BattleOutcome::Retreat { side } => format!("{:?} retreats", side),
+        BattleOutcome::Surrender { side, cost } => {
+            format!("{:?} surrenders for {} gold", side, cost)
+        }
+    };
+    draw_text(
+        canvas,
+        tc,
+        statics,
+        &title,
+        Point::new(center_x, line_y),
+        text_color,
+    )?;
+
+    for (side, side_report) in Side::iter().zip(report.sides.iter()) {
+        line_y += LINE_HEIGHT * 3 / 2;
+
+        let commander = side_report
+            .hero
+            .map_or("no hero".to_string(), |hero| format!("{:?}", hero));
+        let heading = format!("{:?} ({})", side, commander);
+        draw_text(
+            canvas,
+            tc,
+            statics,
+            &heading,
+            Point::new(center_x, line_y),
+            text_color,
+        )?;
+
+        let mut lines = vec![];
+        if side_report.experience > 0 {
+            lines.push(format!("Experience: +{}", side_report.experience));
+        }
+        if let Some((creature, count)) = side_report.raised {
+            lines.push(format!("Raised: {:?} x{}", creature, count));
+        }
+        if !side_report.resurrected.is_empty() {
+            let resurrected: Vec<String> = side_report
+                .resurrected
+                .iter()
+                .map(|(creature, count)| format!("{:?} x{}", creature, count))
+                .collect();
+            lines.push(format!("Resurrected: {}", resurrected.join(", ")));
+        }
+        if !side_report.learned_spells.is_empty() {
+            let spells: Vec<String> = side_report
+                .learned_spells
+                .iter()
+                .map(|spell| format!("{:?}", spell))
+                .collect();
+            lines.push(format!("Learned: {}", spells.join(", ")));
+        }
+        lines.push(if side_report.casualties.is_empty() {
+            "Casualties: none".to_string()
+        } else {
+            "Casualties:".to_string()
+        });
+
+        for line in lines {
+            line_y += LINE_HEIGHT;
+            draw_text(
+                canvas,
+                tc,
+                statics,
+                &line,
+                Point::new(center_x, line_y),
+                text_color,
+            )?;
+        }
+
+        // Портреты с числом павших под каждым, рядами по центру окна
+        for row in side_report.casualties.chunks(PORTRAITS_PER_ROW) {
+            line_y += LINE_HEIGHT / 2;
+            let row_width = row.len() as i32 * PORTRAIT_STEP;
+            let mut portrait_x =
+                center_x - row_width / 2 + (PORTRAIT_STEP - PORTRAIT_SIZE as i32) / 2;
+
+            for &(creature, count) in row {
+                if let Some(sprite) = statics.portraits.get_frame(creature.portrait_index()) {
+                    let texture = sprite.surface.as_texture(tc)?;
+                    canvas.copy(
+                        &texture,
+                        None,
+                        Rect::new(portrait_x, line_y, PORTRAIT_SIZE, PORTRAIT_SIZE),
+                    )?;
+                }
+
+                let count_center = Point::new(
+                    portrait_x + PORTRAIT_SIZE as i32 / 2,
+                    line_y + PORTRAIT_SIZE as i32 + 8,
+                );
+                draw_text(
+                    canvas,
+                    tc,
+                    statics,
+                    &count.to_string(),
+                    count_center,
+                    text_color,
+                )?;
+
+                portrait_x += PORTRAIT_STEP;
+            }
+
+            line_y += PORTRAIT_SIZE as i32 + 16;
+        }
+    }
+
+    draw_dialog_buttons(canvas, tc, statics, shapes)
+}
+
+fn draw_dialog_buttons(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    statics: &Statics,
+    shapes: &[(Rect, Texture)],
+) -> AnyWay {
+    for (rect, texture) in shapes.iter() {
+        if let Texture::Button(button @ (Button::Ok | Button::Cancel), _) = *texture {
+            let sprite = statics.ui.get(button).get(ButtonState::Base);
+            let texture = sprite.surface.as_texture(tc)?;
+
+            canvas.copy(&texture, None, *rect)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn draw_text(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    statics: &Statics,
+    text: &str,
+    center: Point,
+    color: Color,
+) -> AnyWay {
+    let font_surface = statics.font.render(text).blended(color)?;
+    let font_texture = font_surface.as_texture(tc)?;
+
+    let mut font_rect = font_surface.rect();
+    font_rect.center_on(center);
+
+    canvas.copy(&font_texture, None, font_rect)?;
+
     Ok(())
 }
 
@@ -212,12 +733,16 @@ fn draw_entities(
         let sprite = spell_animation.get_frame(frame as usize).unwrap();
         let texture = sprite.surface.as_texture(tc)?;
 
+        let progress = animation.progress.progress();
+        let (x, y) = animation.position;
+        let (shift_x, shift_y) = animation.shift;
+
         canvas.copy(
             &texture,
             None,
             Rect::new(
-                animation.position.0,
-                animation.position.1,
+                x + (shift_x as f32 * progress) as i32,
+                y + (shift_y as f32 * progress) as i32,
                 sprite.width,
                 sprite.height,
             ),
@@ -226,12 +751,65 @@ fn draw_entities(
     Ok(())
 }
 
-fn set_cursor(cursors: &Cursors, state: &GameState, frame_data: &FrameData, is_animating: bool) {
-    if is_animating {
+fn draw_projectiles(
+    canvas: &mut WindowCanvas,
+    tc: &TextureCreator<WindowContext>,
+    rr: &mut ResourceRegistry,
+    animations: &Animations,
+) -> AnyWay {
+    for projectile in animations.projectiles.iter() {
+        if !projectile.delay.is_finished() {
+            continue;
+        }
+
+        let shift = projectile.end - projectile.start;
+        let progress = projectile.progress.progress();
+        let position = projectile.start.offset(
+            (shift.x() as f32 * progress) as i32,
+            (shift.y() as f32 * progress) as i32,
+        );
+
+        // Кадры снаряда повёрнуты от направления вверх до направления вниз,
+        // летящий влево снаряд отражается по горизонтали
+        let angle = f32::atan2(shift.y() as f32, shift.x().abs() as f32);
+        let missile = rr.get_missile(projectile.missile);
+        let max_frame = missile.frames_count().saturating_sub(1);
+        let frame = ((angle / std::f32::consts::PI + 0.5) * max_frame as f32).round() as usize;
+        let sprite = missile.get_frame(frame).unwrap();
+        let texture = sprite.surface.as_texture(tc)?;
+
+        canvas.copy_ex(
+            &texture,
+            None,
+            Rect::from_center(position, sprite.width, sprite.height),
+            0.0,
+            None,
+            shift.x() < 0,
+            false,
+        )?;
+    }
+    Ok(())
+}
+
+fn set_cursor(
+    cursors: &Cursors,
+    state: &GameState,
+    frame_data: &FrameData,
+    stage: &Stage,
+    is_animating: bool,
+) {
+    let is_menu_open = matches!(stage, Stage::SpellBook(_) | Stage::Confirmation(_))
+        || stage.is_auto_battle_for(state.get_current_stack().side);
+    if is_animating || is_menu_open {
         cursors.get(Cursor::Pointer).set();
         return;
     }
 
+    if let Stage::Targeting(targeting) = stage {
+        set_spell_cursor(cursors, state, frame_data, targeting);
+        return;
+    }
+
     let cursor = if let Some(command) = frame_data.potential_lmb_command {
         match command {
             Command::Move { .. } => {
@@ -243,6 +821,8 @@ fn set_cursor(cursors: &Cursors, state: &GameState, frame_data: &FrameData, is_a
             }
             Command::Attack(command) => cursors::from_attack_direction(command.attack_direction),
             Command::Shoot { .. } => Cursor::Arrow,
+            Command::Heal { .. } => Cursor::Heal,
+            Command::Bombard { .. } => Cursor::Catapult,
             _ => unreachable!(),
         }
     } else {
@@ -253,6 +833,28 @@ fn set_cursor(cursors: &Cursors, state: &GameState, frame_data: &FrameData, is_a
     sdl_cursor.set();
 }
 
+fn set_spell_cursor(
+    cursors: &Cursors,
+    state: &GameState,
+    frame_data: &FrameData,
+    targeting: &Targeting,
+) {
+    let is_valid = frame_data.potential_lmb_command.is_some()
+        || targeting.is_valid_target(state, frame_data.current_hover);
+
+    if !is_valid {
+        cursors.get(Cursor::Forbidden).set();
+        return;
+    }
+
+    // Вторая клетка Телепорта и Жертвы выбирается собственным курсором
+    match (targeting.spell, targeting.target) {
+        (Spell::Teleport, Some(_)) => cursors.get(Cursor::Teleport).set(),
+        (Spell::Sacrifice, Some(_)) => cursors.get(Cursor::Sacrifice).set(),
+        _ => cursors.spell().set(),
+    }
+}
+
 fn gather_highlighted_cells(state: &GameState, frame_data: &FrameData) -> Vec<GridPos> {
     let mut highlighted_cells = vec![];
 
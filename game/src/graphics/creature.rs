@@ -9,6 +9,7 @@ use sdl2::video::{Window, WindowContext};
 use crate::resources::spritesheets::{AnimationGroup, Sprite};
 
 impl AnimationGroup<creatures::Animation> {
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &self,
         canvas: &mut Canvas<Window>,
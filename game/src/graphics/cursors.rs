@@ -18,7 +18,10 @@ pub fn from_attack_direction(attack_direction: AttackDirection) -> Cursor {
     }
 }
 
-pub struct Cursors(Box<[SDLCursor]>);
+pub struct Cursors {
+    combat: Box<[SDLCursor]>,
+    spell: SDLCursor,
+}
 
 impl Cursors {
     pub fn load(rr: &mut ResourceRegistry) -> Self {
@@ -35,10 +38,22 @@ impl Cursors {
             })
             .collect();
 
-        Self(cursors)
+        let spell_animation = rr.load_animation::<70>(Cursor::SPELL_CONTAINER_FILENAME);
+        let spell_sprite = spell_animation.get_frame(0).unwrap();
+        let (off_x, off_y) = Cursor::SPELL_POINTER_OFFSET;
+        let spell = SDLCursor::from_surface(&spell_sprite.surface, off_x, off_y).unwrap();
+
+        Self {
+            combat: cursors,
+            spell,
+        }
     }
 
     pub fn get(&self, cursor: Cursor) -> &SDLCursor {
-        &self.0[cursor as usize]
+        &self.combat[cursor as usize]
+    }
+
+    pub fn spell(&self) -> &SDLCursor {
+        &self.spell
     }
 }
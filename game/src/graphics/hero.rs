@@ -1,9 +1,10 @@
 use gamedata::heroes;
 use logic::gamestate::Side;
-use sdl2::rect::{Point, Rect};
+use sdl2::rect::Rect;
 use sdl2::render::{Canvas, TextureCreator};
 use sdl2::video::{Window, WindowContext};
 
+use crate::gui;
 use crate::resources::spritesheets::{AnimationGroup, Sprite};
 
 impl AnimationGroup<heroes::Animation> {
@@ -29,18 +30,6 @@ impl AnimationGroup<heroes::Animation> {
 }
 
 fn draw_rect(sprite: &Sprite, side: Side) -> Rect {
-    const FULL_WIDTH: u32 = 150;
-    const FULL_HEIGHT: u32 = 175;
-
-    const Y: i32 = 75;
-
-    let x = match side {
-        Side::Attacker => 50,
-        Side::Defender => 785,
-    };
-
-    let center = Point::new(x, Y);
-
     let Sprite {
         left_margin,
         top_margin,
@@ -49,7 +38,7 @@ fn draw_rect(sprite: &Sprite, side: Side) -> Rect {
         ..
     } = *sprite;
 
-    let full_rect = Rect::from_center(center, FULL_WIDTH, FULL_HEIGHT);
+    let full_rect = gui::hero_area(side);
     let (reference_point, x_offset) = (full_rect.top_left(), left_margin as i32);
 
     let top_left = reference_point.offset(x_offset, top_margin as i32);
@@ -0,0 +1,98 @@
+use common::error::{AnyHow, AnyWay};
+use gamedata::towns::{Town, WallSection, WallState};
+use logic::gamestate::GameState;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+use strum::{EnumCount, IntoEnumIterator};
+
+use super::statics::StaticTexture;
+use super::Statics;
+use crate::gridpos;
+use crate::resources::ResourceRegistry;
+
+// Центральная башня стоит за стеной, вне клеток поля
+const KEEP_POSITION: (i32, i32) = (720, 290);
+
+const WALL_STATES: [WallState; 3] = [WallState::Intact, WallState::Damaged, WallState::Destroyed];
+
+// Картинки стен города защитника во всех состояниях
+pub struct SiegeTextures<'a>([[Option<Texture<'a>>; 3]; WallSection::COUNT]);
+
+impl<'a> SiegeTextures<'a> {
+    pub fn load(
+        town: Town,
+        rr: &mut ResourceRegistry,
+        tc: &'a TextureCreator<WindowContext>,
+    ) -> AnyHow<Self> {
+        let mut textures: [[Option<Texture>; 3]; WallSection::COUNT] = Default::default();
+
+        for section in WallSection::iter() {
+            for (index, wall_state) in WALL_STATES.into_iter().enumerate() {
+                let filename = town.wall_sprite(section, wall_state);
+
+                // Без картинки часть стены отмечается затенёнными клетками
+                if !rr.has_image(&filename) {
+                    continue;
+                }
+
+                let mut image = rr.load_palette_image(&filename)?;
+                image.apply_transparency()?;
+                let texture = image.into_surface().as_texture(tc)?;
+
+                textures[section as usize][index] = Some(texture);
+            }
+        }
+
+        Ok(Self(textures))
+    }
+
+    fn get(&self, section: WallSection, wall_state: WallState) -> Option<&Texture<'a>> {
+        let index = WALL_STATES.iter().position(|&s| s == wall_state).unwrap();
+        self.0[section as usize][index].as_ref()
+    }
+}
+
+// Точка, вокруг которой рисуется часть стены и куда целится катапульта
+pub fn section_anchor(state: &GameState, section: WallSection) -> Point {
+    let cells = state.get_wall_section_cells(section);
+
+    if cells.is_empty() {
+        return Point::from(KEEP_POSITION);
+    }
+
+    let sum = cells
+        .iter()
+        .map(|&cell| gridpos::center(cell))
+        .fold(Point::new(0, 0), |acc, point| acc + point);
+
+    sum / cells.len() as i32
+}
+
+pub fn draw_walls(canvas: &mut WindowCanvas, statics: &Statics, state: &GameState) -> AnyWay {
+    let Some(textures) = &statics.siege else {
+        return Ok(());
+    };
+
+    for section in WallSection::iter() {
+        let Some(wall_state) = state.get_wall_state(section) else {
+            return Ok(());
+        };
+
+        if let Some(texture) = textures.get(section, wall_state) {
+            let query = texture.query();
+            let rect = Rect::from_center(section_anchor(state, section), query.width, query.height);
+            canvas.copy(texture, None, rect)?;
+        } else if wall_state != WallState::Destroyed {
+            for cell in state.get_wall_section_cells(section) {
+                canvas.copy(
+                    statics.textures.get(StaticTexture::GridCellShadow),
+                    None,
+                    gridpos::bounding_rect(cell),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use common::error::AnyHow;
-use gamedata::gui::{Button, ButtonState};
+use gamedata::artifacts::Artifact;
+use gamedata::creatures::Creature;
+use gamedata::gui::{Button, ButtonState, SpellBookTab};
 use gamedata::heroes;
 use gamedata::spells::Spell;
 use sdl2::render::{Texture, TextureCreator};
@@ -9,7 +11,8 @@ use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::EnumCount;
 
 use super::cursors::Cursors;
-use crate::resources::spritesheets::{AnimationGroup, SpriteGroup};
+use super::siege::SiegeTextures;
+use crate::resources::spritesheets::{AnimationGroup, SpriteGroup, SpriteSheetSingle};
 use crate::{Config, ResourceRegistry};
 
 pub struct Statics<'a> {
@@ -19,6 +22,10 @@ pub struct Statics<'a> {
     pub heroes: [Option<AnimationGroup<heroes::Animation>>; 2],
     pub ui: UI,
     pub spells: SpriteGroup<Spell>,
+    pub spell_book_tabs: SpriteGroup<SpellBookTab>,
+    pub portraits: SpriteSheetSingle,
+    pub artifacts: SpriteSheetSingle,
+    pub siege: Option<SiegeTextures<'a>>,
 }
 
 impl<'a> Statics<'a> {
@@ -31,12 +38,23 @@ impl<'a> Statics<'a> {
         let font_path = &config.ttf_font;
         let font_size = 16;
 
-        let heroes = config.armies.map(|army| {
+        let heroes = config.armies.each_ref().map(|army| {
             army.hero
                 .map(|h| rr.load_spritesheet(h.class().spritesheet_filename()))
         });
 
         let spells = rr.load_sprite_group(Spell::SPRITESHEET);
+        let spell_book_tabs = rr.load_sprite_group(SpellBookTab::SPRITESHEET);
+        let portraits = rr.load_animation::<{ Creature::PORTRAITS_CONTAINER_TYPE }>(
+            Creature::PORTRAITS_SPRITESHEET,
+        );
+
+        let artifacts = rr.load_animation::<{ Artifact::CONTAINER_TYPE }>(Artifact::SPRITESHEET);
+
+        let siege = config.armies[1]
+            .town
+            .map(|town| SiegeTextures::load(town, rr, tc))
+            .transpose()?;
 
         Ok(Self {
             cursors: Cursors::load(rr),
@@ -45,6 +63,10 @@ impl<'a> Statics<'a> {
             heroes,
             ui: UI::load(rr),
             spells,
+            spell_book_tabs,
+            portraits,
+            artifacts,
+            siege,
         })
     }
 }
@@ -67,8 +89,17 @@ impl<'a> Textures<'a> {
         rr: &mut ResourceRegistry,
         tc: &'a TextureCreator<WindowContext>,
     ) -> AnyHow<Self> {
+        // В осаде поле боя - вид на стены города защитника
+        let siege_background = config.armies[1]
+            .town
+            .map(|town| town.siege_background())
+            .filter(|filename| rr.has_image(filename));
+        let battlefield = siege_background
+            .as_deref()
+            .unwrap_or(config.battlefield.filename());
+
         let textures: Vec<Texture> = [
-            (config.battlefield.filename(), false),
+            (battlefield, false),
             ("cbar.pcx", false),
             ("CmNumWin.pcx", false),
             ("CCellGrd.pcx", true),
@@ -97,7 +128,7 @@ impl<'a> Textures<'a> {
         Ok(Self(textures))
     }
 
-    pub fn get(&self, texture: StaticTexture) -> &Texture {
+    pub fn get(&self, texture: StaticTexture) -> &Texture<'_> {
         &self.0[texture as usize]
     }
 }
@@ -1,36 +1,130 @@
+use common::EnumIndex;
 use egui::epaint::{ClippedShape, RectShape};
-use egui::{Context, FullOutput, Shape, TextureId, Ui};
-use gamedata::gui::{Button, ButtonState, Texture};
-use gamedata::spells::Spell;
-use logic::command::Cast;
-use sdl2::rect::Rect;
+use egui::{Context, FullOutput, Sense, Shape, TextureId, Ui};
+use gamedata::gui::{Button, ButtonState, SpellBookTab, Texture};
+use gamedata::spells::{Spell, SpellTarget, SpellType};
+use logic::command::{Cast, Command};
+use logic::gamestate::{GameState, Side};
+use sdl2::rect::{Point, Rect};
+use strum::IntoEnumIterator;
 
-use crate::input::FrameInput;
+use crate::input::{FrameInput, Targeting};
 use crate::{input, Stage};
 
+// Открытая книга заклинаний: выбранная закладка и разворот
+pub struct SpellBook {
+    pub tab: SpellBookTab,
+    pub page: usize,
+}
+
+// Левый верхний угол книги на экране
+pub const BOOK_POSITION: (i32, i32) = (400 - 310, 2);
+
+// Заклинаний на развороте: по шесть на каждой странице
+const SPELLS_PER_PAGE: usize = 12;
+
+const SPELL_ICON_SIZE: (f32, f32) = (67., 48.);
+
+// Диалог подтверждения бегства или капитуляции
+pub const DIALOG_POSITION: (f32, f32) = (250., 200.);
+pub const DIALOG_SIZE: (f32, f32) = (300., 140.);
+
+// Кнопка окончания расстановки над очередью ходов
+pub const TACTICS_BUTTON_POSITION: (f32, f32) = (368., 470.);
+
+pub const HERO_INFO_POSITION: (f32, f32) = (200., 40.);
+pub const HERO_INFO_SIZE: (f32, f32) = (400., 470.);
+
+pub const RESULTS_POSITION: (f32, f32) = (150., 30.);
+pub const RESULTS_SIZE: (f32, f32) = (500., 500.);
+
+// Место героя на поле боя, клик по нему открывает окно героя
+pub fn hero_area(side: Side) -> Rect {
+    let x = match side {
+        Side::Attacker => 50,
+        Side::Defender => 785,
+    };
+
+    Rect::from_center(Point::new(x, 75), 150, 175)
+}
+
 pub fn create_frame(
     ctx: &Context,
-    input: &FrameInput,
+    input: &mut FrameInput,
     stage: &mut Stage,
-    cast: &mut Option<Cast>,
+    command: &mut Option<Command>,
+    state: &GameState,
 ) -> Vec<(Rect, Texture)> {
     let raw_input = input::to_raw_input(input);
 
+    let mut is_click_consumed = false;
+
     let full_output = ctx.run(raw_input, |ctx| {
-        egui::Area::new("menu")
-            .fixed_pos((0., 556.))
-            .show(ctx, |ui| menu(ui, stage));
+        // После битвы кнопки меню уже ни на что не влияют
+        if !matches!(stage, Stage::Results) {
+            egui::Area::new("menu")
+                .fixed_pos((0., 556.))
+                .show(ctx, |ui| menu(ui, stage, state));
+        }
 
-        if matches!(stage, Stage::SpellBook) {
+        if matches!(stage, Stage::SpellBook(_)) {
             egui::Area::new("spellbook")
-                .fixed_pos((400. - 310., 0.))
-                .show(ctx, |ui| spell_book(ui, stage, cast));
+                .fixed_pos((BOOK_POSITION.0 as f32, 0.))
+                .show(ctx, |ui| {
+                    is_click_consumed = spell_book(ui, stage, command, state);
+                });
+        }
+
+        if matches!(stage, Stage::Tactics) {
+            egui::Area::new("tactics")
+                .fixed_pos(TACTICS_BUTTON_POSITION)
+                .show(ctx, |ui| {
+                    is_click_consumed = end_tactics(ui, command);
+                });
+        }
+
+        if matches!(stage, Stage::HeroInfo(_)) {
+            egui::Area::new("hero_info")
+                .fixed_pos(HERO_INFO_POSITION)
+                .show(ctx, |ui| {
+                    is_click_consumed = hero_info(ui, stage);
+                });
+        }
+
+        if matches!(stage, Stage::Results) {
+            egui::Area::new("results")
+                .fixed_pos(RESULTS_POSITION)
+                .show(ctx, |ui| {
+                    is_click_consumed = results(ui, stage);
+                });
+        }
+
+        if matches!(stage, Stage::Confirmation(_)) {
+            egui::Area::new("confirmation")
+                .fixed_pos(DIALOG_POSITION)
+                .show(ctx, |ui| {
+                    is_click_consumed = confirmation(ui, stage, command);
+                });
         }
     });
 
+    // Клик по книге не должен сразу выбрать цель заклинания на поле
+    if is_click_consumed {
+        input.consume_lmb();
+    }
+
     to_shapes(full_output)
 }
 
+impl SpellBook {
+    pub fn new() -> Self {
+        Self {
+            tab: SpellBookTab::All,
+            page: 0,
+        }
+    }
+}
+
 fn to_shapes(output: FullOutput) -> Vec<(Rect, Texture)> {
     output
         .shapes
@@ -59,7 +153,7 @@ fn to_shapes(output: FullOutput) -> Vec<(Rect, Texture)> {
         .collect()
 }
 
-fn menu(ui: &mut Ui, state: &mut Stage) {
+fn menu(ui: &mut Ui, stage: &mut Stage, state: &GameState) {
     let buttons = [
         (Button::Settings, 3),
         (Button::Surrender, 54),
@@ -82,44 +176,230 @@ fn menu(ui: &mut Ui, state: &mut Stage) {
             ((x + 48) as f32, 557. + 44.).into(),
         );
         if ui.put(rect, button).clicked() {
-            let name: &'static str = b.into();
-            dbg!(name);
+            match b {
+                Button::BookOfMagic => *stage = Stage::SpellBook(SpellBook::new()),
+                // Повторное нажатие возвращает управление игроку
+                Button::AutoBattle => {
+                    let side = state.get_current_stack().side;
+                    *stage = if stage.is_auto_battle_for(side) {
+                        Stage::Main
+                    } else {
+                        Stage::AutoBattle(side)
+                    };
+                }
+                Button::Retreat | Button::Surrender => {
+                    let command = if matches!(b, Button::Retreat) {
+                        Command::Retreat
+                    } else {
+                        Command::Surrender
+                    };
 
-            if matches!(b, Button::BookOfMagic) {
-                *state = Stage::SpellBook;
+                    // Битва заканчивается только после подтверждения
+                    if state.is_command_applicable(command) {
+                        *stage = Stage::Confirmation(command);
+                    }
+                }
+                _ => {}
             }
         }
     }
 }
 
-fn spell_book(ui: &mut Ui, state: &mut Stage, command: &mut Option<Cast>) {
-    let x_start = 210.;
-    let y_start = 110.;
+// Возвращает true, если клик пришёлся на элемент книги
+fn spell_book(
+    ui: &mut Ui,
+    stage: &mut Stage,
+    command: &mut Option<Command>,
+    state: &GameState,
+) -> bool {
+    let Stage::SpellBook(book) = stage else {
+        return false;
+    };
+
+    let spells = book_spells(state, book.tab);
+
+    let (book_x, book_y) = (BOOK_POSITION.0 as f32, BOOK_POSITION.1 as f32);
+    let area = |x: f32, y: f32, width: f32, height: f32| {
+        egui::Rect::from_min_size((book_x + x, book_y + y).into(), (width, height).into())
+    };
 
-    let x_change = 80.;
-    let y_change = 100.;
+    let mut is_clicked = false;
 
-    for x in 0..2 {
-        for y in 0..3 {
-            let x_pos = x_start + x_change * x as f32;
-            let y_pos = y_start + y_change * y as f32;
+    // Закладки школ магии: рисуется только выбранная
+    let tab_texture = Texture::SpellBookTab(book.tab);
+    let texture = egui::load::SizedTexture::new(TextureId::User(tab_texture.into()), (36., 56.));
+    ui.put(
+        area(524., 88., 36., 56.),
+        egui::widgets::Image::from_texture(texture),
+    );
+
+    for (i, tab) in SpellBookTab::iter().enumerate() {
+        let y = [94., 151., 210., 270., 330.][i];
+        if ui
+            .allocate_rect(area(549., y, 36., 56.), Sense::click())
+            .clicked()
+        {
+            book.tab = tab;
+            book.page = 0;
+            is_clicked = true;
+        }
+    }
+
+    // Углы страниц перелистывают разворот
+    let pages_count = spells.len().div_ceil(SPELLS_PER_PAGE).max(1);
+    if ui
+        .allocate_rect(area(97., 77., 40., 40.), Sense::click())
+        .clicked()
+    {
+        book.page = book.page.saturating_sub(1);
+        is_clicked = true;
+    }
+    if ui
+        .allocate_rect(area(487., 72., 40., 40.), Sense::click())
+        .clicked()
+    {
+        book.page = usize::min(book.page + 1, pages_count - 1);
+        is_clicked = true;
+    }
+
+    let page_spells = spells.iter().skip(book.page * SPELLS_PER_PAGE);
+
+    let mut picked = None;
+
+    for (i, &spell) in page_spells.take(SPELLS_PER_PAGE).enumerate() {
+        let x = [115., 205., 340., 430.][i / 3];
+        let y = [100., 195., 290.][i % 3];
+
+        let texture_id = TextureId::User(Texture::Spell(spell).into());
+        let texture = egui::load::SizedTexture::new(texture_id, SPELL_ICON_SIZE);
+        let image = egui::widgets::Image::from_texture(texture);
+        let button = egui::Button::image(image).frame(false);
 
-            let rect =
-                egui::Rect::from_two_pos((x_pos, y_pos).into(), (x_pos + 67., y_pos + 48.).into());
+        let rect = area(x, y, SPELL_ICON_SIZE.0, SPELL_ICON_SIZE.1);
+        if ui.put(rect, button).clicked() {
+            is_clicked = true;
 
-            let texture_id = TextureId::User(Texture::Spell(Spell::Armageddon).into());
-            let texture = egui::load::SizedTexture::new(texture_id, (67., 48.));
-            let image = egui::widgets::Image::from_texture(texture);
-            let button = egui::Button::image(image).frame(false);
+            if state.is_spell_available(spell) {
+                picked = Some(spell);
+            }
+        }
+    }
 
-            if ui.put(rect, button).clicked() {
-                dbg!("ARMAGEDDON!!!");
-                *state = Stage::Main;
-                *command = Some(Cast {
-                    spell: Spell::Armageddon,
+    if let Some(spell) = picked {
+        // Заклинания без цели применяются сразу
+        match state.get_spell_target(spell) {
+            Some(SpellTarget::Global(_) | SpellTarget::None) => {
+                *stage = Stage::Main;
+                *command = Some(Command::Cast(Cast {
+                    spell,
                     target: None,
-                });
+                    destination: None,
+                }));
+            }
+            _ => *stage = Stage::Targeting(Targeting::new(spell)),
+        }
+    }
+
+    is_clicked
+}
+
+// Возвращает true, если клик пришёлся на кнопку диалога
+fn confirmation(ui: &mut Ui, stage: &mut Stage, command: &mut Option<Command>) -> bool {
+    let Stage::Confirmation(confirmed) = *stage else {
+        return false;
+    };
+
+    let (dialog_x, dialog_y) = DIALOG_POSITION;
+    let buttons = [
+        (Button::Ok, 40.),
+        (Button::Cancel, DIALOG_SIZE.0 - 40. - 64.),
+    ];
+
+    let mut is_clicked = false;
+
+    for (b, x) in buttons {
+        let button = Texture::Button(b, ButtonState::Base);
+        let texture = egui::load::SizedTexture::new(TextureId::User(button.into()), (64., 30.));
+        let image = egui::widgets::Image::from_texture(texture);
+        let button = egui::Button::image(image).frame(false);
+
+        let rect = egui::Rect::from_min_size(
+            (dialog_x + x, dialog_y + DIALOG_SIZE.1 - 45.).into(),
+            (64., 30.).into(),
+        );
+        if ui.put(rect, button).clicked() {
+            is_clicked = true;
+
+            if matches!(b, Button::Ok) {
+                *command = Some(confirmed);
             }
+            *stage = Stage::Main;
         }
     }
+
+    is_clicked
+}
+
+// Возвращает true, если клик пришёлся на кнопку
+fn end_tactics(ui: &mut Ui, command: &mut Option<Command>) -> bool {
+    if ok_button(ui, TACTICS_BUTTON_POSITION) {
+        *command = Some(Command::EndTactics);
+        return true;
+    }
+
+    false
+}
+
+// Окно закрывается только кнопкой, остальные клики поглощаются
+fn hero_info(ui: &mut Ui, stage: &mut Stage) -> bool {
+    let (x, y) = HERO_INFO_POSITION;
+    let (width, height) = HERO_INFO_SIZE;
+
+    if ok_button(ui, (x + (width - 64.) / 2., y + height - 40.)) {
+        *stage = Stage::Main;
+    }
+
+    true
+}
+
+// Кнопка окна итогов завершает игру
+fn results(ui: &mut Ui, stage: &mut Stage) -> bool {
+    let (x, y) = RESULTS_POSITION;
+    let (width, height) = RESULTS_SIZE;
+
+    if ok_button(ui, (x + (width - 64.) / 2., y + height - 40.)) {
+        *stage = Stage::Main;
+    }
+
+    true
+}
+
+fn ok_button(ui: &mut Ui, position: (f32, f32)) -> bool {
+    let button = Texture::Button(Button::Ok, ButtonState::Base);
+    let texture = egui::load::SizedTexture::new(TextureId::User(button.into()), (64., 30.));
+    let image = egui::widgets::Image::from_texture(texture);
+    let button = egui::Button::image(image).frame(false);
+
+    let rect = egui::Rect::from_min_size(position.into(), (64., 30.).into());
+    ui.put(rect, button).clicked()
+}
+
+// Известные герою боевые заклинания выбранной школы по возрастанию уровня
+// Волшебная стрела без школы относится ко всем четырём стихиям
+fn book_spells(state: &GameState, tab: SpellBookTab) -> Vec<Spell> {
+    let side = state.get_current_stack().side;
+
+    let mut spells: Vec<Spell> = state
+        .get_hero_spell_book(side)
+        .unwrap_or_default()
+        .iter()
+        .copied()
+        .filter(|spell| matches!(spell.r#type(), SpellType::Battle))
+        .filter(|spell| {
+            tab.school().is_none() || spell.school().is_none() || spell.school() == tab.school()
+        })
+        .collect();
+
+    spells.sort_by_key(|&spell| (spell.level() as u8, spell.into_index()));
+    spells
 }
@@ -1,8 +1,9 @@
 use egui::RawInput;
 use gamedata::creatures::Creature;
+use gamedata::spells::Spell;
 use logic::command;
-use logic::command::{Cast, Command};
-use logic::gamestate::GameState;
+use logic::command::Command;
+use logic::gamestate::{GameState, Side};
 use logic::grid::{AttackDirection, GridPos};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
@@ -12,7 +13,7 @@ use sdl2::EventPump;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-use crate::{gridpos, Stage};
+use crate::{gridpos, gui, Stage};
 
 #[derive(Default)]
 pub struct FrameInput {
@@ -21,7 +22,9 @@ pub struct FrameInput {
     btn_rmb: bool,
     key_d: bool,
     key_w: bool,
-    quit: bool,
+    // Escape закрывает окна, а из основного экрана выходит из игры
+    escape: bool,
+    window_closed: bool,
 }
 
 pub struct FrameData {
@@ -65,10 +68,10 @@ pub fn gather_input(event_pump: &mut EventPump) -> FrameInput {
             } => match keycode {
                 Keycode::D => frame_input.key_d = true,
                 Keycode::W => frame_input.key_w = true,
-                Keycode::Escape => frame_input.quit = true,
+                Keycode::Escape => frame_input.escape = true,
                 _ => {}
             },
-            Event::Quit { .. } => frame_input.quit = true,
+            Event::Quit { .. } => frame_input.window_closed = true,
             _ => {}
         }
     }
@@ -80,20 +83,54 @@ pub fn process_input(
     state: &GameState,
     frame_input: &FrameInput,
     stage: &mut Stage,
-    cast: Option<Cast>,
+    gui_command: Option<Command>,
 ) -> FrameData {
-    if frame_input.quit {
+    if frame_input.window_closed {
+        std::process::exit(0);
+    }
+
+    // Escape при расстановке завершает её, как кнопка под полем
+    let mut escape_command = None;
+
+    if frame_input.escape {
         match stage {
-            Stage::Main => std::process::exit(0),
-            Stage::SpellBook => *stage = Stage::Main,
+            Stage::Main | Stage::Results => std::process::exit(0),
+            Stage::Tactics => escape_command = Some(Command::EndTactics),
+            Stage::SpellBook(_)
+            | Stage::Targeting(_)
+            | Stage::Confirmation(_)
+            | Stage::HeroInfo(_)
+            | Stage::AutoBattle(_) => *stage = Stage::Main,
         }
     }
 
+    // Правый клик отменяет выбор цели заклинания
+    if frame_input.btn_rmb && matches!(stage, Stage::Targeting(_)) {
+        *stage = Stage::Main;
+    }
+
+    let current_side = state.get_current_stack().side;
+
     match stage {
-        Stage::Main => {
+        // Пока компьютер играет за одну сторону, другая ходит как обычно
+        Stage::Main | Stage::Tactics | Stage::AutoBattle(_)
+            if !stage.is_auto_battle_for(current_side) =>
+        {
             let cursor_pos = frame_input.cursor_position;
             let current_hover = gridpos::find_pointer_position(cursor_pos.into());
 
+            // Клик по герою вне клеток поля открывает его окно
+            if frame_input.btn_lmb && current_hover.is_none() {
+                let clicked_hero = Side::iter().find(|&side| {
+                    gui::hero_area(side).contains_point(cursor_pos)
+                        && state.get_hero_info(side).is_some()
+                });
+
+                if let Some(side) = clicked_hero {
+                    *stage = Stage::HeroInfo(side);
+                }
+            }
+
             let current_stack = state.get_current_stack();
             let attack_direction = current_hover.map(|cell| {
                 calculate_attack_direction(cell, cursor_pos.into(), current_stack.creature)
@@ -102,8 +139,8 @@ pub fn process_input(
             let potential_lmb_command =
                 construct_potential_lmb_command(state, current_hover, attack_direction);
 
-            let command = cast
-                .map(Command::Cast)
+            let command = gui_command
+                .or(escape_command)
                 .or_else(|| construct_command(frame_input, potential_lmb_command));
 
             FrameData {
@@ -112,11 +149,85 @@ pub fn process_input(
                 command,
             }
         }
-        Stage::SpellBook => FrameData {
+        // Ход стороны, за которую играет компьютер, тоже без ввода
+        Stage::Main
+        | Stage::Tactics
+        | Stage::SpellBook(_)
+        | Stage::Confirmation(_)
+        | Stage::HeroInfo(_)
+        | Stage::AutoBattle(_)
+        | Stage::Results => FrameData {
             current_hover: None,
             potential_lmb_command: None,
             command: None,
         },
+        Stage::Targeting(targeting) => {
+            let current_hover = gridpos::find_pointer_position(frame_input.cursor_position.into());
+            let spell = targeting.spell;
+
+            let potential_lmb_command = current_hover
+                .map(|cell| {
+                    let (target, destination) = match targeting.target {
+                        Some(target) => (target, Some(cell)),
+                        None => (cell, None),
+                    };
+
+                    Command::Cast(command::Cast {
+                        spell,
+                        target: Some(target),
+                        destination,
+                    })
+                })
+                .filter(|c| state.is_command_applicable(*c));
+
+            let mut command = None;
+
+            if frame_input.btn_lmb {
+                if potential_lmb_command.is_some() {
+                    command = potential_lmb_command;
+                    *stage = Stage::Main;
+                } else if targeting.is_valid_target(state, current_hover) {
+                    // Для Телепорта и Жертвы сначала выбирается отряд, затем вторая клетка
+                    targeting.target = current_hover;
+                }
+            }
+
+            FrameData {
+                current_hover,
+                potential_lmb_command,
+                command,
+            }
+        }
+    }
+}
+
+// Выбор цели для заклинания
+pub struct Targeting {
+    pub spell: Spell,
+    // Уже выбранная первая клетка для заклинаний с двумя клетками
+    pub target: Option<GridPos>,
+}
+
+impl Targeting {
+    pub fn new(spell: Spell) -> Self {
+        Self {
+            spell,
+            target: None,
+        }
+    }
+
+    pub fn is_valid_target(&self, state: &GameState, cell: Option<GridPos>) -> bool {
+        let is_first_step = self.spell.has_destination() && self.target.is_none();
+
+        cell.is_some_and(|cell| {
+            is_first_step && state.get_spell_target_cells(self.spell).contains(&cell)
+        })
+    }
+}
+
+impl FrameInput {
+    pub fn consume_lmb(&mut self) {
+        self.btn_lmb = false;
     }
 }
 
@@ -177,7 +288,9 @@ fn construct_potential_lmb_command(
     current_hover
         .map(|grid| {
             if let Some(target) = state.find_unit_for_cell(grid) {
-                if current_stack.can_shoot(state) {
+                if current_stack.creature == Creature::FirstAidTent {
+                    Command::Heal(command::Heal { target })
+                } else if current_stack.can_shoot(state) {
                     Command::Shoot(command::Shoot { target })
                 } else {
                     Command::Attack(command::Attack {
@@ -185,6 +298,8 @@ fn construct_potential_lmb_command(
                         attack_direction: attack_direction.unwrap(),
                     })
                 }
+            } else if let Some(section) = state.find_wall_section_for_cell(grid) {
+                Command::Bombard(command::Bombard { section })
             } else {
                 Command::Move(command::Move { destination: grid })
             }
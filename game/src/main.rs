@@ -3,9 +3,11 @@ use std::pin::Pin;
 use std::ptr::addr_of_mut;
 use std::time::{Duration, Instant};
 
+use ai::AiPlayer;
 use animations::Animations;
 use common::error::{AnyHow, AnyWay};
 
+mod ai;
 mod animations;
 mod config;
 mod graphics;
@@ -17,14 +19,37 @@ mod sound;
 
 use config::Config;
 use graphics::Statics;
-use logic::gamestate::GameState;
+use gui::SpellBook;
+use input::Targeting;
+use logic::ai::Controller;
+use logic::command::Command;
+use logic::gamestate::{GameState, Side};
 use resources::ResourceRegistry;
 use sdl2::render::{TextureCreator, WindowCanvas};
 use sdl2::video::WindowContext;
 
 pub enum Stage {
     Main,
-    SpellBook,
+    SpellBook(SpellBook),
+    // Выбор цели для заклинания из книги
+    Targeting(Targeting),
+    // Ожидание подтверждения бегства или капитуляции
+    Confirmation(Command),
+    // Компьютер играет за сторону игрока, пока тот не отменит
+    AutoBattle(Side),
+    // Расстановка отрядов перед первым раундом
+    Tactics,
+    // Окно героя: навыки, характеристики и артефакты
+    HeroInfo(Side),
+    // Итоги битвы: потери сторон и опыт победителя
+    Results,
+}
+
+impl Stage {
+    // В игре вдвоём второй игрок продолжает ходить сам
+    pub fn is_auto_battle_for(&self, side: Side) -> bool {
+        matches!(self, Self::AutoBattle(auto_side) if *auto_side == side)
+    }
 }
 
 pub struct Graphics<'a> {
@@ -110,7 +135,14 @@ fn main() -> AnyWay {
     // Инициализация подсистемы событий
     let mut event_pump = sdl_context.event_pump()?;
 
-    let mut game_state = GameState::new(&config.armies)?;
+    let mut game_state = match config.seed {
+        Some(seed) => GameState::with_seed(&config.armies, seed)?,
+        None => GameState::new(&config.armies)?,
+    };
+
+    if config.quick_combat {
+        logic::ai::resolve_battle(&mut game_state);
+    }
 
     let mut animations = Animations::init(&game_state, &mut resource_registry);
 
@@ -124,18 +156,55 @@ fn main() -> AnyWay {
 
     let mut frame_timer = FrameTimer::init();
 
+    let mut is_report_shown = false;
+
+    let mut ai_player = AiPlayer::default();
+
     loop {
         let dt = frame_timer.dt();
 
-        let frame_input = input::gather_input(&mut event_pump);
+        let mut frame_input = input::gather_input(&mut event_pump);
 
-        let mut cast = None;
-        let shapes = gui::create_frame(&ctx, &frame_input, &mut stage, &mut cast);
-
-        let frame_data = input::process_input(&game_state, &frame_input, &mut stage, cast);
+        // Расстановка может закончиться и по команде компьютера
+        match stage {
+            Stage::Main if game_state.is_tactics_phase() => stage = Stage::Tactics,
+            Stage::Tactics if !game_state.is_tactics_phase() => stage = Stage::Main,
+            _ => {}
+        }
 
-        if !animations.is_animating() {
-            if let Some(command) = frame_data.command {
+        let mut gui_command = None;
+        let shapes = gui::create_frame(
+            &ctx,
+            &mut frame_input,
+            &mut stage,
+            &mut gui_command,
+            &game_state,
+        );
+
+        let frame_data = input::process_input(&game_state, &frame_input, &mut stage, gui_command);
+
+        if !animations.is_animating() && game_state.get_outcome().is_none() {
+            let side = game_state.get_current_stack().side;
+
+            // Ввод игрока за сторону компьютера игнорируется
+            let controller =
+                if stage.is_auto_battle_for(side) || game_state.is_current_stack_automatic() {
+                    Controller::Ai
+                } else {
+                    config.controllers[side as usize]
+                };
+
+            let command = match controller {
+                Controller::Human => {
+                    // Автобой выключили, пока компьютер думал
+                    ai_player.cancel();
+                    frame_data.command
+                }
+                Controller::Ai => ai_player.poll(&game_state),
+            };
+
+            if let Some(command) = command {
+                ai_player.cancel();
                 let events = game_state.apply_command(command);
 
                 animations::process_events(
@@ -149,6 +218,17 @@ fn main() -> AnyWay {
 
         animations.update(dt, &mut resource_registry);
 
+        // Итог показывается, когда доиграют последние анимации,
+        // а после закрытия окна итогов игра завершается
+        if game_state.get_outcome().is_some() {
+            if !animations.is_animating() && !is_report_shown {
+                stage = Stage::Results;
+                is_report_shown = true;
+            } else if is_report_shown && !matches!(stage, Stage::Results) {
+                return Ok(());
+            }
+        }
+
         graphics_.canvas.clear();
         graphics::draw(
             &game_state,
@@ -7,8 +7,8 @@ use formats::def;
 use formats::lod::LodIndex;
 use formats::snd::SndIndex;
 use gamedata::creatures;
-use gamedata::creatures::Creature;
-use gamedata::spells::SpellAnimation;
+use gamedata::creatures::{Creature, Missile};
+use gamedata::spells::{Spell, SpellAnimation};
 use gamedata::traits::{AnimationGroupT, ContainerType, SpriteGroupT};
 use sdl2::mixer::{Chunk, LoaderRWops};
 use sdl2::rwops::RWops;
@@ -32,6 +32,8 @@ pub struct ResourceRegistry {
     snd_archive: SndIndex,
     creature_cache: ResourceCache<{ Creature::COUNT }, Creature, CreatureResources>,
     spells_cache: ResourceCache<{ SpellAnimation::COUNT }, SpellAnimation, SpriteSheetSingle>,
+    spell_sounds_cache: ResourceCache<{ Spell::COUNT }, Spell, Chunk>,
+    missiles_cache: ResourceCache<{ Missile::COUNT }, Missile, SpriteSheetSingle>,
 }
 
 struct ResourceCache<const SIZE: usize, I: EnumIndex, V> {
@@ -63,6 +65,8 @@ impl ResourceRegistry {
             snd_archive,
             creature_cache: ResourceCache::new(),
             spells_cache: ResourceCache::new(),
+            spell_sounds_cache: ResourceCache::new(),
+            missiles_cache: ResourceCache::new(),
         }
     }
 
@@ -72,6 +76,10 @@ impl ResourceRegistry {
         Ok(image)
     }
 
+    pub fn has_image(&self, filename: &str) -> bool {
+        self.pcx_archive.contains(filename)
+    }
+
     pub fn load_palette_image(&mut self, filename: &str) -> AnyHow<PaletteImage> {
         let bytes = self.pcx_archive.read_file(filename);
         let image = PaletteImage::from_bytes(bytes)?;
@@ -147,6 +155,11 @@ impl ResourceRegistry {
         }
     }
 
+    // Не для всех заклинаний удалось подобрать файл анимации
+    pub fn has_spell_animation(&self, spell_animation: SpellAnimation) -> bool {
+        self.def_archive.contains(spell_animation.spritesheet())
+    }
+
     pub fn get_spell_animation(&mut self, spell_animation: SpellAnimation) -> &SpriteSheetSingle {
         if self.spells_cache.get(spell_animation).is_none() {
             let spritesheet = self.load_animation::<{ SpellAnimation::CONTAINER_TYPE }>(
@@ -156,6 +169,29 @@ impl ResourceRegistry {
         }
         self.spells_cache.get(spell_animation).unwrap()
     }
+
+    pub fn has_missile(&self, missile: Missile) -> bool {
+        self.def_archive.contains(missile.spritesheet())
+    }
+
+    pub fn get_missile(&mut self, missile: Missile) -> &SpriteSheetSingle {
+        if self.missiles_cache.get(missile).is_none() {
+            let spritesheet =
+                self.load_animation::<{ Missile::CONTAINER_TYPE }>(missile.spritesheet());
+            self.missiles_cache.put(missile, spritesheet);
+        }
+        self.missiles_cache.get(missile).unwrap()
+    }
+
+    pub fn get_spell_sound(&mut self, spell: Spell) -> Option<&Chunk> {
+        let filename = spell.sound()?;
+
+        if self.spell_sounds_cache.get(spell).is_none() && self.snd_archive.contains(filename) {
+            let chunk = self.load_sound(filename).unwrap();
+            self.spell_sounds_cache.put(spell, chunk);
+        }
+        self.spell_sounds_cache.get(spell)
+    }
 }
 
 impl<const SIZE: usize, I: EnumIndex, V> ResourceCache<{ SIZE }, I, V> {
@@ -0,0 +1,269 @@
+use serde::Deserialize;
+
+use crate::creatures::WarMachine;
+use crate::heroes::Stats;
+use crate::spells::SpellSchool;
+
+// Артефакты, влияющие на битву
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Artifact {
+    Ballista,
+    AmmoCart,
+    FirstAidTent,
+
+    CentaursAxe,
+    BlackshardOfTheDeadKnight,
+    GreaterGnollsFlail,
+    OgresClubOfHavoc,
+    SwordOfHellfire,
+    TitansGladius,
+
+    ShieldOfTheDwarvenLords,
+    ShieldOfTheYawningDead,
+    BucklerOfTheGnollKing,
+    TargOfTheRampagingOgre,
+    ShieldOfTheDamned,
+    SentinelsShield,
+
+    HelmOfTheAlabasterUnicorn,
+    SkullHelmet,
+    HelmOfChaos,
+    CrownOfTheSupremeMagi,
+    HellstormHelmet,
+    ThunderHelmet,
+
+    BreastplateOfPetrifiedWood,
+    RibCage,
+    ScalesOfTheGreaterBasilisk,
+    TunicOfTheCyclopsKing,
+    BreastplateOfBrimstone,
+    TitansCuirass,
+
+    ArmorOfWonder,
+    SandalsOfTheSaint,
+    CelestialNecklaceOfBliss,
+    LionsShieldOfCourage,
+    SwordOfJudgement,
+    HelmOfHeavenlyEnlightenment,
+
+    QuietEyeOfTheDragon,
+    RedDragonFlameTongue,
+    DragonScaleShield,
+    DragonScaleArmor,
+    DragonboneGreaves,
+    DragonWingTabard,
+    NecklaceOfDragonteeth,
+    CrownOfDragontooth,
+    StillEyeOfTheDragon,
+
+    CloverOfFortune,
+    CardsOfProphecy,
+    LadybirdOfLuck,
+    BadgeOfCourage,
+    CrestOfValor,
+    GlyphOfGallantry,
+    PendantOfCourage,
+
+    CharmOfMana,
+    TalismanOfMana,
+    MysticOrbOfMana,
+
+    OrbOfTheFirmament,
+    OrbOfSilt,
+    OrbOfTempestuousFire,
+    OrbOfDrivingRain,
+
+    // Снимают со стрелков героя штрафы за дальность и стену
+    GoldenBow,
+    BowOfTheSharpshooter,
+
+    // Запрещает обеим сторонам заклинания выше второго уровня
+    RecantersCloak,
+    // Отменяет сопротивление магии всех отрядов на поле
+    OrbOfVulnerability,
+}
+
+impl Artifact {
+    pub const SPRITESHEET: &'static str = "Artifact.def";
+    pub const CONTAINER_TYPE: u32 = 71;
+
+    // Кадры расположены в порядке номеров артефактов оригинальной игры
+    pub const fn icon_index(self) -> usize {
+        match self {
+            Self::Ballista => 4,
+            Self::AmmoCart => 5,
+            Self::FirstAidTent => 6,
+            Self::CentaursAxe => 7,
+            Self::BlackshardOfTheDeadKnight => 8,
+            Self::GreaterGnollsFlail => 9,
+            Self::OgresClubOfHavoc => 10,
+            Self::SwordOfHellfire => 11,
+            Self::TitansGladius => 12,
+            Self::ShieldOfTheDwarvenLords => 13,
+            Self::ShieldOfTheYawningDead => 14,
+            Self::BucklerOfTheGnollKing => 15,
+            Self::TargOfTheRampagingOgre => 16,
+            Self::ShieldOfTheDamned => 17,
+            Self::SentinelsShield => 18,
+            Self::HelmOfTheAlabasterUnicorn => 19,
+            Self::SkullHelmet => 20,
+            Self::HelmOfChaos => 21,
+            Self::CrownOfTheSupremeMagi => 22,
+            Self::HellstormHelmet => 23,
+            Self::ThunderHelmet => 24,
+            Self::BreastplateOfPetrifiedWood => 25,
+            Self::RibCage => 26,
+            Self::ScalesOfTheGreaterBasilisk => 27,
+            Self::TunicOfTheCyclopsKing => 28,
+            Self::BreastplateOfBrimstone => 29,
+            Self::TitansCuirass => 30,
+            Self::ArmorOfWonder => 31,
+            Self::SandalsOfTheSaint => 32,
+            Self::CelestialNecklaceOfBliss => 33,
+            Self::LionsShieldOfCourage => 34,
+            Self::SwordOfJudgement => 35,
+            Self::HelmOfHeavenlyEnlightenment => 36,
+            Self::QuietEyeOfTheDragon => 37,
+            Self::RedDragonFlameTongue => 38,
+            Self::DragonScaleShield => 39,
+            Self::DragonScaleArmor => 40,
+            Self::DragonboneGreaves => 41,
+            Self::DragonWingTabard => 42,
+            Self::NecklaceOfDragonteeth => 43,
+            Self::CrownOfDragontooth => 44,
+            Self::StillEyeOfTheDragon => 45,
+            Self::CloverOfFortune => 46,
+            Self::CardsOfProphecy => 47,
+            Self::LadybirdOfLuck => 48,
+            Self::BadgeOfCourage => 49,
+            Self::CrestOfValor => 50,
+            Self::GlyphOfGallantry => 51,
+            Self::CharmOfMana => 73,
+            Self::TalismanOfMana => 74,
+            Self::MysticOrbOfMana => 75,
+            Self::OrbOfTheFirmament => 79,
+            Self::OrbOfSilt => 80,
+            Self::OrbOfTempestuousFire => 81,
+            Self::OrbOfDrivingRain => 82,
+            Self::RecantersCloak => 83,
+            Self::GoldenBow => 91,
+            Self::OrbOfVulnerability => 93,
+            Self::PendantOfCourage => 108,
+            Self::BowOfTheSharpshooter => 137,
+        }
+    }
+
+    // Прибавка к первичным навыкам героя
+    pub const fn stats(self) -> Stats {
+        let (attack, defence, power, knowledge) = match self {
+            Self::CentaursAxe => (2, 0, 0, 0),
+            Self::BlackshardOfTheDeadKnight => (3, 0, 0, 0),
+            Self::GreaterGnollsFlail => (4, 0, 0, 0),
+            Self::OgresClubOfHavoc => (5, 0, 0, 0),
+            Self::SwordOfHellfire => (6, 0, 0, 0),
+            Self::TitansGladius => (12, -3, 0, 0),
+
+            Self::ShieldOfTheDwarvenLords => (0, 2, 0, 0),
+            Self::ShieldOfTheYawningDead => (0, 3, 0, 0),
+            Self::BucklerOfTheGnollKing => (0, 4, 0, 0),
+            Self::TargOfTheRampagingOgre => (0, 5, 0, 0),
+            Self::ShieldOfTheDamned => (0, 6, 0, 0),
+            Self::SentinelsShield => (-3, 12, 0, 0),
+
+            Self::HelmOfTheAlabasterUnicorn => (0, 0, 0, 1),
+            Self::SkullHelmet => (0, 0, 0, 2),
+            Self::HelmOfChaos => (0, 0, 0, 3),
+            Self::CrownOfTheSupremeMagi => (0, 0, 0, 4),
+            Self::HellstormHelmet => (0, 0, 0, 5),
+            Self::ThunderHelmet => (0, 0, -2, 10),
+
+            Self::BreastplateOfPetrifiedWood => (0, 0, 1, 0),
+            Self::RibCage => (0, 0, 2, 0),
+            Self::ScalesOfTheGreaterBasilisk => (0, 0, 3, 0),
+            Self::TunicOfTheCyclopsKing => (0, 0, 4, 0),
+            Self::BreastplateOfBrimstone => (0, 0, 5, 0),
+            Self::TitansCuirass => (0, 0, 10, -2),
+
+            Self::ArmorOfWonder => (1, 1, 1, 1),
+            Self::SandalsOfTheSaint => (2, 2, 2, 2),
+            Self::CelestialNecklaceOfBliss => (3, 3, 3, 3),
+            Self::LionsShieldOfCourage => (4, 4, 4, 4),
+            Self::SwordOfJudgement => (5, 5, 5, 5),
+            Self::HelmOfHeavenlyEnlightenment => (6, 6, 6, 6),
+
+            Self::QuietEyeOfTheDragon => (1, 1, 0, 0),
+            Self::RedDragonFlameTongue => (2, 2, 0, 0),
+            Self::DragonScaleShield => (3, 3, 0, 0),
+            Self::DragonScaleArmor => (4, 4, 0, 0),
+            Self::DragonboneGreaves => (0, 0, 1, 1),
+            Self::DragonWingTabard => (0, 0, 2, 2),
+            Self::NecklaceOfDragonteeth => (0, 0, 3, 3),
+            Self::CrownOfDragontooth => (0, 0, 4, 4),
+
+            _ => (0, 0, 0, 0),
+        };
+
+        Stats {
+            attack,
+            defence,
+            power,
+            knowledge,
+        }
+    }
+
+    pub const fn morale(self) -> i32 {
+        match self {
+            Self::StillEyeOfTheDragon
+            | Self::BadgeOfCourage
+            | Self::CrestOfValor
+            | Self::GlyphOfGallantry => 1,
+            Self::PendantOfCourage => 3,
+            _ => 0,
+        }
+    }
+
+    pub const fn luck(self) -> i32 {
+        match self {
+            Self::StillEyeOfTheDragon
+            | Self::CloverOfFortune
+            | Self::CardsOfProphecy
+            | Self::LadybirdOfLuck => 1,
+            Self::PendantOfCourage => 3,
+            _ => 0,
+        }
+    }
+
+    // Очки заклинаний, восстанавливаемые каждый день
+    pub const fn mana_regeneration(self) -> i32 {
+        match self {
+            Self::CharmOfMana => 1,
+            Self::TalismanOfMana => 2,
+            Self::MysticOrbOfMana => 3,
+            _ => 0,
+        }
+    }
+
+    // Сферы усиливают на 50% урон заклинаний своей школы
+    pub const fn boosted_school(self) -> Option<SpellSchool> {
+        match self {
+            Self::OrbOfTheFirmament => Some(SpellSchool::Air),
+            Self::OrbOfSilt => Some(SpellSchool::Earth),
+            Self::OrbOfTempestuousFire => Some(SpellSchool::Fire),
+            Self::OrbOfDrivingRain => Some(SpellSchool::Water),
+            _ => None,
+        }
+    }
+
+    pub const fn removes_shooting_penalties(self) -> bool {
+        matches!(self, Self::GoldenBow | Self::BowOfTheSharpshooter)
+    }
+
+    pub const fn war_machine(self) -> Option<WarMachine> {
+        match self {
+            Self::Ballista => Some(WarMachine::Ballista),
+            Self::AmmoCart => Some(WarMachine::AmmoCart),
+            Self::FirstAidTent => Some(WarMachine::FirstAidTent),
+            _ => None,
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use std::time::Duration;
 
+use common::EnumIndex;
 use macros::EnumIndex;
 use serde::Deserialize;
 use strum::EnumCount;
 use strum_macros::{EnumCount, EnumIter};
 
+use crate::spells::{Spell, SpellLevel, SpellSchool};
 use crate::traits::{AnimationGroupT, ContainerType};
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Deserialize, EnumCount, EnumIndex)]
@@ -183,13 +185,48 @@ pub enum Ability {
 
     ReturnAfterStrike,
 
+    NoMeleePenalty,
+    NoRangePenalty,
+
     NoRetaliation,
     ExtraRetaliation,
     InfiniteRetaliations,
 
-    Hatred { to: Box<[Creature]> },
+    Hatred {
+        to: Box<[Creature]>,
+    },
+
+    IgnoreDefence {
+        percent: f32,
+    },
 
-    IgnoreDefence { percent: f32 },
+    // Шанс полностью отразить вражеское заклинание
+    MagicResistance {
+        percent: i32,
+    },
+    // Невосприимчивость к заклинаниям до указанного уровня включительно
+    SpellImmunity {
+        max_level: SpellLevel,
+    },
+    SchoolImmunity {
+        school: SpellSchool,
+    },
+    MindImmunity,
+    // Снижение урона от заклинаний
+    SpellDamageReduction {
+        percent: i32,
+    },
+    // Шанс перенаправить вражеское заклинание на отряд противника
+    MagicMirror {
+        percent: i32,
+    },
+    // Шанс наложить заклинание на цель при каждом ударе, в том числе ответном,
+    // на указанное число раундов
+    SpellOnHit {
+        spell: Spell,
+        percent: i32,
+        rounds: i32,
+    },
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -199,6 +236,37 @@ pub enum MovementType {
     Teleport,
 }
 
+// Боевые машины героя, у каждой стороны не больше одной машины каждого вида
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum WarMachine {
+    Ballista,
+    FirstAidTent,
+    AmmoCart,
+    // В осаде катапульта всегда есть у нападающего
+    Catapult,
+}
+
+// Снаряды стрелков, общие для существ и их улучшений
+#[derive(Clone, Copy, EnumCount, EnumIndex)]
+pub enum Missile {
+    Arrow,
+    Monk,
+    Elf,
+    Gremlin,
+    Mage,
+    Titan,
+    Gog,
+    Lich,
+    Beholder,
+    Medusa,
+    Orc,
+    Cyclops,
+    Lizardman,
+    StormElemental,
+    IceElemental,
+    Halfling,
+}
+
 #[derive(Clone, Copy, EnumCount, EnumIter, EnumIndex)]
 pub enum Sound {
     Attack,
@@ -238,6 +306,53 @@ pub enum Animation {
 }
 
 impl Creature {
+    // Маленькие портреты существ, первые два кадра - пустые рамки
+    pub const PORTRAITS_SPRITESHEET: &'static str = "CPRSMALL.def";
+    pub const PORTRAITS_CONTAINER_TYPE: u32 = 71;
+
+    // Портреты расположены в порядке номеров существ оригинальной игры
+    pub fn portrait_index(self) -> usize {
+        let id = match self {
+            Self::AirElemental => 112,
+            Self::EarthElemental => 113,
+            Self::FireElemental => 114,
+            Self::WaterElemental => 115,
+            Self::GoldGolem => 116,
+            Self::DiamondGolem => 117,
+            Self::Pixie => 118,
+            Self::Sprite => 119,
+            Self::PsychicElemental => 120,
+            Self::MagicElemental => 121,
+            Self::IceElemental => 123,
+            Self::MagmaElemental => 125,
+            Self::StormElemental => 127,
+            Self::EnergyElemental => 129,
+            Self::Firebird => 130,
+            Self::Phoenix => 131,
+            Self::AzureDragon => 132,
+            Self::CrystalDragon => 133,
+            Self::FaerieDragon => 134,
+            Self::RustDragon => 135,
+            Self::Enchanter => 136,
+            Self::Sharpshooter => 137,
+            Self::Halfling => 138,
+            Self::Peasant => 139,
+            Self::Boar => 140,
+            Self::Mummy => 141,
+            Self::Nomad => 142,
+            Self::Rogue => 143,
+            Self::Troll => 144,
+            Self::Catapult => 145,
+            Self::Ballista => 146,
+            Self::FirstAidTent => 147,
+            Self::AmmoCart => 148,
+            // Существа городов от Замка до Крепости идут подряд
+            _ => self.into_index(),
+        };
+
+        id + 2
+    }
+
     pub const fn base_stats(&self) -> Stats {
         match self {
             // Castle
@@ -1573,49 +1688,393 @@ impl Creature {
         let genies = Box::new([Creature::Genie, Creature::MasterGenie]);
         let efreets = Box::new([Creature::Efreeti, Creature::EfreetSultan]);
 
+        let fire_immunity = Ability::SchoolImmunity {
+            school: SpellSchool::Fire,
+        };
+
         match self {
             Self::Marksman => vec![Ability::DoubleShot],
             Self::Griffin => vec![Ability::ExtraRetaliation],
             Self::RoyalGriffin => vec![Ability::InfiniteRetaliations],
             Self::Crusader => vec![Ability::DoubleStrike],
+            Self::Zealot => vec![Ability::NoMeleePenalty],
             Self::Angel => vec![Ability::Hatred { to: devils }],
             Self::Archangel => vec![Ability::Hatred { to: devils }],
 
+            Self::Unicorn | Self::WarUnicorn => vec![Ability::SpellOnHit {
+                spell: Spell::Blind,
+                percent: 20,
+                rounds: 3,
+            }],
+
+            Self::Dwarf => vec![Ability::MagicResistance { percent: 20 }],
+            Self::BattleDwarf => vec![Ability::MagicResistance { percent: 40 }],
+            Self::GreenDragon => vec![Ability::SpellImmunity {
+                max_level: SpellLevel::Third,
+            }],
+            Self::GoldDragon => vec![Ability::SpellImmunity {
+                max_level: SpellLevel::Fourth,
+            }],
+
+            Self::StoneGargoyle => vec![Ability::MindImmunity],
+            Self::ObsidianGargoyle => vec![Ability::MindImmunity],
+            Self::StoneGolem => vec![
+                Ability::MindImmunity,
+                Ability::SpellDamageReduction { percent: 50 },
+            ],
+            Self::IronGolem => vec![
+                Ability::MindImmunity,
+                Ability::SpellDamageReduction { percent: 75 },
+            ],
+            Self::Mage => vec![Ability::NoMeleePenalty],
+            Self::ArchMage => vec![Ability::NoMeleePenalty],
             Self::Genie => vec![Ability::Hatred { to: efreets }],
             Self::MasterGenie => vec![Ability::Hatred { to: efreets }],
             Self::Naga => vec![Ability::NoRetaliation],
             Self::NagaQueen => vec![Ability::NoRetaliation],
-            Self::Titan => vec![Ability::Hatred {
-                to: Box::new([Creature::BlackDragon]),
-            }],
+            Self::Titan => vec![
+                Ability::NoMeleePenalty,
+                Ability::Hatred {
+                    to: Box::new([Creature::BlackDragon]),
+                },
+            ],
 
             Self::Harpy => vec![Ability::NoRetaliation],
             Self::HarpyHag => vec![Ability::NoRetaliation, Ability::ReturnAfterStrike],
+            Self::Beholder => vec![Ability::NoMeleePenalty],
+            Self::EvilEye => vec![Ability::NoMeleePenalty],
+            Self::Medusa => vec![Ability::NoMeleePenalty],
+            Self::MedusaQueen => vec![Ability::NoMeleePenalty],
 
             Self::Cerberus => vec![Ability::NoRetaliation],
-            Self::Efreeti => vec![Ability::Hatred { to: genies }],
-            Self::EfreetSultan => vec![Ability::Hatred { to: genies }],
+            Self::Efreeti => vec![Ability::Hatred { to: genies }, fire_immunity],
+            Self::EfreetSultan => vec![Ability::Hatred { to: genies }, fire_immunity],
             Self::Devil => vec![Ability::NoRetaliation, Ability::Hatred { to: angels }],
             Self::ArchDevil => vec![Ability::NoRetaliation, Ability::Hatred { to: angels }],
 
+            Self::BlackKnight | Self::DreadKnight => vec![Ability::SpellOnHit {
+                spell: Spell::Curse,
+                percent: 20,
+                rounds: 3,
+            }],
             Self::Vampire => vec![Ability::NoRetaliation],
             Self::VampireLord => vec![Ability::NoRetaliation],
 
+            Self::RedDragon => vec![Ability::SpellImmunity {
+                max_level: SpellLevel::Third,
+            }],
+            Self::BlackDragon => vec![Ability::SpellImmunity {
+                max_level: SpellLevel::Fifth,
+            }],
+
             Self::WolfRaider => vec![Ability::DoubleStrike],
             Self::Behemoth => vec![Ability::IgnoreDefence { percent: 0.4 }],
             Self::AncientBehemoth => vec![Ability::IgnoreDefence { percent: 0.8 }],
 
+            Self::DragonFly => vec![Ability::SpellOnHit {
+                spell: Spell::Weakness,
+                percent: 100,
+                rounds: 3,
+            }],
             Self::Hydra => vec![Ability::NoRetaliation],
             Self::ChaosHydra => vec![Ability::NoRetaliation],
 
             Self::Sprite => vec![Ability::NoRetaliation],
-            Self::PsychicElemental => vec![Ability::NoRetaliation],
-            Self::MagicElemental => vec![Ability::NoRetaliation],
+            Self::AirElemental | Self::StormElemental => vec![Ability::MindImmunity],
+            Self::WaterElemental | Self::IceElemental => vec![Ability::MindImmunity],
+            Self::FireElemental | Self::EnergyElemental => {
+                vec![Ability::MindImmunity, fire_immunity]
+            }
+            Self::EarthElemental | Self::MagmaElemental => vec![Ability::MindImmunity],
+            Self::PsychicElemental => vec![Ability::NoRetaliation, Ability::MindImmunity],
+            Self::MagicElemental => vec![
+                Ability::NoRetaliation,
+                Ability::MindImmunity,
+                Ability::SpellImmunity {
+                    max_level: SpellLevel::Fifth,
+                },
+            ],
+            Self::Phoenix => vec![fire_immunity],
+
+            Self::Sharpshooter => vec![Ability::NoRangePenalty],
+            Self::GoldGolem => vec![
+                Ability::MindImmunity,
+                Ability::SpellDamageReduction { percent: 85 },
+            ],
+            Self::DiamondGolem => vec![
+                Ability::MindImmunity,
+                Ability::SpellDamageReduction { percent: 95 },
+            ],
+            Self::FaerieDragon => vec![Ability::MagicMirror { percent: 20 }],
+            Self::AzureDragon => vec![Ability::SpellImmunity {
+                max_level: SpellLevel::Third,
+            }],
 
             _ => vec![],
         }
     }
 
+    pub fn is_immune_to(self, spell: Spell) -> bool {
+        let is_mindless = self.is_undead() || self.has_ability(Ability::MindImmunity);
+        if spell.is_mind() && is_mindless {
+            return true;
+        }
+
+        self.abilities().into_iter().any(|ability| match ability {
+            Ability::SpellImmunity { max_level } => spell.level() <= max_level,
+            Ability::SchoolImmunity { school } => spell.school() == Some(school),
+            _ => false,
+        })
+    }
+
+    pub fn magic_resistance(self) -> i32 {
+        self.abilities()
+            .into_iter()
+            .find_map(|ability| match ability {
+                Ability::MagicResistance { percent } => Some(percent),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn spell_damage_reduction(self) -> i32 {
+        self.abilities()
+            .into_iter()
+            .find_map(|ability| match ability {
+                Ability::SpellDamageReduction { percent } => Some(percent),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn magic_mirror(self) -> i32 {
+        self.abilities()
+            .into_iter()
+            .find_map(|ability| match ability {
+                Ability::MagicMirror { percent } => Some(percent),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    // Цена найма одного существа в золоте
+    pub const fn cost(self) -> i32 {
+        match self {
+            // Castle
+            Self::Pikeman => 60,
+            Self::Halberdier => 75,
+            Self::Archer => 100,
+            Self::Marksman => 150,
+            Self::Griffin => 200,
+            Self::RoyalGriffin => 240,
+            Self::Swordsman => 300,
+            Self::Crusader => 400,
+            Self::Monk => 400,
+            Self::Zealot => 450,
+            Self::Cavalier => 1000,
+            Self::Champion => 1200,
+            Self::Angel => 3000,
+            Self::Archangel => 5000,
+
+            // Rampart
+            Self::Centaur => 70,
+            Self::CentaurCaptain => 90,
+            Self::Dwarf => 120,
+            Self::BattleDwarf => 150,
+            Self::WoodElf => 200,
+            Self::GrandElf => 225,
+            Self::Pegasus => 250,
+            Self::SilverPegasus => 275,
+            Self::DendroidGuard => 350,
+            Self::DendroidSoldier => 425,
+            Self::Unicorn => 850,
+            Self::WarUnicorn => 950,
+            Self::GreenDragon => 2400,
+            Self::GoldDragon => 4000,
+
+            // Tower
+            Self::Gremlin => 30,
+            Self::MasterGremlin => 40,
+            Self::StoneGargoyle => 130,
+            Self::ObsidianGargoyle => 160,
+            Self::StoneGolem => 150,
+            Self::IronGolem => 200,
+            Self::Mage => 350,
+            Self::ArchMage => 450,
+            Self::Genie => 550,
+            Self::MasterGenie => 600,
+            Self::Naga => 1100,
+            Self::NagaQueen => 1600,
+            Self::Giant => 2000,
+            Self::Titan => 5000,
+
+            // Inferno
+            Self::Imp => 50,
+            Self::Familiar => 60,
+            Self::Gog => 125,
+            Self::Magog => 175,
+            Self::HellHound => 200,
+            Self::Cerberus => 250,
+            Self::Demon => 250,
+            Self::HornedDemon => 270,
+            Self::PitFiend => 500,
+            Self::PitLord => 700,
+            Self::Efreeti => 900,
+            Self::EfreetSultan => 1100,
+            Self::Devil => 2700,
+            Self::ArchDevil => 4500,
+
+            // Necropolis
+            Self::Skeleton => 60,
+            Self::SkeletonWarrior => 70,
+            Self::WalkingDead => 100,
+            Self::Zombie => 125,
+            Self::Wight => 200,
+            Self::Wraith => 230,
+            Self::Vampire => 360,
+            Self::VampireLord => 500,
+            Self::Lich => 550,
+            Self::PowerLich => 600,
+            Self::BlackKnight => 1200,
+            Self::DreadKnight => 1500,
+            Self::BoneDragon => 1800,
+            Self::GhostDragon => 3000,
+
+            // Dungeon
+            Self::Troglodyte => 50,
+            Self::InfernalTroglodyte => 65,
+            Self::Harpy => 130,
+            Self::HarpyHag => 170,
+            Self::Beholder => 250,
+            Self::EvilEye => 280,
+            Self::Medusa => 300,
+            Self::MedusaQueen => 330,
+            Self::Minotaur => 500,
+            Self::MinotaurKing => 575,
+            Self::Manticore => 850,
+            Self::Scorpicore => 1050,
+            Self::RedDragon => 2500,
+            Self::BlackDragon => 4000,
+
+            // Stronghold
+            Self::Goblin => 40,
+            Self::Hobgoblin => 50,
+            Self::WolfRider => 100,
+            Self::WolfRaider => 140,
+            Self::Orc => 150,
+            Self::OrcChieftain => 165,
+            Self::Ogre => 300,
+            Self::OgreMagi => 400,
+            Self::Roc => 600,
+            Self::Thunderbird => 700,
+            Self::Cyclops => 750,
+            Self::CyclopsKing => 1100,
+            Self::Behemoth => 1500,
+            Self::AncientBehemoth => 3000,
+
+            // Fortress
+            Self::Gnoll => 50,
+            Self::GnollMarauder => 70,
+            Self::Lizardman => 110,
+            Self::LizardWarrior => 140,
+            Self::SerpentFly => 220,
+            Self::DragonFly => 240,
+            Self::Basilisk => 325,
+            Self::GreaterBasilisk => 400,
+            Self::Gorgon => 525,
+            Self::MightyGorgon => 600,
+            Self::Wyvern => 800,
+            Self::WyvernMonarch => 1100,
+            Self::Hydra => 2200,
+            Self::ChaosHydra => 3500,
+
+            // Conflux
+            Self::Pixie => 25,
+            Self::Sprite => 30,
+            Self::AirElemental => 250,
+            Self::StormElemental => 275,
+            Self::WaterElemental => 300,
+            Self::IceElemental => 375,
+            Self::FireElemental => 350,
+            Self::EnergyElemental => 400,
+            Self::EarthElemental => 400,
+            Self::MagmaElemental => 500,
+            Self::PsychicElemental => 750,
+            Self::MagicElemental => 800,
+            Self::Firebird => 1500,
+            Self::Phoenix => 2000,
+
+            // Neutral
+            Self::Peasant => 10,
+            Self::Halfling => 50,
+            Self::Boar => 150,
+            Self::Rogue => 100,
+            Self::Mummy => 300,
+            Self::Nomad => 200,
+            Self::Sharpshooter => 400,
+            Self::Troll => 500,
+            Self::GoldGolem => 500,
+            Self::DiamondGolem => 750,
+            Self::Enchanter => 500,
+            Self::FaerieDragon => 10000,
+            Self::RustDragon => 15000,
+            Self::CrystalDragon => 20000,
+            Self::AzureDragon => 30000,
+
+            // War Machines
+            Self::Ballista => 2500,
+            Self::FirstAidTent => 750,
+            Self::Catapult => 0,
+            Self::AmmoCart => 1000,
+        }
+    }
+
+    pub fn is_war_machine(self) -> bool {
+        matches!(
+            self,
+            Self::Ballista | Self::FirstAidTent | Self::Catapult | Self::AmmoCart
+        )
+    }
+
+    pub fn is_shooter(self) -> bool {
+        self.base_stats().ammo_capacity > 0
+    }
+
+    pub fn is_undead(self) -> bool {
+        [
+            Self::Skeleton,
+            Self::SkeletonWarrior,
+            Self::WalkingDead,
+            Self::Zombie,
+            Self::Wight,
+            Self::Wraith,
+            Self::Vampire,
+            Self::VampireLord,
+            Self::Lich,
+            Self::PowerLich,
+            Self::BlackKnight,
+            Self::DreadKnight,
+            Self::BoneDragon,
+            Self::GhostDragon,
+            Self::Mummy,
+        ]
+        .contains(&self)
+    }
+
+    // Дыхание задевает клетку за целью
+    pub fn has_reach(self) -> bool {
+        [
+            Self::GreenDragon,
+            Self::GoldDragon,
+            Self::RedDragon,
+            Self::BlackDragon,
+            Self::Firebird,
+            Self::Phoenix,
+            Self::AzureDragon,
+        ]
+        .contains(&self)
+    }
+
     pub fn is_wide(&self) -> bool {
         [
             // Castle
@@ -1839,13 +2298,39 @@ impl Creature {
             Self::CrystalDragon => MovementType::Walk,
             Self::AzureDragon => MovementType::Fly,
 
-            Self::Ballista => unreachable!(),
-            Self::FirstAidTent => unreachable!(),
-            Self::Catapult => unreachable!(),
-            Self::AmmoCart => unreachable!(),
+            // Машины не ходят, но отряду нужен способ передвижения
+            Self::Ballista => MovementType::Walk,
+            Self::FirstAidTent => MovementType::Walk,
+            Self::Catapult => MovementType::Walk,
+            Self::AmmoCart => MovementType::Walk,
         }
     }
 
+    pub const fn missile(self) -> Option<Missile> {
+        let missile = match self {
+            Self::Archer | Self::Marksman => Missile::Arrow,
+            Self::Monk | Self::Zealot => Missile::Monk,
+            Self::WoodElf | Self::GrandElf | Self::Sharpshooter => Missile::Elf,
+            Self::MasterGremlin => Missile::Gremlin,
+            Self::Mage | Self::ArchMage | Self::Enchanter => Missile::Mage,
+            Self::Titan => Missile::Titan,
+            Self::Gog | Self::Magog => Missile::Gog,
+            Self::Lich | Self::PowerLich => Missile::Lich,
+            // Ядро баллисты использует тот же спрайт, что и снаряд бехолдера
+            Self::Beholder | Self::EvilEye | Self::Ballista => Missile::Beholder,
+            Self::Medusa | Self::MedusaQueen => Missile::Medusa,
+            Self::Orc | Self::OrcChieftain => Missile::Orc,
+            Self::Cyclops | Self::CyclopsKing => Missile::Cyclops,
+            Self::Lizardman | Self::LizardWarrior => Missile::Lizardman,
+            Self::StormElemental => Missile::StormElemental,
+            Self::IceElemental => Missile::IceElemental,
+            Self::Halfling => Missile::Halfling,
+            _ => return None,
+        };
+
+        Some(missile)
+    }
+
     pub fn is_flying(self) -> bool {
         [MovementType::Fly, MovementType::Teleport].contains(&self.movement_type())
     }
@@ -2164,7 +2649,7 @@ impl Creature {
             Self::Rogue => 1.00,
             Self::Troll => 1.00,
 
-            Self::Catapult | Self::Ballista | Self::FirstAidTent | Self::AmmoCart => unreachable!(),
+            Self::Catapult | Self::Ballista | Self::FirstAidTent | Self::AmmoCart => 1.00,
         }
     }
 
@@ -3653,6 +4138,44 @@ impl Animation {
     }
 }
 
+impl Missile {
+    pub const fn spritesheet(self) -> &'static str {
+        match self {
+            Self::Arrow => "PLCBOWX.def",
+            Self::Monk => "CPRZEAX.def",
+            Self::Elf => "PELFX.def",
+            Self::Gremlin => "PMGREMX.def",
+            Self::Mage => "PMAGEX.def",
+            Self::Titan => "CPRGTIX.def",
+            Self::Gog => "CPRGOGX.def",
+            Self::Lich => "PLICH.def",
+            Self::Beholder => "SMBALX.def",
+            Self::Medusa => "PMEDUSX.def",
+            Self::Orc => "PORCHX.def",
+            Self::Cyclops => "PCYCLBX.def",
+            Self::Lizardman => "PLIZARX.def",
+            Self::StormElemental => "PSTORMX.def",
+            Self::IceElemental => "PICEE.def",
+            Self::Halfling => "PHALF.def",
+        }
+    }
+}
+
+impl WarMachine {
+    pub const fn creature(self) -> Creature {
+        match self {
+            Self::Ballista => Creature::Ballista,
+            Self::FirstAidTent => Creature::FirstAidTent,
+            Self::AmmoCart => Creature::AmmoCart,
+            Self::Catapult => Creature::Catapult,
+        }
+    }
+}
+
+impl ContainerType for Missile {
+    const CONTAINER_TYPE: u32 = 64;
+}
+
 impl ContainerType for Animation {
     const CONTAINER_TYPE: u32 = 66;
 }
@@ -29,6 +29,9 @@ pub enum Cursor {
 
 impl Cursor {
     pub const CONTAINTER_FILENAME: &'static str = "CRCOMBAT.def";
+    // Анимированный курсор выбора цели заклинания, используется первый кадр
+    pub const SPELL_CONTAINER_FILENAME: &'static str = "CRSPELL.def";
+    pub const SPELL_POINTER_OFFSET: (i32, i32) = (18, 28);
 
     pub const fn pointer_offset(self) -> (i32, i32) {
         match self {
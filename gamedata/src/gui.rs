@@ -1,12 +1,13 @@
 use macros::EnumIndex;
 use strum_macros::{EnumCount, EnumIter, IntoStaticStr};
 
-use crate::spells::Spell;
+use crate::spells::{Spell, SpellSchool};
 use crate::traits::{ContainerType, SpriteGroupT};
 
 pub enum Texture {
     Button(Button, ButtonState),
     Spell(Spell),
+    SpellBookTab(SpellBookTab),
 }
 
 #[derive(Clone, Copy, EnumCount, EnumIter, IntoStaticStr)]
@@ -18,6 +19,8 @@ pub enum Button {
     BookOfMagic,
     Wait,
     Defend,
+    Ok,
+    Cancel,
 }
 
 #[allow(unused)]
@@ -29,6 +32,16 @@ pub enum ButtonState {
     Hovered,
 }
 
+// Закладки книги заклинаний в порядке кадров SpelTab.def
+#[derive(Clone, Copy, PartialEq, EnumCount, EnumIter, EnumIndex)]
+pub enum SpellBookTab {
+    Air,
+    Fire,
+    Water,
+    Earth,
+    All,
+}
+
 impl From<Texture> for u64 {
     fn from(value: Texture) -> Self {
         match value {
@@ -130,6 +143,19 @@ impl From<Texture> for u64 {
             Texture::Spell(Spell::SummonFire) => 96,
             Texture::Spell(Spell::SummonWater) => 97,
             Texture::Spell(Spell::SummonAir) => 98,
+            Texture::SpellBookTab(SpellBookTab::Air) => 99,
+            Texture::SpellBookTab(SpellBookTab::Fire) => 100,
+            Texture::SpellBookTab(SpellBookTab::Water) => 101,
+            Texture::SpellBookTab(SpellBookTab::Earth) => 102,
+            Texture::SpellBookTab(SpellBookTab::All) => 103,
+            Texture::Button(Button::Ok, ButtonState::Base) => 104,
+            Texture::Button(Button::Ok, ButtonState::Pressed) => 105,
+            Texture::Button(Button::Ok, ButtonState::Disabled) => 106,
+            Texture::Button(Button::Ok, ButtonState::Hovered) => 107,
+            Texture::Button(Button::Cancel, ButtonState::Base) => 108,
+            Texture::Button(Button::Cancel, ButtonState::Pressed) => 109,
+            Texture::Button(Button::Cancel, ButtonState::Disabled) => 110,
+            Texture::Button(Button::Cancel, ButtonState::Hovered) => 111,
         }
     }
 }
@@ -237,6 +263,19 @@ impl TryFrom<u64> for Texture {
             96 => Texture::Spell(Spell::SummonFire),
             97 => Texture::Spell(Spell::SummonWater),
             98 => Texture::Spell(Spell::SummonAir),
+            99 => Texture::SpellBookTab(SpellBookTab::Air),
+            100 => Texture::SpellBookTab(SpellBookTab::Fire),
+            101 => Texture::SpellBookTab(SpellBookTab::Water),
+            102 => Texture::SpellBookTab(SpellBookTab::Earth),
+            103 => Texture::SpellBookTab(SpellBookTab::All),
+            104 => Texture::Button(Button::Ok, ButtonState::Base),
+            105 => Texture::Button(Button::Ok, ButtonState::Pressed),
+            106 => Texture::Button(Button::Ok, ButtonState::Disabled),
+            107 => Texture::Button(Button::Ok, ButtonState::Hovered),
+            108 => Texture::Button(Button::Cancel, ButtonState::Base),
+            109 => Texture::Button(Button::Cancel, ButtonState::Pressed),
+            110 => Texture::Button(Button::Cancel, ButtonState::Disabled),
+            111 => Texture::Button(Button::Cancel, ButtonState::Hovered),
             _ => return Err("Invalid texture id"),
         };
 
@@ -254,10 +293,32 @@ impl Button {
             Self::BookOfMagic => "icm005.def",
             Self::Wait => "icm006.def",
             Self::Defend => "icm007.def",
+            Self::Ok => "iOKAY.def",
+            Self::Cancel => "ICANCEL.def",
+        }
+    }
+}
+
+impl SpellBookTab {
+    pub const SPRITESHEET: &'static str = "SpelTab.def";
+
+    pub const fn school(self) -> Option<SpellSchool> {
+        match self {
+            Self::Air => Some(SpellSchool::Air),
+            Self::Fire => Some(SpellSchool::Fire),
+            Self::Water => Some(SpellSchool::Water),
+            Self::Earth => Some(SpellSchool::Earth),
+            Self::All => None,
         }
     }
 }
 
+impl ContainerType for SpellBookTab {
+    const CONTAINER_TYPE: u32 = 71;
+}
+
+impl SpriteGroupT for SpellBookTab {}
+
 impl ContainerType for ButtonState {
     const CONTAINER_TYPE: u32 = 71;
 }
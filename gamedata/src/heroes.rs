@@ -2,10 +2,11 @@ use macros::EnumIndex;
 use serde::Deserialize;
 use strum_macros::{EnumCount, EnumIter};
 
+use crate::spells::Spell;
 use crate::towns::Town;
 use crate::traits::{AnimationGroupT, ContainerType};
 
-#[derive(Clone, Copy, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize)]
 pub enum Hero {
     Christian,
     Edric,
@@ -165,6 +166,7 @@ pub enum Hero {
     Luna,
 }
 
+#[derive(Clone, Copy)]
 pub enum Class {
     Knight,
     Cleric,
@@ -186,6 +188,7 @@ pub enum Class {
     Elementalist,
 }
 
+#[derive(Clone, Copy, Deserialize)]
 pub struct Stats {
     pub attack: i32,
     pub defence: i32,
@@ -193,7 +196,7 @@ pub struct Stats {
     pub knowledge: i32,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
 pub enum Ability {
     AirMagic,
     Archery,
@@ -225,7 +228,7 @@ pub enum Ability {
     Wisdom,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
 pub enum AbilityLevel {
     Basic,
     Advanced,
@@ -416,6 +419,96 @@ impl Hero {
         }
     }
 
+    // Заклинание, с которым герой начинает игру
+    // Герои силы кроме алхимиков начинают без книги заклинаний
+    pub const fn starting_spell(self) -> Option<Spell> {
+        match self {
+            Hero::Adela => Some(Spell::Bless),
+            Hero::Adelaide => Some(Spell::FrostRing),
+            Hero::Caitlin => Some(Spell::Cure),
+            Hero::Cuthbert => Some(Spell::Weakness),
+            Hero::Ingham => Some(Spell::Curse),
+            Hero::Loynis => Some(Spell::Prayer),
+            Hero::Rion => Some(Spell::StoneSkin),
+            Hero::Sanya => Some(Spell::Dispel),
+            Hero::Aeris => Some(Spell::ProtectAir),
+            Hero::Alagar => Some(Spell::IceBolt),
+            Hero::Coronius => Some(Spell::Slayer),
+            Hero::Elleshar => Some(Spell::Curse),
+            Hero::Gem => Some(Spell::SummonBoat),
+            Hero::Malcom => Some(Spell::MagicArrow),
+            Hero::Melodia => Some(Spell::Fortune),
+            Hero::Uland => Some(Spell::Cure),
+            Hero::Fafner => Some(Spell::Haste),
+            Hero::Iona => Some(Spell::MagicArrow),
+            Hero::Josephine => Some(Spell::Haste),
+            Hero::Neela => Some(Spell::Shield),
+            Hero::Piquedram => Some(Spell::Shield),
+            Hero::Rissa => Some(Spell::MagicArrow),
+            Hero::Thane => Some(Spell::MagicArrow),
+            Hero::Torosar => Some(Spell::MagicArrow),
+            Hero::Aine => Some(Spell::Curse),
+            Hero::Astral => Some(Spell::Hypnotize),
+            Hero::Cyra => Some(Spell::Haste),
+            Hero::Daremyth => Some(Spell::Fortune),
+            Hero::Halon => Some(Spell::StoneSkin),
+            Hero::Serena => Some(Spell::Dispel),
+            Hero::Solmyr => Some(Spell::ChainLightning),
+            Hero::Theodorus => Some(Spell::Shield),
+            Hero::Dracon => Some(Spell::Haste),
+            Hero::Ash => Some(Spell::Bloodlust),
+            Hero::Axsis => Some(Spell::ProtectAir),
+            Hero::Ayden => Some(Spell::ViewEarth),
+            Hero::Calid => Some(Spell::Haste),
+            Hero::Olema => Some(Spell::Weakness),
+            Hero::Xarfax => Some(Spell::Fireball),
+            Hero::Xyron => Some(Spell::Inferno),
+            Hero::Zydar => Some(Spell::StoneSkin),
+            Hero::Aislinn => Some(Spell::MeteorShower),
+            Hero::Nagash => Some(Spell::ProtectAir),
+            Hero::Nimbus => Some(Spell::Shield),
+            Hero::Sandro => Some(Spell::Slow),
+            Hero::Septienna => Some(Spell::DeathRipple),
+            Hero::Thant => Some(Spell::AnimateDead),
+            Hero::Vidomina => Some(Spell::Curse),
+            Hero::Xsi => Some(Spell::StoneSkin),
+            Hero::Alamar => Some(Spell::Resurrection),
+            Hero::Darkstorn => Some(Spell::StoneSkin),
+            Hero::Deemer => Some(Spell::MeteorShower),
+            Hero::Geon => Some(Spell::Slow),
+            Hero::Jaegar => Some(Spell::Shield),
+            Hero::Jeddite => Some(Spell::Resurrection),
+            Hero::Malekith => Some(Spell::Bloodlust),
+            Hero::Sephinroth => Some(Spell::ProtectAir),
+            Hero::Dessa => Some(Spell::StoneSkin),
+            Hero::Gird => Some(Spell::Bloodlust),
+            Hero::Gundula => Some(Spell::Slow),
+            Hero::Oris => Some(Spell::ProtectAir),
+            Hero::Saurug => Some(Spell::Bless),
+            Hero::Terek => Some(Spell::Haste),
+            Hero::Vey => Some(Spell::MagicArrow),
+            Hero::Zubin => Some(Spell::Precision),
+            Hero::Andra => Some(Spell::Dispel),
+            Hero::Merist => Some(Spell::StoneSkin),
+            Hero::Mirlanda => Some(Spell::Weakness),
+            Hero::Rosic => Some(Spell::MagicArrow),
+            Hero::Styg => Some(Spell::Shield),
+            Hero::Tiva => Some(Spell::StoneSkin),
+            Hero::Verdish => Some(Spell::ProtectFire),
+            Hero::Voy => Some(Spell::Slow),
+            Hero::Adrienne => Some(Spell::Inferno),
+            Hero::Aenain => Some(Spell::DisruptingRay),
+            Hero::Brissa => Some(Spell::Haste),
+            Hero::Ciele => Some(Spell::MagicArrow),
+            Hero::Gelare => Some(Spell::Dispel),
+            Hero::Grindan => Some(Spell::Slow),
+            Hero::Inteus => Some(Spell::Bloodlust),
+            Hero::Labetha => Some(Spell::StoneSkin),
+            Hero::Luna => Some(Spell::FireWall),
+            _ => None,
+        }
+    }
+
     pub const fn starting_abilities(self) -> [Option<LearnedAbility>; 2] {
         match self {
             Hero::Christian => [
@@ -2078,6 +2171,37 @@ impl Class {
             },
         }
     }
+
+    // Шансы в процентах, что новый уровень повысит атаку, защиту, силу или знания
+    // До десятого уровня класс растёт в своём направлении, дальше ровнее
+    pub const fn level_up_chances(self, level: u32) -> [i32; 4] {
+        let (early, late) = match self {
+            Class::Knight => ([35, 45, 10, 10], [30, 30, 20, 20]),
+            Class::Cleric => ([20, 15, 20, 45], [20, 20, 30, 30]),
+            Class::Ranger => ([35, 45, 10, 10], [30, 30, 20, 20]),
+            Class::Druid => ([10, 20, 35, 35], [20, 20, 30, 30]),
+            Class::Alchemist => ([30, 30, 15, 25], [30, 30, 20, 20]),
+            Class::Wizard => ([10, 10, 40, 40], [30, 20, 20, 30]),
+            Class::Demoniac => ([35, 35, 15, 15], [30, 30, 20, 20]),
+            Class::Heretic => ([15, 15, 35, 35], [20, 20, 30, 30]),
+            Class::DeathKnight => ([30, 25, 20, 25], [25, 25, 25, 25]),
+            Class::Necromancer => ([15, 15, 35, 35], [25, 25, 25, 25]),
+            Class::Overlord => ([35, 35, 15, 15], [30, 30, 20, 20]),
+            Class::Warlock => ([10, 10, 50, 30], [20, 20, 30, 30]),
+            Class::Barbarian => ([55, 35, 5, 5], [30, 30, 20, 20]),
+            Class::BattleMage => ([30, 20, 25, 25], [25, 25, 25, 25]),
+            Class::Beastmaster => ([30, 50, 10, 10], [30, 30, 20, 20]),
+            Class::Witch => ([5, 15, 40, 40], [20, 20, 30, 30]),
+            Class::Planeswalker => ([45, 25, 15, 15], [30, 30, 20, 20]),
+            Class::Elementalist => ([15, 15, 35, 35], [25, 25, 25, 25]),
+        };
+
+        if level < 10 {
+            early
+        } else {
+            late
+        }
+    }
 }
 
 impl ContainerType for Animation {
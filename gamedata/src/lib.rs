@@ -1,3 +1,4 @@
+pub mod artifacts;
 pub mod battlefields;
 pub mod creatures;
 pub mod cursors;
@@ -1,10 +1,11 @@
 use macros::EnumIndex;
+use serde::Deserialize;
 use strum_macros::{EnumCount, EnumIter};
 
 use crate::heroes::AbilityLevel;
 use crate::traits::{ContainerType, SpriteGroupT};
 
-#[derive(Clone, Copy, Debug, PartialEq, EnumCount, EnumIndex)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, EnumCount, EnumIndex, EnumIter)]
 pub enum Spell {
     SummonBoat,
     ScuttleBoat,
@@ -84,7 +85,7 @@ pub enum SpellType {
     Battle,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum SpellSchool {
     Air,
     Earth,
@@ -92,7 +93,7 @@ pub enum SpellSchool {
     Water,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum SpellLevel {
     First,
     Second,
@@ -101,9 +102,89 @@ pub enum SpellLevel {
     Fifth,
 }
 
+// На кого или на что направлено заклинание
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpellTarget {
+    // Один отряд
+    Stack(TargetSide),
+    // Все отряды в радиусе от выбранной клетки
+    // Кольцо не задевает центральную клетку
+    Area { radius: i32, ring: bool },
+    // Все отряды на поле
+    Global(TargetSide),
+    // Свободная клетка поля
+    Hex,
+    // Без цели
+    None,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TargetSide {
+    Friendly,
+    Enemy,
+    Any,
+}
+
 #[derive(Clone, Copy, EnumCount, EnumIter, EnumIndex)]
 pub enum SpellAnimation {
+    MagicArrow,
+    IceBolt,
+    Lightning,
+    Implosion,
+    FrostRing,
+    Fireball,
+    Inferno,
+    MeteorShower,
+    DeathRipple,
+    DestroyUndead,
     Armageddon,
+    Shield,
+    AirShield,
+    FireShield,
+    ProtectAir,
+    ProtectFire,
+    ProtectWater,
+    ProtectEarth,
+    AntiMagic,
+    Dispel,
+    MagicMirror,
+    Cure,
+    Resurrection,
+    Bless,
+    Curse,
+    Bloodlust,
+    Precision,
+    Weakness,
+    StoneSkin,
+    DisruptingRay,
+    Prayer,
+    Mirth,
+    Sorrow,
+    Fortune,
+    Misfortune,
+    Haste,
+    Slow,
+    Slayer,
+    Frenzy,
+    Counterstrike,
+    Berserk,
+    Hypnotize,
+    Forgetfulness,
+    Blind,
+    Teleport,
+}
+
+// Где проигрывается анимация заклинания
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AnimationPlacement {
+    // Поверх каждого задетого отряда
+    OnStack,
+    // Один раз в центре выбранной клетки
+    Area,
+    // Замощает всё поле боя
+    FullScreen,
+    // Летит от героя к каждому задетому отряду
+    Projectile,
 }
 
 impl Spell {
@@ -130,6 +211,342 @@ impl Spell {
         }
     }
 
+    pub fn target(self, school_level: Option<AbilityLevel>) -> SpellTarget {
+        let is_expert = matches!(school_level, Some(AbilityLevel::Expert));
+
+        // На эксперте большинство усилений и ослаблений действуют на всю армию
+        let mass = |side| {
+            if is_expert {
+                SpellTarget::Global(side)
+            } else {
+                SpellTarget::Stack(side)
+            }
+        };
+
+        match self {
+            Self::MagicArrow
+            | Self::IceBolt
+            | Self::LightningBolt
+            | Self::Implosion
+            | Self::ChainLightning
+            | Self::TitanLightningBolt => SpellTarget::Stack(TargetSide::Enemy),
+
+            Self::Fireball | Self::MeteorShower => SpellTarget::Area {
+                radius: 1,
+                ring: false,
+            },
+            Self::Inferno => SpellTarget::Area {
+                radius: 2,
+                ring: false,
+            },
+            Self::FrostRing => SpellTarget::Area {
+                radius: 1,
+                ring: true,
+            },
+            Self::Berserk => SpellTarget::Area {
+                radius: match school_level {
+                    None | Some(AbilityLevel::Basic) => 0,
+                    Some(AbilityLevel::Advanced) => 1,
+                    Some(AbilityLevel::Expert) => 2,
+                },
+                ring: false,
+            },
+
+            Self::DeathRipple | Self::DestroyUndead | Self::Armageddon => {
+                SpellTarget::Global(TargetSide::Any)
+            }
+
+            Self::Bless
+            | Self::Bloodlust
+            | Self::Haste
+            | Self::Shield
+            | Self::AirShield
+            | Self::StoneSkin
+            | Self::Precision
+            | Self::Fortune
+            | Self::Mirth
+            | Self::Prayer
+            | Self::ProtectAir
+            | Self::ProtectFire
+            | Self::ProtectWater
+            | Self::ProtectEarth
+            | Self::Counterstrike
+            | Self::Cure => mass(TargetSide::Friendly),
+
+            Self::Curse
+            | Self::Slow
+            | Self::Weakness
+            | Self::Misfortune
+            | Self::Sorrow
+            | Self::Forgetfulness => mass(TargetSide::Enemy),
+
+            Self::Dispel => mass(TargetSide::Any),
+
+            Self::FireShield
+            | Self::Frenzy
+            | Self::Slayer
+            | Self::AntiMagic
+            | Self::MagicMirror
+            | Self::Resurrection
+            | Self::AnimateDead
+            | Self::Sacrifice
+            | Self::Teleport
+            | Self::Clone => SpellTarget::Stack(TargetSide::Friendly),
+
+            Self::DisruptingRay | Self::Blind | Self::Hypnotize => {
+                SpellTarget::Stack(TargetSide::Enemy)
+            }
+
+            Self::Quicksand
+            | Self::LandMine
+            | Self::ForceField
+            | Self::FireWall
+            | Self::RemoveObstacle => SpellTarget::Hex,
+
+            Self::Earthquake
+            | Self::SummonAir
+            | Self::SummonEarth
+            | Self::SummonFire
+            | Self::SummonWater => SpellTarget::None,
+
+            Self::SummonBoat
+            | Self::ScuttleBoat
+            | Self::Visions
+            | Self::ViewAir
+            | Self::Disguise
+            | Self::ViewEarth
+            | Self::Fly
+            | Self::WaterWalk
+            | Self::DimensionDoor
+            | Self::TownPortal => SpellTarget::None,
+        }
+    }
+
+    // Урон заклинания: (множитель силы магии, базовый урон)
+    pub const fn damage(self, school_level: Option<AbilityLevel>) -> Option<(i32, i32)> {
+        use AbilityLevel::{Advanced, Basic, Expert};
+
+        let damage = match (self, school_level) {
+            (Spell::MagicArrow, None | Some(Basic)) => (10, 10),
+            (Spell::MagicArrow, Some(Advanced)) => (10, 20),
+            (Spell::MagicArrow, Some(Expert)) => (10, 30),
+
+            (Spell::IceBolt, None | Some(Basic)) => (20, 10),
+            (Spell::IceBolt, Some(Advanced)) => (20, 20),
+            (Spell::IceBolt, Some(Expert)) => (20, 50),
+
+            (Spell::LightningBolt, None | Some(Basic)) => (25, 10),
+            (Spell::LightningBolt, Some(Advanced)) => (25, 20),
+            (Spell::LightningBolt, Some(Expert)) => (25, 50),
+
+            (Spell::TitanLightningBolt, _) => (0, 600),
+
+            (Spell::Implosion, None | Some(Basic)) => (75, 100),
+            (Spell::Implosion, Some(Advanced)) => (75, 200),
+            (Spell::Implosion, Some(Expert)) => (75, 300),
+
+            (Spell::ChainLightning, None | Some(Basic)) => (40, 25),
+            (Spell::ChainLightning, Some(Advanced)) => (40, 50),
+            (Spell::ChainLightning, Some(Expert)) => (40, 100),
+
+            (Spell::FrostRing, None | Some(Basic)) => (10, 15),
+            (Spell::FrostRing, Some(Advanced)) => (10, 30),
+            (Spell::FrostRing, Some(Expert)) => (10, 60),
+
+            (Spell::Fireball, None | Some(Basic)) => (10, 15),
+            (Spell::Fireball, Some(Advanced)) => (10, 30),
+            (Spell::Fireball, Some(Expert)) => (10, 60),
+
+            (Spell::Inferno, None | Some(Basic)) => (10, 20),
+            (Spell::Inferno, Some(Advanced)) => (10, 40),
+            (Spell::Inferno, Some(Expert)) => (10, 80),
+
+            (Spell::MeteorShower, None | Some(Basic)) => (25, 25),
+            (Spell::MeteorShower, Some(Advanced)) => (25, 50),
+            (Spell::MeteorShower, Some(Expert)) => (25, 100),
+
+            (Spell::DeathRipple, None | Some(Basic)) => (5, 10),
+            (Spell::DeathRipple, Some(Advanced)) => (5, 20),
+            (Spell::DeathRipple, Some(Expert)) => (5, 30),
+
+            (Spell::DestroyUndead, None | Some(Basic)) => (10, 10),
+            (Spell::DestroyUndead, Some(Advanced)) => (10, 20),
+            (Spell::DestroyUndead, Some(Expert)) => (10, 50),
+
+            (Spell::Armageddon, None | Some(Basic)) => (50, 30),
+            (Spell::Armageddon, Some(Advanced)) => (50, 60),
+            (Spell::Armageddon, Some(Expert)) => (50, 120),
+
+            _ => return None,
+        };
+
+        Some(damage)
+    }
+
+    // Лечение и воскрешение: (множитель силы магии, базовое количество здоровья)
+    pub const fn healing(self, school_level: Option<AbilityLevel>) -> Option<(i32, i32)> {
+        use AbilityLevel::{Advanced, Basic, Expert};
+
+        let healing = match (self, school_level) {
+            (Spell::Cure, None | Some(Basic)) => (5, 10),
+            (Spell::Cure, Some(Advanced)) => (5, 20),
+            (Spell::Cure, Some(Expert)) => (5, 30),
+
+            (Spell::Resurrection, None | Some(Basic)) => (50, 40),
+            (Spell::Resurrection, Some(Advanced)) => (50, 80),
+            (Spell::Resurrection, Some(Expert)) => (50, 160),
+
+            (Spell::AnimateDead, None | Some(Basic)) => (50, 30),
+            (Spell::AnimateDead, Some(Advanced)) => (50, 60),
+            (Spell::AnimateDead, Some(Expert)) => (50, 160),
+
+            _ => return None,
+        };
+
+        Some(healing)
+    }
+
+    pub const fn animation(self) -> Option<SpellAnimation> {
+        let animation = match self {
+            Self::MagicArrow => SpellAnimation::MagicArrow,
+            Self::IceBolt => SpellAnimation::IceBolt,
+            Self::LightningBolt | Self::TitanLightningBolt | Self::ChainLightning => {
+                SpellAnimation::Lightning
+            }
+            Self::Implosion => SpellAnimation::Implosion,
+            Self::FrostRing => SpellAnimation::FrostRing,
+            Self::Fireball => SpellAnimation::Fireball,
+            Self::Inferno => SpellAnimation::Inferno,
+            Self::MeteorShower => SpellAnimation::MeteorShower,
+            Self::DeathRipple => SpellAnimation::DeathRipple,
+            Self::DestroyUndead => SpellAnimation::DestroyUndead,
+            Self::Armageddon => SpellAnimation::Armageddon,
+            Self::Shield => SpellAnimation::Shield,
+            Self::AirShield => SpellAnimation::AirShield,
+            Self::FireShield => SpellAnimation::FireShield,
+            Self::ProtectAir => SpellAnimation::ProtectAir,
+            Self::ProtectFire => SpellAnimation::ProtectFire,
+            Self::ProtectWater => SpellAnimation::ProtectWater,
+            Self::ProtectEarth => SpellAnimation::ProtectEarth,
+            Self::AntiMagic => SpellAnimation::AntiMagic,
+            Self::Dispel => SpellAnimation::Dispel,
+            Self::MagicMirror => SpellAnimation::MagicMirror,
+            Self::Cure => SpellAnimation::Cure,
+            Self::Resurrection | Self::AnimateDead | Self::Sacrifice => {
+                SpellAnimation::Resurrection
+            }
+            Self::Bless => SpellAnimation::Bless,
+            Self::Curse => SpellAnimation::Curse,
+            Self::Bloodlust => SpellAnimation::Bloodlust,
+            Self::Precision => SpellAnimation::Precision,
+            Self::Weakness => SpellAnimation::Weakness,
+            Self::StoneSkin => SpellAnimation::StoneSkin,
+            Self::DisruptingRay => SpellAnimation::DisruptingRay,
+            Self::Prayer => SpellAnimation::Prayer,
+            Self::Mirth => SpellAnimation::Mirth,
+            Self::Sorrow => SpellAnimation::Sorrow,
+            Self::Fortune => SpellAnimation::Fortune,
+            Self::Misfortune => SpellAnimation::Misfortune,
+            Self::Haste => SpellAnimation::Haste,
+            Self::Slow => SpellAnimation::Slow,
+            Self::Slayer => SpellAnimation::Slayer,
+            Self::Frenzy => SpellAnimation::Frenzy,
+            Self::Counterstrike => SpellAnimation::Counterstrike,
+            Self::Berserk => SpellAnimation::Berserk,
+            Self::Hypnotize => SpellAnimation::Hypnotize,
+            Self::Forgetfulness => SpellAnimation::Forgetfulness,
+            Self::Blind => SpellAnimation::Blind,
+            Self::Teleport => SpellAnimation::Teleport,
+            // Призванные и клонированные отряды просто появляются на поле
+            _ => return None,
+        };
+
+        Some(animation)
+    }
+
+    // Звук из Heroes3.snd, проигрываемый при сотворении
+    pub const fn sound(self) -> Option<&'static str> {
+        let sound = match self {
+            Self::Quicksand => "QUIKSAND",
+            Self::LandMine => "LANDMINE",
+            Self::ForceField => "FORCEFLD",
+            Self::FireWall => "FIREWALL",
+            Self::Earthquake => "QUAKE",
+            Self::MagicArrow => "MAGICBLT",
+            Self::IceBolt => "ICERAY",
+            Self::LightningBolt | Self::TitanLightningBolt => "LIGHTBLT",
+            Self::Implosion => "DECAY",
+            Self::ChainLightning => "CHAINLTE",
+            Self::FrostRing => "FROSTRNG",
+            Self::Fireball => "SPONTCOMB",
+            Self::Inferno => "FIREBLST",
+            Self::MeteorShower => "METEOR",
+            Self::DeathRipple => "DEATHRIP",
+            Self::DestroyUndead => "SACBRETH",
+            Self::Armageddon => "ARMGEDN",
+            Self::Shield => "SHIELD",
+            Self::AirShield => "AIRSHELD",
+            Self::FireShield => "FIRESHIE",
+            Self::ProtectAir => "PROTECTA",
+            Self::ProtectFire => "PROTECTF",
+            Self::ProtectWater => "PROTECTW",
+            Self::ProtectEarth => "PROTECTE",
+            Self::AntiMagic => "ANTIMAGK",
+            Self::Dispel => "DISPELL",
+            Self::MagicMirror => "BACKLASH",
+            Self::Cure => "CURE",
+            Self::Resurrection => "RESURECT",
+            Self::AnimateDead => "ANIMDEAD",
+            Self::Sacrifice => "SACRIF1",
+            Self::Bless => "BLESS",
+            Self::Curse => "CURSE",
+            Self::Bloodlust => "BLOODLUS",
+            Self::Precision => "PRECISON",
+            Self::Weakness => "WEAKNESS",
+            Self::StoneSkin => "TUFFSKIN",
+            Self::DisruptingRay => "DISRUPTR",
+            Self::Prayer => "PRAYER",
+            Self::Sorrow => "SORROW",
+            Self::Fortune => "FORTUNE",
+            Self::Mirth => "MIRTH",
+            Self::Misfortune => "MISFORT",
+            Self::Haste => "TAILWIND",
+            Self::Slow => "MUCKMIRE",
+            Self::Slayer => "SLAYER",
+            Self::Frenzy => "FRENZY",
+            Self::Counterstrike => "CNTRSTRK",
+            Self::Berserk => "BERSERK",
+            Self::Hypnotize => "HYPNOTIZ",
+            Self::Forgetfulness => "FORGET",
+            Self::Blind => "BLIND",
+            Self::Teleport => "TELPTOUT",
+            Self::RemoveObstacle => "REMOVEOB",
+            Self::Clone => "CLONE",
+            Self::SummonEarth | Self::SummonFire | Self::SummonWater | Self::SummonAir => "SUMNELM",
+            _ => return None,
+        };
+
+        Some(sound)
+    }
+
+    // Кроме цели нужна вторая клетка: куда переместить или кого принести в жертву
+    pub fn has_destination(self) -> bool {
+        matches!(self, Self::Teleport | Self::Sacrifice)
+    }
+
+    // Заклинания разума не действуют на нежить, големов и элементалей
+    pub fn is_mind(self) -> bool {
+        matches!(
+            self,
+            Self::Blind
+                | Self::Berserk
+                | Self::Hypnotize
+                | Self::Forgetfulness
+                | Self::Mirth
+                | Self::Sorrow
+        )
+    }
+
     pub fn school(self) -> Option<SpellSchool> {
         match self {
             Self::MagicArrow => None,
@@ -516,7 +933,62 @@ impl SpellSchool {
 impl SpellAnimation {
     pub const fn spritesheet(self) -> &'static str {
         match self {
+            Self::MagicArrow => "C08SPA0.def",
+            Self::IceBolt => "C17SPE0.def",
+            Self::Lightning => "C20SPX.def",
+            Self::Implosion => "C05SPE0.def",
+            Self::FrostRing => "C07SPW.def",
+            Self::Fireball => "C13SPF.def",
+            Self::Inferno => "C04SPF0.def",
+            Self::MeteorShower => "C08SPE0.def",
+            Self::DeathRipple => "C04SPE0.def",
+            Self::DestroyUndead => "C14SPA0.def",
             Self::Armageddon => "C06SPF0.def",
+            Self::Shield => "C13SPE0.def",
+            Self::AirShield => "C01SPA0.def",
+            Self::FireShield => "C13SPF0.def",
+            Self::ProtectAir => "C11SPA1.def",
+            Self::ProtectFire => "C11SPF0.def",
+            Self::ProtectWater => "C11SPW0.def",
+            Self::ProtectEarth => "C11SPE0.def",
+            Self::AntiMagic => "C02SPE0.def",
+            Self::Dispel => "C05SPW.def",
+            Self::MagicMirror => "C18SPW0.def",
+            Self::Cure => "C03SPW.def",
+            Self::Resurrection => "C01SPE0.def",
+            Self::Bless => "C01SPW.def",
+            Self::Curse => "C04SPW.def",
+            Self::Bloodlust => "C03SPF0.def",
+            Self::Precision => "C12SPA0.def",
+            Self::Weakness => "C0ACID.def",
+            Self::StoneSkin => "C16SPE.def",
+            Self::DisruptingRay => "C07SPA1.def",
+            Self::Prayer => "C10SPW.def",
+            Self::Mirth => "C09SPW0.def",
+            Self::Sorrow => "C14SPE0.def",
+            Self::Fortune => "C09SPA0.def",
+            Self::Misfortune => "C10SPF0.def",
+            Self::Haste => "C15SPA0.def",
+            Self::Slow => "C09SPE0.def",
+            Self::Slayer => "C13SPW0.def",
+            Self::Frenzy => "C17SPF0.def",
+            Self::Counterstrike => "C07SPF0.def",
+            Self::Berserk => "C05SPF0.def",
+            Self::Hypnotize => "C10SPA0.def",
+            Self::Forgetfulness => "C06SPW.def",
+            Self::Blind => "C02SPF0.def",
+            Self::Teleport => "C05SPA0.def",
+        }
+    }
+
+    pub const fn placement(self) -> AnimationPlacement {
+        match self {
+            Self::MagicArrow | Self::IceBolt => AnimationPlacement::Projectile,
+            Self::FrostRing | Self::Fireball | Self::Inferno | Self::MeteorShower => {
+                AnimationPlacement::Area
+            }
+            Self::Armageddon => AnimationPlacement::FullScreen,
+            _ => AnimationPlacement::OnStack,
         }
     }
 }
@@ -1,3 +1,7 @@
+use serde::Deserialize;
+use strum_macros::{EnumCount, EnumIter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
 pub enum Town {
     Castle,
     Rampart,
@@ -9,3 +13,87 @@ pub enum Town {
     Fortress,
     Conflux,
 }
+
+// Части городской стены, которые можно разрушить катапультой
+#[derive(Clone, Copy, Debug, PartialEq, EnumCount, EnumIter)]
+pub enum WallSection {
+    Keep,
+    UpperTower,
+    UpperWall,
+    UpperMiddleWall,
+    Gate,
+    LowerMiddleWall,
+    LowerWall,
+    LowerTower,
+}
+
+// Состояние части стены для выбора картинки
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WallState {
+    Intact,
+    Damaged,
+    Destroyed,
+}
+
+impl Town {
+    const fn siege_prefix(self) -> &'static str {
+        match self {
+            Self::Castle => "SGCS",
+            Self::Rampart => "SGRM",
+            Self::Tower => "SGTW",
+            Self::Inferno => "SGIN",
+            Self::Necropolis => "SGNC",
+            Self::Dungeon => "SGDN",
+            Self::Stronghold => "SGST",
+            Self::Fortress => "SGFR",
+            Self::Conflux => "SGEL",
+        }
+    }
+
+    pub fn siege_background(self) -> String {
+        format!("{}BACK.pcx", self.siege_prefix())
+    }
+
+    pub fn wall_sprite(self, section: WallSection, state: WallState) -> String {
+        let suffix = match section {
+            WallSection::Keep => "MAN",
+            WallSection::UpperTower => "TW2",
+            WallSection::UpperWall => "WA6",
+            WallSection::UpperMiddleWall => "WA4",
+            WallSection::Gate => "DRW",
+            WallSection::LowerMiddleWall => "WA3",
+            WallSection::LowerWall => "WA1",
+            WallSection::LowerTower => "TW1",
+        };
+
+        let state = match state {
+            WallState::Intact => 1,
+            WallState::Damaged => 2,
+            WallState::Destroyed => 3,
+        };
+
+        format!("{}{}{}.pcx", self.siege_prefix(), suffix, state)
+    }
+
+    // Урон, который ров наносит вошедшему в него отряду
+    pub const fn moat_damage(self) -> i32 {
+        match self {
+            Self::Inferno | Self::Dungeon | Self::Fortress | Self::Conflux => 90,
+            _ => 70,
+        }
+    }
+}
+
+impl WallSection {
+    // Сколько попаданий катапульты выдерживает часть стены
+    pub const MAX_HEALTH: i32 = 2;
+
+    // Урон стрелы, пока башня цела
+    pub const fn tower_damage(self) -> Option<(i32, i32)> {
+        match self {
+            Self::Keep => Some((10, 15)),
+            Self::UpperTower | Self::LowerTower => Some((6, 9)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use gamedata::creatures::Ability;
+use gamedata::spells::SpellTarget;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use crate::command::{Attack, Bombard, Cast, Command, Heal, Move, Shoot};
+use crate::gamestate::{BattleOutcome, GameState, StackHandle};
+use crate::grid::{AttackDirection, GridPos};
+use crate::pathfinding::{self, NavigationArray};
+use crate::stack::Stack;
+
+// Кто отдаёт команды за сторону
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum Controller {
+    #[default]
+    Human,
+    Ai,
+}
+
+// Защита от битв, где ни одна сторона не может добраться до другой
+const MAX_COMMANDS: usize = 10_000;
+
+// Быстрый бой: доигрывает битву за обе стороны без анимаций
+// Команды проходят через тот же GameState::apply_command, что и в обычной битве
+pub fn resolve_battle(state: &mut GameState) -> Option<BattleOutcome> {
+    for _ in 0..MAX_COMMANDS {
+        if let Some(outcome) = state.get_outcome() {
+            return Some(outcome);
+        }
+
+        let Some(command) = choose_command(state) else {
+            break;
+        };
+        state.apply_command(command);
+    }
+
+    state.get_outcome()
+}
+
+// Команда для текущего отряда
+// Заклинание не тратит ход, поэтому после него компьютер спросят ещё раз
+// Неприменимую команду компьютер повторял бы бесконечно, поэтому её заменяет запасная,
+// а если не подходит и она, команды нет вовсе
+pub fn choose_command(state: &GameState) -> Option<Command> {
+    Some(best_command(state))
+        .filter(|&command| state.is_command_applicable(command))
+        .or_else(|| fallback_command(state))
+}
+
+// Запасной ход, который подходит почти любому отряду
+fn fallback_command(state: &GameState) -> Option<Command> {
+    [Command::Defend, Command::Wait]
+        .into_iter()
+        .chain(stand_still(state))
+        .find(|&command| state.is_command_applicable(command))
+}
+
+fn best_command(state: &GameState) -> Command {
+    // Начальная расстановка компьютера устраивает
+    if state.is_tactics_phase() {
+        return Command::EndTactics;
+    }
+
+    if let Some(bombard) = best_bombard(state) {
+        return bombard;
+    }
+
+    if let Some(cast) = best_cast(state) {
+        return cast;
+    }
+
+    if let Some(heal) = best_heal(state) {
+        return heal;
+    }
+
+    let attack = [best_shot(state), best_melee(state)]
+        .into_iter()
+        .flatten()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((command, _)) = attack {
+        return command;
+    }
+
+    // Пусть враг подойдёт сам, а если ждать уже нельзя - сближаемся
+    if state.is_command_applicable(Command::Wait) {
+        return Command::Wait;
+    }
+
+    approach(state)
+        .or_else(|| stand_still(state))
+        .unwrap_or(Command::Defend)
+}
+
+// Ценность урона - стоимость убитых существ, с учётом неполного верхнего
+fn damage_value(stack: &Stack, damage: i32) -> f32 {
+    let health = stack.creature.base_stats().health;
+    let damage = i32::min(damage, stack.total_health());
+
+    damage as f32 * stack.creature.cost() as f32 / health as f32
+}
+
+// Угроза - ценность урона, который враг нанесёт текущему отряду
+fn threat(state: &GameState, enemy: StackHandle) -> f32 {
+    let current = state.get_current_stack_handle();
+    let stack = state.get_stack(enemy);
+    let is_ranged = stack.creature.is_shooter() && stack.current_ammo > 0;
+
+    let damage = state.get_expected_damage(enemy, current, is_ranged);
+    damage_value(state.get_current_stack(), damage)
+}
+
+fn attack_score(state: &GameState, target: StackHandle, is_ranged: bool) -> f32 {
+    let current = state.get_current_stack_handle();
+    let attacker = state.get_current_stack();
+    let defender = state.get_stack(target);
+
+    let damage = state.get_expected_damage(current, target, is_ranged);
+    let killed = f32::min(damage as f32 / defender.total_health() as f32, 1.0);
+
+    // Ослабленный враг меньше опасен в ответ и в свой ход
+    let mut score = damage_value(defender, damage) + killed * threat(state, target);
+
+    let has_retaliation = !is_ranged
+        && killed < 1.0
+        && defender.retaliation_count.has_retaliation()
+        && !attacker.creature.has_ability(Ability::NoRetaliation);
+
+    if has_retaliation {
+        let retaliation = state.get_expected_damage(target, current, false) as f32;
+        let retaliation = (retaliation * (1.0 - killed)) as i32;
+        score -= damage_value(attacker, retaliation);
+    }
+
+    score
+}
+
+fn enemies(state: &GameState) -> Vec<StackHandle> {
+    let current = state.get_current_stack();
+
+    state
+        .units()
+        .into_iter()
+        .filter(|&handle| !state.is_current(handle))
+        .filter(|&handle| {
+            let stack = state.get_stack(handle);
+            stack.is_alive() && current.is_enemy(stack)
+        })
+        .collect()
+}
+
+fn best_shot(state: &GameState) -> Option<(Command, f32)> {
+    enemies(state)
+        .into_iter()
+        .map(|target| Command::Shoot(Shoot { target }))
+        .filter(|&command| state.is_command_applicable(command))
+        .map(|command| {
+            let Command::Shoot(Shoot { target }) = command else {
+                unreachable!()
+            };
+            (command, attack_score(state, target, true))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+fn best_melee(state: &GameState) -> Option<(Command, f32)> {
+    let mut best: Option<(Command, f32)> = None;
+
+    for target in enemies(state) {
+        // Для одной цели урон не зависит от направления удара
+        let score = attack_score(state, target, false);
+        if best.is_some_and(|(_, best_score)| best_score >= score) {
+            continue;
+        }
+
+        let attack = state
+            .get_stack(target)
+            .get_occupied_cells()
+            .into_iter()
+            .flat_map(|cell| {
+                AttackDirection::iter().map(move |direction| Attack {
+                    attack_position: cell,
+                    attack_direction: direction,
+                })
+            })
+            .filter(|&attack| fits_for_attack(state, attack))
+            .map(Command::Attack)
+            .find(|&command| state.is_command_applicable(command));
+
+        if let Some(command) = attack {
+            best = Some((command, score));
+        }
+    }
+
+    best
+}
+
+// Широкий отряд должен целиком поместиться на поле в точке удара
+fn fits_for_attack(state: &GameState, attack: Attack) -> bool {
+    let stack = state.get_current_stack();
+
+    pathfinding::unit_position_for_attack(
+        attack.attack_position,
+        attack.attack_direction,
+        stack.side,
+        stack.creature.is_wide(),
+    )
+    .and_then(|position| pathfinding::get_occupied_cells_for(stack.creature, stack.side, position))
+    .is_some()
+}
+
+fn best_bombard(state: &GameState) -> Option<Command> {
+    let section = state.get_catapult_target()?;
+    let command = Command::Bombard(Bombard { section });
+
+    state.is_command_applicable(command).then_some(command)
+}
+
+// Палатка лечит отряд, потерявший больше всего в цене
+fn best_heal(state: &GameState) -> Option<Command> {
+    state
+        .units()
+        .into_iter()
+        .map(|target| Command::Heal(Heal { target }))
+        .filter(|&command| state.is_command_applicable(command))
+        .max_by(|&a, &b| {
+            let missing = |command| {
+                let Command::Heal(Heal { target }) = command else {
+                    unreachable!()
+                };
+                let stack = state.get_stack(target);
+                let wound = stack.creature.base_stats().health - stack.current_health;
+                damage_value(stack, wound)
+            };
+            missing(a).total_cmp(&missing(b))
+        })
+}
+
+// Из боевых заклинаний берём то, что сильнее всего бьёт по врагам
+// и меньше всего задевает своих
+fn best_cast(state: &GameState) -> Option<Command> {
+    let side = state.get_current_stack().side;
+    let spells = state.get_hero_spell_book(side)?;
+
+    let mut best: Option<(Command, f32)> = None;
+
+    for &spell in spells {
+        if !state.is_spell_available(spell) || spell.damage(None).is_none() {
+            continue;
+        }
+
+        let targets: Vec<Option<GridPos>> = match state.get_spell_target(spell) {
+            Some(SpellTarget::Stack(_)) => state
+                .get_spell_target_cells(spell)
+                .into_iter()
+                .map(Some)
+                .collect(),
+            Some(SpellTarget::Area { .. }) => state
+                .units()
+                .into_iter()
+                .map(|handle| state.get_stack(handle))
+                .filter(|stack| stack.is_alive())
+                .flat_map(|stack| stack.get_occupied_cells())
+                .map(Some)
+                .collect(),
+            Some(SpellTarget::Global(_)) => vec![None],
+            _ => vec![],
+        };
+
+        for target in targets {
+            let score: f32 = state
+                .get_expected_spell_damage(spell, target)
+                .into_iter()
+                .map(|(handle, damage)| {
+                    let stack = state.get_stack(handle);
+                    let value = damage_value(stack, damage);
+                    if stack.side == side {
+                        -value
+                    } else {
+                        value
+                    }
+                })
+                .sum();
+
+            let command = Command::Cast(Cast {
+                spell,
+                target,
+                destination: None,
+            });
+
+            let is_better = best.is_none_or(|(_, best_score)| score > best_score);
+            if score > 0.0 && is_better && state.is_command_applicable(command) {
+                best = Some((command, score));
+            }
+        }
+    }
+
+    best.map(|(command, _)| command)
+}
+
+// Идём к самому опасному врагу с учётом длины пути
+fn approach(state: &GameState) -> Option<Command> {
+    let stack = state.get_current_stack();
+    let navigation =
+        NavigationArray::new(stack.head, state, stack.side, stack.creature.is_flying());
+
+    let path = enemies(state)
+        .into_iter()
+        .filter_map(|enemy| {
+            let path = state
+                .get_stack(enemy)
+                .get_occupied_cells()
+                .into_iter()
+                .flat_map(|cell| cell.get_successors())
+                .filter(|&cell| state.find_unit_for_cell(cell).is_none())
+                .filter_map(|cell| navigation.get_shortest_path(cell))
+                .min_by_key(|path| path.len())?;
+
+            let priority = threat(state, enemy) / path.len() as f32;
+            Some((path, priority))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(path, _)| path)?;
+
+    // Самая дальняя клетка пути, куда отряд дойдёт за этот ход
+    path.into_iter()
+        .rev()
+        .map(|destination| Command::Move(Move { destination }))
+        .find(|&command| state.is_command_applicable(command))
+}
+
+// Берсерк не может ни ждать, ни защищаться, поэтому без цели просто стоит на месте
+fn stand_still(state: &GameState) -> Option<Command> {
+    let destination = state.get_current_stack().head;
+    let command = Command::Move(Move { destination });
+
+    state.is_command_applicable(command).then_some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::Creature;
+    use gamedata::heroes::Hero as GDHero;
+    use gamedata::towns::Town;
+
+    use super::*;
+    use crate::effect::{Status, StatusEffect};
+    use crate::gamestate::{Army, Side};
+    use crate::testing::{army, battle};
+
+    #[test]
+    fn berserk_without_target_stands_still() {
+        let defender = Army {
+            town: Some(Town::Castle),
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let mut state = battle(army(&[(Creature::Pikeman, 10)]), defender);
+
+        // Без катапульты берсерку за стеной некого атаковать
+        let catapult = state
+            .units()
+            .into_iter()
+            .find(|&h| state.get_stack(h).creature == Creature::Catapult)
+            .unwrap();
+        state.edit_stack(catapult, |stack| stack.count = 0);
+
+        let current = state.get_current_stack_handle();
+        assert_eq!(state.get_current_stack().creature, Creature::Pikeman);
+        assert_eq!(state.get_current_stack().side, Side::Attacker);
+
+        state.edit_stack(current, |stack| {
+            stack.apply_effect(StatusEffect::new(Status::Berserk, None, Some(1)))
+        });
+
+        let command = choose_command(&state).unwrap();
+        let head = state.get_current_stack().head;
+        assert!(matches!(command, Command::Move(Move { destination }) if destination == head));
+
+        state.apply_command(command);
+        assert_ne!(state.get_current_stack_handle(), current);
+    }
+
+    #[test]
+    fn fallback_defends_or_stands_still() {
+        let mut state = battle(
+            army(&[(Creature::Pikeman, 10)]),
+            army(&[(Creature::Pikeman, 10)]),
+        );
+        assert!(matches!(fallback_command(&state), Some(Command::Defend)));
+
+        let current = state.get_current_stack_handle();
+        state.edit_stack(current, |stack| {
+            stack.apply_effect(StatusEffect::new(Status::Berserk, None, Some(1)))
+        });
+        assert!(matches!(fallback_command(&state), Some(Command::Move(_))));
+    }
+
+    #[test]
+    fn chosen_commands_are_always_applicable() {
+        let hero_army = |stacks: &[(Creature, i32)], town| Army {
+            hero: Some(GDHero::Solmyr),
+            town,
+            ..army(stacks)
+        };
+        let battles = [
+            (
+                hero_army(&[(Creature::Archer, 20), (Creature::Griffin, 10)], None),
+                hero_army(&[(Creature::Pikeman, 30), (Creature::Harpy, 10)], None),
+            ),
+            (
+                hero_army(&[(Creature::Swordsman, 15), (Creature::Marksman, 10)], None),
+                hero_army(
+                    &[(Creature::Pikeman, 20), (Creature::Archer, 10)],
+                    Some(Town::Castle),
+                ),
+            ),
+        ];
+
+        for (attacker, defender) in battles {
+            for seed in 0..3 {
+                let mut state =
+                    GameState::with_seed(&[attacker.clone(), defender.clone()], seed).unwrap();
+
+                for _ in 0..MAX_COMMANDS {
+                    if state.get_outcome().is_some() {
+                        break;
+                    }
+
+                    let command = choose_command(&state).expect("AI has no command");
+                    assert!(state.is_command_applicable(command), "{command:?}");
+                    state.apply_command(command);
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use gamedata::spells::Spell;
+use gamedata::towns::WallSection;
 use strum_macros::EnumDiscriminants;
 
 use super::gamestate::StackHandle;
@@ -13,6 +14,15 @@ pub enum Command {
     Attack(Attack),
     Shoot(Shoot),
     Cast(Cast),
+    // Палатка лечит верхнее существо своего отряда
+    Heal(Heal),
+    // Катапульта бьёт по стене осаждённого города
+    Bombard(Bombard),
+    // Заканчивает расстановку перед битвой
+    EndTactics,
+    // Завершают битву за сторону текущего отряда
+    Retreat,
+    Surrender,
 }
 
 impl Command {
@@ -23,12 +33,32 @@ impl Command {
             CommandDiscriminants::Shoot,
             CommandDiscriminants::Wait,
             CommandDiscriminants::Attack,
+            CommandDiscriminants::Heal,
+            CommandDiscriminants::Bombard,
         ]
         .contains(&self.into())
     }
 
+    // Боевой дух срабатывает только после действия самого отряда
+    pub fn allows_morale(&self) -> bool {
+        [
+            CommandDiscriminants::Move,
+            CommandDiscriminants::Attack,
+            CommandDiscriminants::Shoot,
+        ]
+        .contains(&self.into())
+    }
+
+    // Заклинание колдует герой, а не отряд
     pub fn spends_turn(&self) -> bool {
-        Into::<CommandDiscriminants>::into(self) != CommandDiscriminants::Wait
+        ![
+            CommandDiscriminants::Wait,
+            CommandDiscriminants::Cast,
+            CommandDiscriminants::EndTactics,
+            CommandDiscriminants::Retreat,
+            CommandDiscriminants::Surrender,
+        ]
+        .contains(&self.into())
     }
 }
 
@@ -48,8 +78,20 @@ pub struct Shoot {
     pub target: StackHandle,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct Heal {
+    pub target: StackHandle,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Bombard {
+    pub section: WallSection,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Cast {
     pub spell: Spell,
     pub target: Option<GridPos>,
+    // Вторая клетка для Телепорта и Жертвы
+    pub destination: Option<GridPos>,
 }
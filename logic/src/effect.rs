@@ -0,0 +1,476 @@
+use gamedata::creatures::Creature;
+use gamedata::heroes::AbilityLevel;
+use gamedata::spells::{Spell, SpellLevel, SpellSchool};
+
+// Временные эффекты на отрядах
+// Накладываются заклинаниями, способностями существ и артефактами
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Bless,
+    Curse,
+    Haste,
+    Slow,
+    Shield,
+    AirShield,
+    StoneSkin,
+    Bloodlust,
+    Precision,
+    Weakness,
+    DisruptingRay,
+    Prayer,
+    Frenzy,
+    Counterstrike,
+    ProtectAir,
+    ProtectEarth,
+    ProtectFire,
+    ProtectWater,
+    AntiMagic,
+    MagicMirror,
+    Blind,
+    Berserk,
+    Forgetfulness,
+    Fortune,
+    Misfortune,
+    Mirth,
+    Sorrow,
+    Slayer,
+    FireShield,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub status: Status,
+    pub level: Option<AbilityLevel>,
+    // None - до конца боя
+    pub rounds_left: Option<i32>,
+}
+
+impl Status {
+    pub fn from_spell(spell: Spell) -> Option<Self> {
+        let status = match spell {
+            Spell::Bless => Self::Bless,
+            Spell::Curse => Self::Curse,
+            Spell::Haste => Self::Haste,
+            Spell::Slow => Self::Slow,
+            Spell::Shield => Self::Shield,
+            Spell::AirShield => Self::AirShield,
+            Spell::StoneSkin => Self::StoneSkin,
+            Spell::Bloodlust => Self::Bloodlust,
+            Spell::Precision => Self::Precision,
+            Spell::Weakness => Self::Weakness,
+            Spell::DisruptingRay => Self::DisruptingRay,
+            Spell::Prayer => Self::Prayer,
+            Spell::Frenzy => Self::Frenzy,
+            Spell::Counterstrike => Self::Counterstrike,
+            Spell::ProtectAir => Self::ProtectAir,
+            Spell::ProtectEarth => Self::ProtectEarth,
+            Spell::ProtectFire => Self::ProtectFire,
+            Spell::ProtectWater => Self::ProtectWater,
+            Spell::AntiMagic => Self::AntiMagic,
+            Spell::MagicMirror => Self::MagicMirror,
+            Spell::Blind => Self::Blind,
+            Spell::Berserk => Self::Berserk,
+            Spell::Forgetfulness => Self::Forgetfulness,
+            Spell::Fortune => Self::Fortune,
+            Spell::Misfortune => Self::Misfortune,
+            Spell::Mirth => Self::Mirth,
+            Spell::Sorrow => Self::Sorrow,
+            Spell::Slayer => Self::Slayer,
+            Spell::FireShield => Self::FireShield,
+            _ => return None,
+        };
+
+        Some(status)
+    }
+
+    // Снимаются заклинанием Лечение
+    pub fn is_negative(self) -> bool {
+        matches!(
+            self,
+            Self::Curse
+                | Self::Slow
+                | Self::Weakness
+                | Self::DisruptingRay
+                | Self::Blind
+                | Self::Berserk
+                | Self::Forgetfulness
+                | Self::Misfortune
+                | Self::Sorrow
+        )
+    }
+
+    // Накапливается при повторном наложении вместо замены
+    pub fn is_cumulative(self) -> bool {
+        self == Self::DisruptingRay
+    }
+}
+
+impl StatusEffect {
+    pub fn new(status: Status, level: Option<AbilityLevel>, rounds: Option<i32>) -> Self {
+        Self {
+            status,
+            level,
+            rounds_left: rounds,
+        }
+    }
+
+    fn is_advanced(&self) -> bool {
+        matches!(
+            self.level,
+            Some(AbilityLevel::Advanced | AbilityLevel::Expert)
+        )
+    }
+
+    fn by_level(&self, basic: i32, advanced: i32, expert: i32) -> i32 {
+        match self.level {
+            None | Some(AbilityLevel::Basic) => basic,
+            Some(AbilityLevel::Advanced) => advanced,
+            Some(AbilityLevel::Expert) => expert,
+        }
+    }
+
+    pub fn attack(&self, is_ranged: bool) -> i32 {
+        match self.status {
+            Status::Bloodlust if !is_ranged => self.by_level(3, 6, 6),
+            Status::Precision if is_ranged => self.by_level(3, 6, 6),
+            Status::Weakness => -self.by_level(3, 6, 6),
+            Status::Prayer => self.by_level(2, 4, 4),
+            _ => 0,
+        }
+    }
+
+    // Убийца прибавляет атаку только против сильнейших существ
+    pub fn slayer_attack(&self, target: Creature) -> i32 {
+        use Creature::*;
+
+        if self.status != Status::Slayer {
+            return 0;
+        }
+
+        let basic = [
+            Behemoth,
+            AncientBehemoth,
+            Hydra,
+            ChaosHydra,
+            GreenDragon,
+            GoldDragon,
+            RedDragon,
+            BlackDragon,
+            BoneDragon,
+            GhostDragon,
+            FaerieDragon,
+            RustDragon,
+            CrystalDragon,
+            AzureDragon,
+        ];
+        let advanced = [Angel, Archangel, Devil, ArchDevil];
+        let expert = [Giant, Titan];
+
+        let is_slain = basic.contains(&target)
+            || advanced.contains(&target) && self.by_level(0, 1, 1) == 1
+            || expert.contains(&target) && self.by_level(0, 0, 1) == 1;
+
+        if is_slain {
+            8
+        } else {
+            0
+        }
+    }
+
+    pub fn defence(&self) -> i32 {
+        match self.status {
+            Status::StoneSkin => self.by_level(3, 6, 6),
+            Status::DisruptingRay => -self.by_level(3, 4, 5),
+            Status::Prayer => self.by_level(2, 4, 4),
+            _ => 0,
+        }
+    }
+
+    pub fn speed(&self, speed: i32) -> i32 {
+        match self.status {
+            Status::Haste => self.by_level(3, 5, 5),
+            Status::Prayer => self.by_level(2, 4, 4),
+            Status::Slow => {
+                let percent = self.by_level(25, 50, 50);
+                -(speed * percent / 100)
+            }
+            _ => 0,
+        }
+    }
+
+    // Бешенство переводит защиту в атаку
+    pub fn frenzy_multiplier(&self) -> Option<f32> {
+        match self.status {
+            Status::Frenzy => Some(match self.by_level(1, 2, 3) {
+                1 => 1.0,
+                2 => 1.5,
+                _ => 2.0,
+            }),
+            _ => None,
+        }
+    }
+
+    // Итоговый разброс урона с учётом Благословения и Проклятия
+    pub fn damage_range(&self, (low, high): (i32, i32)) -> (i32, i32) {
+        match self.status {
+            Status::Bless => (high, high),
+            Status::Curse if self.is_advanced() => {
+                let low = i32::max(low * 4 / 5, 1);
+                (low, low)
+            }
+            Status::Curse => (low, low),
+            _ => (low, high),
+        }
+    }
+
+    // Доля урона, который отряд не получит
+    pub fn damage_reduction(&self, is_ranged: bool) -> f32 {
+        match self.status {
+            Status::Shield if !is_ranged => {
+                if self.is_advanced() {
+                    0.3
+                } else {
+                    0.15
+                }
+            }
+            Status::AirShield if is_ranged => {
+                if self.is_advanced() {
+                    0.5
+                } else {
+                    0.25
+                }
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn luck(&self) -> i32 {
+        match self.status {
+            Status::Fortune => self.by_level(1, 2, 2),
+            Status::Misfortune => -self.by_level(1, 2, 2),
+            _ => 0,
+        }
+    }
+
+    pub fn morale(&self) -> i32 {
+        match self.status {
+            Status::Mirth => self.by_level(1, 2, 2),
+            Status::Sorrow => -self.by_level(1, 2, 2),
+            _ => 0,
+        }
+    }
+
+    // Доля урона ближнего боя, которую Огненный щит возвращает бьющему
+    pub fn fire_shield(&self) -> f32 {
+        match self.status {
+            Status::FireShield => self.by_level(20, 25, 30) as f32 / 100.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn extra_retaliations(&self) -> i32 {
+        match self.status {
+            Status::Counterstrike => self.by_level(1, 2, 2),
+            _ => 0,
+        }
+    }
+
+    // Доля урона при ответном ударе ослеплённого отряда
+    pub fn retaliation_multiplier(&self) -> f32 {
+        match self.status {
+            Status::Blind => match self.by_level(1, 2, 3) {
+                1 => 0.5,
+                2 => 0.25,
+                _ => 0.0,
+            },
+            _ => 1.0,
+        }
+    }
+
+    pub fn prevents_acting(&self) -> bool {
+        self.status == Status::Blind
+    }
+
+    // Забывчивость на базовом уровне вдвое снижает урон стрелков,
+    // а на продвинутом полностью запрещает стрельбу
+    pub fn prevents_shooting(&self) -> bool {
+        self.status == Status::Forgetfulness && self.is_advanced()
+    }
+
+    pub fn ranged_multiplier(&self) -> f32 {
+        if self.status == Status::Forgetfulness && !self.is_advanced() {
+            0.5
+        } else {
+            1.0
+        }
+    }
+
+    // Берсерк не может ждать и защищаться, зато бьёт и своих
+    pub fn is_berserk(&self) -> bool {
+        self.status == Status::Berserk
+    }
+
+    // Ослепление спадает после атаки на отряд
+    pub fn breaks_on_attack(&self) -> bool {
+        self.status == Status::Blind
+    }
+
+    // Защита от стихии снижает урон заклинаний её школы
+    pub fn spell_damage_reduction(&self, school: Option<SpellSchool>) -> f32 {
+        let protected_school = match self.status {
+            Status::ProtectAir => SpellSchool::Air,
+            Status::ProtectEarth => SpellSchool::Earth,
+            Status::ProtectFire => SpellSchool::Fire,
+            Status::ProtectWater => SpellSchool::Water,
+            _ => return 0.0,
+        };
+
+        if school != Some(protected_school) {
+            0.0
+        } else if self.is_advanced() {
+            0.5
+        } else {
+            0.3
+        }
+    }
+
+    // Антимагия защищает от заклинаний до третьего уровня,
+    // на продвинутом уровне до четвёртого, на эксперте от всех
+    pub fn blocks_spell(&self, spell: Spell) -> bool {
+        let max_level = match self.status {
+            Status::AntiMagic => match self.level {
+                None | Some(AbilityLevel::Basic) => SpellLevel::Third,
+                Some(AbilityLevel::Advanced) => SpellLevel::Fourth,
+                Some(AbilityLevel::Expert) => SpellLevel::Fifth,
+            },
+            _ => return false,
+        };
+
+        spell.level() <= max_level
+    }
+
+    // Шанс отразить вражеское заклинание в процентах
+    pub fn magic_mirror(&self) -> i32 {
+        match self.status {
+            Status::MagicMirror => self.by_level(20, 30, 40),
+            _ => 0,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if let Some(ref mut rounds) = self.rounds_left {
+            *rounds -= 1;
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.rounds_left.is_some_and(|rounds| rounds <= 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::Side;
+    use crate::grid::GridPos;
+    use crate::stack::Stack;
+
+    fn stack(creature: Creature) -> Stack {
+        Stack::new(creature, 10, GridPos::new(1, 1), Side::Attacker)
+    }
+
+    fn effect(status: Status, level: Option<AbilityLevel>) -> StatusEffect {
+        StatusEffect::new(status, level, Some(3))
+    }
+
+    #[test]
+    fn effect_expires_after_its_rounds() {
+        let mut bless = StatusEffect::new(Status::Bless, None, Some(2));
+        bless.tick();
+        assert!(!bless.is_expired());
+        bless.tick();
+        assert!(bless.is_expired());
+
+        let mut endless = StatusEffect::new(Status::Bless, None, None);
+        endless.tick();
+        assert!(!endless.is_expired());
+    }
+
+    #[test]
+    fn stack_drops_expired_effects_and_reports_them() {
+        let mut pikeman = stack(Creature::Pikeman);
+        pikeman.apply_effect(StatusEffect::new(Status::Haste, None, Some(1)));
+        pikeman.apply_effect(StatusEffect::new(Status::Bless, None, Some(2)));
+
+        assert_eq!(pikeman.tick_effects(), [Status::Haste]);
+        assert!(!pikeman.has_status(Status::Haste));
+        assert!(pikeman.has_status(Status::Bless));
+
+        assert_eq!(pikeman.tick_effects(), [Status::Bless]);
+        assert!(pikeman.effects.is_empty());
+    }
+
+    #[test]
+    fn same_status_replaces_unless_cumulative() {
+        let mut angel = stack(Creature::Angel);
+        let defence = angel.defence();
+
+        angel.apply_effect(StatusEffect::new(Status::StoneSkin, None, Some(1)));
+        angel.apply_effect(StatusEffect::new(
+            Status::StoneSkin,
+            Some(AbilityLevel::Advanced),
+            Some(3),
+        ));
+        assert_eq!(angel.effects.len(), 1);
+        assert_eq!(angel.effects[0].rounds_left, Some(3));
+        assert_eq!(angel.defence(), defence + 6);
+
+        angel.remove_effects(|_| true);
+        angel.apply_effect(effect(Status::DisruptingRay, None));
+        angel.apply_effect(effect(Status::DisruptingRay, None));
+        assert_eq!(angel.effects.len(), 2);
+        assert_eq!(angel.defence(), defence - 6);
+    }
+
+    #[test]
+    fn modifiers_depend_on_level() {
+        let expert = Some(AbilityLevel::Expert);
+
+        let bloodlust = effect(Status::Bloodlust, None);
+        assert_eq!(bloodlust.attack(false), 3);
+        assert_eq!(bloodlust.attack(true), 0);
+        assert_eq!(effect(Status::Bloodlust, expert).attack(false), 6);
+        assert_eq!(effect(Status::Weakness, expert).attack(true), -6);
+
+        assert_eq!(effect(Status::Haste, None).speed(4), 3);
+        assert_eq!(effect(Status::Slow, None).speed(8), -2);
+        assert_eq!(effect(Status::Slow, expert).speed(8), -4);
+
+        assert_eq!(effect(Status::Bless, None).damage_range((2, 5)), (5, 5));
+        assert_eq!(effect(Status::Curse, None).damage_range((2, 5)), (2, 2));
+        assert_eq!(effect(Status::Curse, expert).damage_range((10, 20)), (8, 8));
+
+        let shield = effect(Status::Shield, None);
+        assert_eq!(shield.damage_reduction(false), 0.15);
+        assert_eq!(shield.damage_reduction(true), 0.0);
+        assert_eq!(
+            effect(Status::AirShield, expert).damage_reduction(true),
+            0.5
+        );
+    }
+
+    #[test]
+    fn blind_weakens_only_retaliation() {
+        let mut pikeman = stack(Creature::Pikeman);
+        pikeman.apply_effect(effect(Status::Blind, None));
+
+        assert_eq!(pikeman.damage_dealt_multiplier(false, false), 1.0);
+        assert_eq!(pikeman.damage_dealt_multiplier(false, true), 0.5);
+
+        // Забывчивость, напротив, касается только стрельбы
+        let mut archer = stack(Creature::Archer);
+        archer.apply_effect(effect(Status::Forgetfulness, None));
+        assert_eq!(archer.damage_dealt_multiplier(true, false), 0.5);
+        assert_eq!(archer.damage_dealt_multiplier(false, true), 1.0);
+    }
+}
@@ -1,12 +1,19 @@
 use gamedata::spells::Spell;
+use gamedata::towns::WallSection;
 
-use crate::gamestate::StackHandle;
-use crate::grid::GridPos;
+use crate::effect::Status;
+use crate::gamestate::{Side, StackHandle};
+use crate::grid::{AttackDirection, GridPos};
 
 #[derive(Debug, Clone)]
 pub struct Strike {
+    // Снятое со цели здоровье
+    pub damage: i32,
     pub retaliation: bool,
     pub lethal: bool,
+    // Огненный щит цели вернул часть урона бьющему
+    pub reflected: i32,
+    pub reflected_lethal: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -15,12 +22,21 @@ pub enum Event {
     Shot(Shot),
     Movement(Movement),
     Cast(Cast),
+    Defend(Defend),
+    Heal(Heal),
+    Bombard(Bombard),
+    TowerShot(TowerShot),
+    MoatDamage(MoatDamage),
+    Morale(Morale),
+    StatusExpired(StatusExpired),
 }
 
 #[derive(Debug, Clone)]
 pub struct Attack {
     pub attacker: StackHandle,
     pub defender: StackHandle,
+    // С какой стороны от цели встал атакующий
+    pub direction: AttackDirection,
     pub strikes: Vec<Strike>,
 }
 
@@ -28,6 +44,7 @@ pub struct Attack {
 pub struct Shot {
     pub attacker: StackHandle,
     pub target: StackHandle,
+    pub damage: i32,
     pub lethal: bool,
 }
 
@@ -40,5 +57,67 @@ pub struct Movement {
 #[derive(Debug, Clone)]
 pub struct Cast {
     pub spell: Spell,
+    pub side: Side,
     pub target: Option<GridPos>,
+    pub affected: Vec<SpellHit>,
+    // Устоявшие благодаря сопротивлению или невосприимчивости
+    pub resisted: Vec<StackHandle>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpellHit {
+    pub stack: StackHandle,
+    // Снятое заклинанием здоровье, у заклинаний без урона - ноль
+    pub damage: i32,
+    pub lethal: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Defend {
+    pub stack: StackHandle,
+}
+
+#[derive(Debug, Clone)]
+pub struct Heal {
+    pub healer: StackHandle,
+    pub target: StackHandle,
+}
+
+// Один снаряд катапульты
+#[derive(Debug, Clone)]
+pub struct Bombard {
+    pub catapult: StackHandle,
+    pub section: WallSection,
+    pub hit: bool,
+    // Часть стены рухнула от этого попадания
+    pub destroyed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TowerShot {
+    pub section: WallSection,
+    pub target: StackHandle,
+    pub damage: i32,
+    pub lethal: bool,
+}
+
+// Отряд закончил движение во рву
+#[derive(Debug, Clone)]
+pub struct MoatDamage {
+    pub stack: StackHandle,
+    pub damage: i32,
+    pub lethal: bool,
+}
+
+// Высокий боевой дух дал отряду ещё один ход, а низкий - отнял ход
+#[derive(Debug, Clone)]
+pub struct Morale {
+    pub stack: StackHandle,
+    pub good: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusExpired {
+    pub stack: StackHandle,
+    pub status: Status,
 }
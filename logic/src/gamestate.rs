@@ -1,88 +1,219 @@
 use std::collections::HashMap;
 
 use common::error::AnyHow;
-use gamedata::creatures::Creature;
-use gamedata::heroes::Hero as GDHero;
+use gamedata::artifacts::Artifact;
+use gamedata::creatures::{Creature, WarMachine};
+use gamedata::heroes::{Ability, AbilityLevel, Hero as GDHero, Stats as HeroStats};
+use gamedata::spells::{Spell, SpellTarget};
+use gamedata::towns::{Town, WallSection, WallState};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
 use serde::Deserialize;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::command::Command;
-use crate::event::Event;
+use crate::event::{Event, Morale, StatusExpired};
 use crate::grid::GridPos;
+use crate::pathfinding;
 use crate::pathfinding::NavigationArray;
 use crate::stack::Stack;
-use crate::turn::Turn;
+use crate::turn;
+use crate::turn::{Tactics, Turn};
 
 mod army;
 mod commands;
 mod damage;
 mod hero;
+mod report;
+mod siege;
+mod spells;
 
+use damage::AttackType;
 use hero::Hero;
+use siege::Siege;
 
+#[derive(Clone)]
 pub struct GameState {
     // Логика
     heroes: [Option<Hero>; 2],
     stacks: HashMap<StackHandle, Stack>,
     turn: Turn,
     current_stack: StackHandle,
+    outcome: Option<BattleOutcome>,
+    // Все броски битвы идут через один генератор,
+    // так что одинаковое зерно и команды дают одинаковый исход
+    rng: SmallRng,
+    // Стены, башни и ров, если защитник сидит в городе
+    siege: Option<Siege>,
 
     // Поиск пути
     navigation_array: NavigationArray,
     reachable_cells: Vec<GridPos>,
 }
 
-#[derive(Clone, Copy, Deserialize)]
+// Сводка о герое для окна героя
+pub struct HeroInfo {
+    pub hero: GDHero,
+    pub level: u32,
+    pub stats: HeroStats,
+    pub abilities: Vec<(Ability, AbilityLevel)>,
+    pub artifacts: Vec<Artifact>,
+    pub spell_points: i32,
+    pub max_spell_points: i32,
+    // Восстановится на следующий день
+    pub daily_mana: i32,
+    pub morale: i32,
+    pub luck: i32,
+}
+
+// Итоги битвы для окна результатов
+pub struct BattleReport {
+    pub outcome: BattleOutcome,
+    pub sides: [SideReport; 2],
+}
+
+pub struct SideReport {
+    pub hero: Option<GDHero>,
+    // Погибшие существа стороны по видам
+    pub casualties: Vec<(Creature, i32)>,
+    // Опыт достаётся только герою-победителю
+    pub experience: i32,
+    pub raised: Option<(Creature, i32)>,
+    pub resurrected: Vec<(Creature, i32)>,
+    pub learned_spells: Vec<Spell>,
+}
+
+// Ожидаемый порядок ходов: остаток текущего раунда и следующий раунд целиком
+pub struct TurnOrder {
+    pub current_round: Vec<QueuedTurn>,
+    pub next_round: Vec<QueuedTurn>,
+}
+
+pub struct QueuedTurn {
+    pub stack: StackHandle,
+    // Боевой дух может дать отряду ещё один ход сразу после этого
+    pub morale_chance: bool,
+}
+
+#[derive(Clone, Deserialize)]
 pub struct Army {
     pub hero: Option<GDHero>,
+    // Заменяют данные героя первого уровня, чтобы воспроизводить битвы поздней игры
+    #[serde(default)]
+    pub hero_level: Option<u32>,
+    #[serde(default)]
+    pub hero_stats: Option<HeroStats>,
+    // Вторичные навыки с уровнями владения
+    #[serde(default)]
+    pub hero_abilities: Option<Vec<(Ability, AbilityLevel)>>,
+    #[serde(default)]
+    pub hero_spells: Option<Vec<Spell>>,
+    #[serde(default)]
+    pub hero_artifacts: Vec<Artifact>,
     pub stacks: [Option<(Creature, i32)>; 7],
+    #[serde(default)]
+    pub war_machines: Vec<WarMachine>,
+    // Город защитника превращает битву в осаду, у нападающего город не учитывается
+    #[serde(default)]
+    pub town: Option<Town>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct StackHandle(u32);
 
-#[derive(Clone, Copy, PartialEq, Debug, EnumIter)]
+#[cfg(test)]
+impl StackHandle {
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumIter)]
 pub enum Side {
     Attacker,
     Defender,
 }
 
-#[derive(Debug)]
-// Compiler is triggered by Side which is not used anywhere in the code
-// But actually is there for Debug output
-#[allow(dead_code)]
-enum Winner {
-    Side(Side),
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BattleOutcome {
+    // Все отряды одной из сторон погибли
+    Victory { winner: Side },
+    // Обе армии погибли одновременно
     Tie,
+    // Герой бежал, бросив армию
+    Retreat { side: Side },
+    // Герой откупился и сохранил армию
+    Surrender { side: Side, cost: i32 },
+}
+
+#[cfg(test)]
+impl GameState {
+    // Расстановка положения для тестов без розыгрыша ходов
+    pub fn edit_stack(&mut self, handle: StackHandle, edit: impl FnOnce(&mut Stack)) {
+        edit(self.get_stack_mut(handle));
+        self.select_stack(self.current_stack);
+    }
 }
 
 impl GameState {
     pub fn new(armies: &[Army; 2]) -> AnyHow<Self> {
+        Self::with_seed(armies, rand::random())
+    }
+
+    pub fn with_seed(armies: &[Army; 2], seed: u64) -> AnyHow<Self> {
         let attacker_army = army::form_units(&armies[0].stacks, Side::Attacker);
         let defender_army = army::form_units(&armies[1].stacks, Side::Defender);
 
-        let heroes = armies.map(|army| army.hero.map(Hero::build));
+        let heroes = [build_hero(&armies[0])?, build_hero(&armies[1])?];
 
-        let stacks = [attacker_army, defender_army]
-            .concat()
-            .into_iter()
-            .enumerate()
-            .map(|(i, v)| {
-                let handle = StackHandle(i as u32);
-                (handle, v)
-            })
-            .collect();
+        let siege = armies[1].town.map(Siege::new);
+        let is_siege = siege.is_some();
+
+        let attacker_machines = army::form_war_machines(&armies[0], Side::Attacker, is_siege);
+        let defender_machines = army::form_war_machines(&armies[1], Side::Defender, false);
+
+        let stacks = [
+            attacker_army,
+            defender_army,
+            attacker_machines,
+            defender_machines,
+        ]
+        .concat()
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let handle = StackHandle(i as u32);
+            (handle, v)
+        })
+        .collect();
+
+        let tactics = find_tactics(&heroes);
 
         let mut state = Self {
             heroes,
             stacks,
-            turn: Turn::new(),
+            turn: Turn::new(tactics),
             current_stack: StackHandle(0),
+            outcome: None,
+            rng: SmallRng::seed_from_u64(seed),
+            siege,
             navigation_array: NavigationArray::empty(),
             reachable_cells: vec![],
         };
 
-        state.update_current_stack();
+        let first_tactics_stack = tactics.and_then(|tactics| {
+            let handle = state.find_tactics_stack(tactics, None)?;
+            Some((handle, tactics))
+        });
+
+        match first_tactics_stack {
+            Some((handle, tactics)) => state.select_tactics_stack(handle, tactics),
+            None => {
+                state.update_current_stack();
+            }
+        }
+
         Ok(state)
     }
 
@@ -92,23 +223,48 @@ impl GameState {
 
     pub fn apply_command(&mut self, command: Command) -> Vec<Event> {
         assert!(commands::is_applicable(self, command));
-        let events = commands::apply(self, command);
-        println!("Command applied!");
+        let mut events = commands::apply(self, command);
 
-        if command.spends_turn() {
+        // Расстановка не тратит ходы и не меняет очерёдность сторон
+        let is_tactics = self.turn.tactics.is_some();
+
+        if command.spends_turn() && !is_tactics {
             let cur_stack = self.get_current_stack_mut();
             cur_stack.turn_state = None;
         }
 
-        if command.requires_current_stack_update() {
-            self.update_current_stack();
+        if command.requires_current_stack_update() && !is_tactics {
+            let side = self.get_current_stack().side;
+            self.turn.record_move(side);
+        }
+
+        if self.outcome.is_none() {
+            self.outcome = self.find_winner();
         }
 
-        if let Some(winner) = self.find_winner() {
-            println!("{:?} wins!", winner);
-            std::process::exit(0);
+        if self.outcome.is_some() {
+            return events;
         }
 
+        if command.allows_morale() && !is_tactics && self.roll_morale() {
+            let handle = self.current_stack;
+            self.turn.morale_stacks.push(handle);
+            self.select_stack(handle);
+            events.push(Event::Morale(Morale {
+                stack: handle,
+                good: true,
+            }));
+            return events;
+        }
+
+        // Заклинание героя может убить и текущий отряд
+        if command.requires_current_stack_update() || !self.get_current_stack().is_alive() {
+            events.extend(self.update_current_stack());
+        }
+
+        // Башни стреляют при смене раунда и могут добить последний отряд
+        self.outcome = self.find_winner();
+
         events
     }
 
@@ -132,8 +288,11 @@ impl GameState {
         self.get_stack_mut(self.current_stack)
     }
 
+    // Порядок не зависит от хеширования, иначе повтор битвы разойдётся
     pub fn units(&self) -> Vec<StackHandle> {
-        self.stacks.keys().copied().collect()
+        let mut units: Vec<StackHandle> = self.stacks.keys().copied().collect();
+        units.sort();
+        units
     }
 
     pub fn find_unit_for_cell(&self, cell: GridPos) -> Option<StackHandle> {
@@ -143,86 +302,473 @@ impl GameState {
             .find(|&handle| self.get_stack(handle).get_occupied_cells().contains(&cell))
     }
 
+    pub fn find_corpse_for_cell(&self, cell: GridPos) -> Option<StackHandle> {
+        self.units()
+            .into_iter()
+            .filter(|&handle| !self.get_stack(handle).is_alive())
+            .find(|&handle| self.get_stack(handle).get_occupied_cells().contains(&cell))
+    }
+
+    pub fn get_hero_info(&self, side: Side) -> Option<HeroInfo> {
+        let hero = self.heroes[side as usize].as_ref()?;
+
+        Some(HeroInfo {
+            hero: hero.hero,
+            level: hero.level,
+            stats: hero.stats,
+            abilities: hero
+                .abilities
+                .iter()
+                .map(|learned| (learned.ability, learned.level))
+                .collect(),
+            artifacts: hero.artifacts.clone(),
+            spell_points: hero.spell_points,
+            max_spell_points: hero.max_spell_points,
+            daily_mana: hero.daily_mana_regeneration(),
+            morale: hero.morale(),
+            luck: hero.luck(),
+        })
+    }
+
+    pub fn get_hero_level(&self, side: Side) -> Option<u32> {
+        self.heroes[side as usize].as_ref().map(|h| h.level)
+    }
+
+    pub fn get_hero_spell_points(&self, side: Side) -> Option<i32> {
+        self.heroes[side as usize].as_ref().map(|h| h.spell_points)
+    }
+
+    pub fn get_hero_spell_book(&self, side: Side) -> Option<&[Spell]> {
+        self.heroes[side as usize]
+            .as_ref()
+            .map(|h| h.spell_book.as_slice())
+    }
+
+    pub fn get_hero_spell_cost(&self, side: Side, spell: Spell) -> Option<i32> {
+        self.heroes[side as usize]
+            .as_ref()
+            .map(|h| h.spell_cost(spell))
+    }
+
+    // Цель заклинания зависит от владения школой магии героем текущей стороны
+    pub fn get_spell_target(&self, spell: Spell) -> Option<SpellTarget> {
+        let side = self.get_current_stack().side;
+        self.heroes[side as usize]
+            .as_ref()
+            .map(|h| spell.target(h.school_level(spell)))
+    }
+
+    pub fn get_spell_target_cells(&self, spell: Spell) -> Vec<GridPos> {
+        let side = self.get_current_stack().side;
+        self.heroes[side as usize].as_ref().map_or(vec![], |h| {
+            spells::target_cells(self, spell, side, h.school_level(spell))
+        })
+    }
+
+    pub fn get_spell_area(&self, spell: Spell, center: GridPos) -> Vec<GridPos> {
+        let side = self.get_current_stack().side;
+        self.heroes[side as usize].as_ref().map_or(vec![], |h| {
+            spells::area_cells(spell, h.school_level(spell), center)
+        })
+    }
+
+    pub fn get_outcome(&self) -> Option<BattleOutcome> {
+        self.outcome
+    }
+
+    // Цена капитуляции для стороны текущего отряда
+    pub fn get_surrender_cost(&self) -> i32 {
+        commands::surrender_cost(self, self.get_current_stack().side)
+    }
+
+    // Без Артиллерии и Первой помощи боевыми машинами управляет компьютер
+    pub fn is_current_stack_automatic(&self) -> bool {
+        let stack = self.get_current_stack();
+
+        let ability = match stack.creature {
+            Creature::Ballista => Ability::Artillery,
+            Creature::FirstAidTent => Ability::FirstAid,
+            Creature::Catapult => Ability::Ballistics,
+            _ => return false,
+        };
+
+        self.heroes[stack.side as usize]
+            .as_ref()
+            .and_then(|hero| hero.get_ability_level(ability))
+            .is_none()
+    }
+
+    pub fn get_current_stack_handle(&self) -> StackHandle {
+        self.current_stack
+    }
+
+    // Средний урон удара без ответа, для оценки ходов компьютером
+    pub fn get_expected_damage(
+        &self,
+        attacker: StackHandle,
+        defender: StackHandle,
+        is_ranged: bool,
+    ) -> i32 {
+        let attacker = self.get_stack(attacker);
+        let defender = self.get_stack(defender);
+
+        let attack_type = if is_ranged {
+            self.shot_type(attacker, defender)
+        } else {
+            AttackType::Melee
+        };
+
+        damage::expected_damage(&self.heroes, attacker, defender, attack_type)
+    }
+
+    // Урон заклинания героя текущей стороны по каждому задетому отряду
+    pub fn get_expected_spell_damage(
+        &self,
+        spell: Spell,
+        target: Option<GridPos>,
+    ) -> Vec<(StackHandle, i32)> {
+        let side = self.get_current_stack().side;
+        spells::expected_damage(self, spell, side, target)
+    }
+
+    // Подсмотренные Орлиным глазом заклинания
+    pub fn get_learned_spells(&self, side: Side) -> &[Spell] {
+        self.heroes[side as usize]
+            .as_ref()
+            .map_or(&[], |h| h.learned_spells.as_slice())
+    }
+
+    // Некромантия победителя поднимает скелетов из павших врагов,
+    // в том числе когда враг бежал или откупился
+    pub fn get_raised_creatures(&self, side: Side) -> Option<(Creature, i32)> {
+        if report::winner(self.outcome?) != Some(side) {
+            return None;
+        }
+
+        let percent = self.heroes[side as usize].as_ref()?.necromancy();
+        let killed_health = report::killed_health(self, side.other());
+
+        let raised = killed_health * percent / 100 / Creature::Skeleton.base_stats().health;
+        (raised > 0).then_some((Creature::Skeleton, raised))
+    }
+
+    pub fn get_siege_town(&self) -> Option<Town> {
+        self.siege.as_ref().map(|siege| siege.town)
+    }
+
+    pub fn get_wall_state(&self, section: WallSection) -> Option<WallState> {
+        self.siege.as_ref().map(|siege| siege.wall_state(section))
+    }
+
+    pub fn get_wall_health(&self, section: WallSection) -> Option<i32> {
+        self.siege
+            .as_ref()
+            .map(|siege| siege.section_health(section))
+    }
+
+    pub fn get_wall_section_cells(&self, section: WallSection) -> Vec<GridPos> {
+        siege::section_cells(section)
+    }
+
+    // Часть стены на клетке, пока в битве есть стены
+    pub fn find_wall_section_for_cell(&self, cell: GridPos) -> Option<WallSection> {
+        self.siege.as_ref().and(siege::section_for_cell(cell))
+    }
+
+    // Часть стены, которую катапульта выбирает сама
+    pub fn get_catapult_target(&self) -> Option<WallSection> {
+        self.siege.as_ref()?.preferred_target()
+    }
+
+    pub fn is_moat(&self, cell: GridPos) -> bool {
+        self.siege.is_some() && siege::is_moat(cell)
+    }
+
+    pub fn is_cell_blocked(&self, cell: GridPos, side: Side) -> bool {
+        self.siege
+            .as_ref()
+            .is_some_and(|siege| siege.is_blocked(cell, side))
+    }
+
+    pub fn get_battle_report(&self) -> Option<BattleReport> {
+        report::build(self)
+    }
+
+    pub fn get_round(&self) -> u32 {
+        self.turn.round
+    }
+
+    pub fn get_turn_order(&self) -> TurnOrder {
+        let morale = self
+            .heroes
+            .each_ref()
+            .map(|hero| hero.as_ref().map_or(0, |hero| hero.morale()));
+        turn::predict(&self.stacks, self.current_stack, &self.turn, morale)
+    }
+
+    pub fn is_spell_available(&self, spell: Spell) -> bool {
+        commands::is_spell_available(self, spell)
+    }
+
+    // Заклинания, которым нужны препятствия или смена хозяина отряда, не реализованы
+    pub fn is_spell_supported(&self, spell: Spell) -> bool {
+        spells::is_castable(spell)
+    }
+
+    fn add_stack(&mut self, stack: Stack) -> StackHandle {
+        let handle = self
+            .stacks
+            .keys()
+            .map(|handle| handle.0 + 1)
+            .max()
+            .map_or(StackHandle(0), StackHandle);
+
+        self.stacks.insert(handle, stack);
+        handle
+    }
+
     pub fn reachable_cells(&self) -> &Vec<GridPos> {
         &self.reachable_cells
     }
 
-    fn update_current_stack(&mut self) {
-        if let Some(handle) = self.find_active_stack() {
-            self.current_stack = handle;
+    pub fn is_tactics_phase(&self) -> bool {
+        self.turn.tactics.is_some()
+    }
 
-            let stack = self.get_current_stack_mut();
-            stack.defending = false;
-            println!("Current stack is {}", stack);
+    // Возвращает события окончания эффектов, если начался новый раунд
+    fn update_current_stack(&mut self) -> Vec<Event> {
+        let mut events = vec![];
 
-            let stack_head = stack.head;
-            let is_flying = stack.creature.is_flying();
-            let stack_speed = stack.speed();
+        if let Some(tactics) = self.turn.tactics {
+            if let Some(handle) = self.find_tactics_stack(tactics, Some(self.current_stack)) {
+                self.select_tactics_stack(handle, tactics);
+                return events;
+            }
+
+            // Переставлять нечего
+            self.turn.tactics = None;
+        }
 
-            let navigation_array = NavigationArray::new(stack_head, self, is_flying);
-            let reachable_cells = navigation_array.get_reachable_cells(stack_speed);
-            self.navigation_array = navigation_array;
-            self.reachable_cells = reachable_cells;
+        if let Some(handle) = self.find_active_stack() {
+            self.select_stack(handle);
+
+            if self.roll_bad_morale() {
+                let stack = self.get_current_stack_mut();
+                stack.turn_state = None;
+                let side = stack.side;
+                self.turn.record_move(side);
+                events.push(Event::Morale(Morale {
+                    stack: handle,
+                    good: false,
+                }));
+                events.extend(self.update_current_stack());
+            }
         } else {
             if !self.turn.try_advance_phase() {
                 self.turn = self.turn.next();
 
-                for stack in self.stacks.values_mut() {
+                for (&handle, stack) in self.stacks.iter_mut() {
+                    for status in stack.tick_effects() {
+                        events.push(Event::StatusExpired(StatusExpired {
+                            stack: handle,
+                            status,
+                        }));
+                    }
                     stack.refresh_for_next_turn();
                 }
+
+                for hero in self.heroes.iter_mut().flatten() {
+                    hero.refresh_for_next_turn();
+                }
+
+                events.extend(siege::tower_volley(self));
             }
-            self.update_current_stack();
+            events.extend(self.update_current_stack());
         }
+
+        events
     }
-    fn find_active_stack(&self) -> Option<StackHandle> {
-        let mut handles = self.units();
-        // Преимущество при равенстве скоростей у того кто ходил вторым на прошлом ходу
-        handles
-            .sort_unstable_by_key(|&handle| self.get_stack(handle).side == self.turn.priority_side);
+
+    fn select_stack(&mut self, handle: StackHandle) {
+        self.current_stack = handle;
+
+        let stack = self.get_current_stack_mut();
+        stack.defending = false;
+
+        let stack_head = stack.head;
+        let side = stack.side;
+        let is_flying = stack.creature.is_flying();
+        let stack_speed = stack.speed();
+
+        let navigation_array = NavigationArray::new(stack_head, self, side, is_flying);
+        let reachable_cells = navigation_array.get_reachable_cells(stack_speed);
+        self.navigation_array = navigation_array;
+        self.reachable_cells = reachable_cells;
+    }
+
+    // Лидерство героя даёт отряду шанс на ещё один ход, но не чаще раза за раунд
+    fn roll_morale(&mut self) -> bool {
+        let stack = self.get_current_stack();
+
+        let can_act_again = stack.is_alive()
+            && stack.can_act()
+            && !stack.creature.is_war_machine()
+            && !self.turn.morale_stacks.contains(&self.current_stack);
+        if !can_act_again {
+            return false;
+        }
+
+        let morale = self.stack_morale(self.current_stack);
+        hero::roll_fortune(morale, &mut self.rng)
+    }
+
+    // Низкий боевой дух может лишить отряд хода в начале его очереди
+    fn roll_bad_morale(&mut self) -> bool {
+        let stack = self.get_current_stack();
+
+        let can_freeze = stack.turn_state == Some(turn::Phase::Fresh)
+            && stack.can_act()
+            && !stack.creature.is_war_machine();
+        if !can_freeze {
+            return false;
+        }
+
+        let morale = self.stack_morale(self.current_stack);
+        hero::roll_fortune(-morale, &mut self.rng)
+    }
+
+    fn stack_morale(&self, handle: StackHandle) -> i32 {
+        let stack = self.get_stack(handle);
+        let hero_morale = self.heroes[stack.side as usize]
+            .as_ref()
+            .map_or(0, |hero| hero.morale());
+
+        hero::cap_fortune(hero_morale + stack.morale())
+    }
+
+    // Отряды стороны выбираются по очереди, после последнего - снова первый
+    fn find_tactics_stack(
+        &self,
+        tactics: Tactics,
+        previous: Option<StackHandle>,
+    ) -> Option<StackHandle> {
+        let handles: Vec<StackHandle> = self
+            .units()
+            .into_iter()
+            .filter(|&handle| {
+                let stack = self.get_stack(handle);
+                stack.side == tactics.side && stack.is_alive() && !stack.creature.is_war_machine()
+            })
+            .collect();
 
         handles
             .iter()
-            .map(|&handle| (handle, self.get_stack(handle)))
-            .filter(|(_handle, stack)| stack.is_alive())
-            .filter(|(_handle, stack)| {
-                stack
-                    .turn_state
-                    .map_or(false, |phase| phase == self.turn.current_phase)
-            })
-            .fold(None, |acc, current| {
-                // Без max_first тяжко
-                fn key((_, stack): (StackHandle, &Stack)) -> i32 {
-                    stack.speed()
-                }
-                match acc {
-                    None => Some(current),
-                    Some(acc) if key(current) > key(acc) => Some(current),
-                    _ => acc,
-                }
+            .find(|&&handle| previous.is_some_and(|previous| handle > previous))
+            .or(handles.first())
+            .copied()
+    }
+
+    // Отряд может встать на любую клетку своей зоны без ограничения дальности
+    fn select_tactics_stack(&mut self, handle: StackHandle, tactics: Tactics) {
+        self.current_stack = handle;
+
+        let stack = self.get_current_stack();
+        let (creature, side, head) = (stack.creature, stack.side, stack.head);
+
+        let reachable_cells = GridPos::X_RANGE
+            .flat_map(|x| GridPos::Y_RANGE.map(move |y| GridPos::new(x, y)))
+            .filter(|&cell| {
+                pathfinding::get_occupied_cells_for(creature, side, cell).is_some_and(|cells| {
+                    cells
+                        .into_iter()
+                        .all(|cell| tactics.contains(cell) && !self.is_cell_blocked(cell, side))
+                })
             })
-            .map(|(handle, _stack)| handle)
+            .collect();
+
+        // Путь строится как для летающего, чтобы чужие отряды не мешали
+        self.navigation_array = NavigationArray::new(head, self, side, true);
+        self.reachable_cells = reachable_cells;
+    }
+
+    // Стрелок за стеной получает штраф, только если стреляет снаружи
+    fn shot_type(&self, attacker: &Stack, defender: &Stack) -> AttackType {
+        let obstructed = self.siege.as_ref().is_some_and(|siege| {
+            !attacker.creature.is_war_machine()
+                && siege::is_shot_obstructed(siege, attacker.head, defender.head)
+        });
+
+        AttackType::Shoot {
+            distance: attacker.distance_to(defender),
+            obstructed,
+        }
+    }
+
+    fn has_war_machine(&self, side: Side, machine: Creature) -> bool {
+        self.stacks
+            .values()
+            .any(|stack| stack.side == side && stack.creature == machine && stack.is_alive())
+    }
+
+    fn find_active_stack(&self) -> Option<StackHandle> {
+        turn::pick_next(
+            &self.stacks,
+            self.turn.current_phase,
+            self.turn.priority_side,
+        )
     }
 
-    fn find_winner(&self) -> Option<Winner> {
+    fn find_winner(&self) -> Option<BattleOutcome> {
         let alive_sides = [Side::Attacker, Side::Defender]
             .into_iter()
             .filter(|&side| {
                 self.stacks
                     .values()
                     .filter(|stack| stack.side == side)
-                    .any(|stack| stack.is_alive())
+                    // Одни боевые машины битву не продолжают
+                    .any(|stack| stack.is_alive() && !stack.creature.is_war_machine())
             })
             .collect::<Vec<Side>>();
 
         match alive_sides.len() {
-            0 => Some(Winner::Tie),
-            1 => Some(Winner::Side(alive_sides[0])),
+            0 => Some(BattleOutcome::Tie),
+            1 => Some(BattleOutcome::Victory {
+                winner: alive_sides[0],
+            }),
             2 => None,
             _ => unreachable!(),
         }
     }
 }
 
+fn build_hero(army: &Army) -> AnyHow<Option<Hero>> {
+    army.hero.map(|hero| Hero::build(hero, army)).transpose()
+}
+
+// Расстановку получает сторона, чей герой владеет Тактикой лучше противника
+fn find_tactics(heroes: &[Option<Hero>; 2]) -> Option<Tactics> {
+    let tactics_level = |side: Side| {
+        heroes[side as usize]
+            .as_ref()
+            .and_then(|hero| hero.get_ability_level(Ability::Tactics))
+    };
+
+    Side::iter().find_map(|side| {
+        let level = tactics_level(side)?;
+        if Some(level) <= tactics_level(side.other()) {
+            return None;
+        }
+
+        let columns = match level {
+            AbilityLevel::Basic => 2,
+            AbilityLevel::Advanced => 4,
+            AbilityLevel::Expert => 6,
+        };
+
+        Some(Tactics { side, columns })
+    })
+}
+
 impl Side {
     pub fn other(self) -> Self {
         match self {
@@ -231,3 +777,95 @@ impl Side {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Move;
+    use crate::testing::{army, battle};
+    use crate::turn::Phase;
+
+    fn tactician(level: Option<AbilityLevel>) -> Army {
+        Army {
+            hero: Some(GDHero::Orrin),
+            hero_abilities: Some(
+                level
+                    .map(|level| (Ability::Tactics, level))
+                    .into_iter()
+                    .collect(),
+            ),
+            ..army(&[(Creature::Pikeman, 10)])
+        }
+    }
+
+    fn tactics(
+        attacker: Option<AbilityLevel>,
+        defender: Option<AbilityLevel>,
+    ) -> Option<(Side, i32)> {
+        let state = battle(tactician(attacker), tactician(defender));
+        state
+            .turn
+            .tactics
+            .map(|tactics| (tactics.side, tactics.columns))
+    }
+
+    #[test]
+    fn better_tactics_gets_placement() {
+        use AbilityLevel::*;
+
+        assert_eq!(tactics(None, None), None);
+        assert_eq!(tactics(Some(Basic), None), Some((Side::Attacker, 2)));
+        assert_eq!(tactics(Some(Advanced), Some(Advanced)), None);
+        assert_eq!(
+            tactics(Some(Basic), Some(Expert)),
+            Some((Side::Defender, 6))
+        );
+
+        // Сторона без героя Тактике не противостоит
+        let state = battle(army(&[(Creature::Pikeman, 10)]), tactician(Some(Advanced)));
+        assert!(state.is_tactics_phase());
+        assert_eq!(state.get_current_stack().side, Side::Defender);
+    }
+
+    #[test]
+    fn placement_is_limited_to_tactics_columns() {
+        let mut state = battle(
+            tactician(Some(AbilityLevel::Advanced)),
+            army(&[(Creature::Pikeman, 10)]),
+        );
+        assert_eq!(state.get_current_stack().side, Side::Attacker);
+
+        let to = |x| {
+            Command::Move(Move {
+                destination: GridPos::new(x, 2),
+            })
+        };
+        assert!(!state.is_command_applicable(to(5)));
+        assert!(state.is_command_applicable(to(4)));
+
+        // Перестановка не тратит ход
+        state.apply_command(to(4));
+        assert!(state.is_tactics_phase());
+        let pikeman = state.get_current_stack();
+        assert_eq!(pikeman.head, GridPos::new(4, 2));
+        assert_eq!(pikeman.turn_state, Some(Phase::Fresh));
+    }
+
+    #[test]
+    fn ending_tactics_starts_first_round() {
+        let mut state = battle(
+            tactician(Some(AbilityLevel::Basic)),
+            army(&[(Creature::Pikeman, 10)]),
+        );
+        assert!(state.is_command_applicable(Command::EndTactics));
+
+        state.apply_command(Command::EndTactics);
+        assert!(!state.is_tactics_phase());
+        assert!(!state.is_command_applicable(Command::EndTactics));
+        assert_eq!(state.turn.round, 1);
+        assert!(state
+            .units()
+            .into_iter()
+            .all(|handle| state.get_stack(handle).turn_state == Some(Phase::Fresh)));
+    }
+}
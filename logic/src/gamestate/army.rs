@@ -1,6 +1,6 @@
-use gamedata::creatures::Creature;
+use gamedata::creatures::{Creature, WarMachine};
 
-use super::Side;
+use super::{Army, Side};
 use crate::grid::GridPos;
 use crate::pathfinding::head_from_tail;
 use crate::stack::Stack;
@@ -35,3 +35,99 @@ pub fn form_units(starting_army: &[Option<(Creature, i32)>; 7], side: Side) -> V
         })
         .collect()
 }
+
+// Машины стоят у края поля на клетках, свободных от основной расстановки
+fn war_machine_row(machine: WarMachine) -> i32 {
+    match machine {
+        WarMachine::AmmoCart => 2,
+        WarMachine::Ballista => 4,
+        WarMachine::FirstAidTent => 10,
+        WarMachine::Catapult => 8,
+    }
+}
+
+// Машины даёт и список армии, и надетые на героя артефакты
+// Под стенами города у нападающего всегда есть катапульта
+pub fn form_war_machines(army: &Army, side: Side, is_siege: bool) -> Vec<Stack> {
+    let starting_x = *match side {
+        Side::Attacker => GridPos::X_RANGE.start(),
+        Side::Defender => GridPos::X_RANGE.end(),
+    };
+
+    let mut placed: Vec<WarMachine> = vec![];
+    let artifacts = army.hero.map_or(&[][..], |_| &army.hero_artifacts);
+    let from_artifacts = artifacts.iter().filter_map(|a| a.war_machine());
+    let catapult = is_siege.then_some(WarMachine::Catapult);
+
+    for machine in army
+        .war_machines
+        .iter()
+        .copied()
+        .chain(from_artifacts)
+        .chain(catapult)
+    {
+        if !placed.contains(&machine) {
+            placed.push(machine);
+        }
+    }
+
+    placed
+        .into_iter()
+        .map(|machine| {
+            let creature = machine.creature();
+            let tail = GridPos::new(starting_x, war_machine_row(machine));
+            let head = head_from_tail(creature, side, tail);
+            Stack::new(creature, 1, head, side)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::artifacts::Artifact;
+    use gamedata::heroes::Hero as GDHero;
+
+    use super::*;
+    use crate::testing::army;
+
+    #[test]
+    fn war_machines_stand_on_their_rows_once() {
+        let army = Army {
+            hero: Some(GDHero::Orrin),
+            hero_artifacts: vec![Artifact::Ballista],
+            war_machines: vec![WarMachine::Ballista, WarMachine::AmmoCart],
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+
+        let stacks = form_war_machines(&army, Side::Attacker, true);
+        let creatures: Vec<Creature> = stacks.iter().map(|stack| stack.creature).collect();
+        assert_eq!(
+            creatures,
+            [Creature::Ballista, Creature::AmmoCart, Creature::Catapult]
+        );
+
+        // Машины стоят у левого края, каждая в своём ряду
+        for (stack, row) in stacks.iter().zip([4, 2, 8]) {
+            let cells = stack.get_occupied_cells();
+            assert!(cells.iter().all(|cell| cell.y == row && cell.x <= 2));
+        }
+    }
+
+    #[test]
+    fn machines_from_artifacts_need_a_hero() {
+        let army = Army {
+            hero_artifacts: vec![Artifact::AmmoCart],
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        assert!(form_war_machines(&army, Side::Defender, false).is_empty());
+
+        let army = Army {
+            hero: Some(GDHero::Orrin),
+            ..army
+        };
+        let stacks = form_war_machines(&army, Side::Defender, false);
+        assert_eq!(stacks.len(), 1);
+        assert_eq!(stacks[0].creature, Creature::AmmoCart);
+        assert_eq!(stacks[0].side, Side::Defender);
+    }
+}
@@ -1,15 +1,38 @@
+use gamedata::spells::Spell;
+
 use super::GameState;
 use crate::command::Command;
 use crate::event::Event;
 
 mod attack;
+mod bombard;
 mod cast;
 mod defend;
+mod end_tactics;
+mod heal;
 mod r#move;
+mod retreat;
 mod shoot;
+mod surrender;
 mod wait;
 
+pub use surrender::surrender_cost;
+
 pub fn is_applicable(state: &GameState, command: Command) -> bool {
+    // После окончания битвы команды не принимаются
+    if state.outcome.is_some() {
+        return false;
+    }
+
+    // При расстановке отряды только переходят на новые места
+    let is_tactics_command = matches!(
+        command,
+        Command::Move(_) | Command::Wait | Command::EndTactics
+    );
+    if state.turn.tactics.is_some() && !is_tactics_command {
+        return false;
+    }
+
     match command {
         Command::Defend => defend::is_applicable(state),
         Command::Wait => wait::is_applicable(state),
@@ -17,6 +40,11 @@ pub fn is_applicable(state: &GameState, command: Command) -> bool {
         Command::Attack(command) => attack::is_applicable(command, state),
         Command::Shoot(command) => shoot::is_applicable(command, state),
         Command::Cast(command) => cast::is_applicable(command, state),
+        Command::Heal(command) => heal::is_applicable(command, state),
+        Command::Bombard(command) => bombard::is_applicable(command, state),
+        Command::EndTactics => end_tactics::is_applicable(state),
+        Command::Retreat => retreat::is_applicable(state),
+        Command::Surrender => surrender::is_applicable(state),
     }
 }
 
@@ -28,5 +56,14 @@ pub fn apply(state: &mut GameState, command: Command) -> Vec<Event> {
         Command::Attack(command) => attack::apply(command, state),
         Command::Shoot(command) => shoot::apply(command, state),
         Command::Cast(command) => cast::apply(command, state),
+        Command::Heal(command) => heal::apply(command, state),
+        Command::Bombard(command) => bombard::apply(command, state),
+        Command::EndTactics => end_tactics::apply(state),
+        Command::Retreat => retreat::apply(state),
+        Command::Surrender => surrender::apply(state),
     }
 }
+
+pub fn is_spell_available(state: &GameState, spell: Spell) -> bool {
+    cast::is_available(spell, state)
+}
@@ -1,11 +1,15 @@
 use gamedata::creatures::Ability;
+use rand::rngs::SmallRng;
+use rand::Rng;
 
 use super::r#move;
 use crate::command::{Attack, Move};
+use crate::effect::{Status, StatusEffect};
 use crate::event::{Attack as AttackEvent, Event, Strike};
 use crate::gamestate::damage::{deal_damage, AttackType};
-use crate::gamestate::GameState;
+use crate::gamestate::{spells, GameState};
 use crate::pathfinding;
+use crate::stack::Stack;
 
 const ATTACK_TYPE: AttackType = AttackType::Melee;
 
@@ -14,13 +18,6 @@ pub fn is_applicable(command: Attack, state: &GameState) -> bool {
     let current_side = current_stack.side;
     let is_wide = current_stack.creature.is_wide();
 
-    let occupied_cells = pathfinding::get_occupied_cells_for(
-        current_stack.creature,
-        current_side,
-        current_stack.head,
-    )
-    .unwrap();
-
     let potential_pos = pathfinding::unit_position_for_attack(
         command.attack_position,
         command.attack_direction,
@@ -28,17 +25,24 @@ pub fn is_applicable(command: Attack, state: &GameState) -> bool {
         is_wide,
     );
 
+    // Боевые машины не ходят в рукопашную
+    if current_stack.creature.is_war_machine() {
+        return false;
+    }
+
     // успех в случае
     // 1. на позиции есть существо
-    // 2. оно - враг
-    // 3. атакующий может дойти до позиции атаки и поместиться там
+    // 2. оно - враг (для берсерка - любой другой отряд)
+    // 3. атакующий уже стоит головой на позиции атаки или может дойти до неё и поместиться там
+    //    (клетка хвоста широкого отряда не подходит: голова ушла бы за край поля)
     state
         .find_unit_for_cell(command.attack_position)
+        .filter(|&handle| handle != state.current_stack)
         .map(|handle| state.get_stack(handle))
-        .filter(|stack| stack.side != current_side)
+        .filter(|stack| current_stack.is_enemy(stack))
         .and(potential_pos)
         .filter(|creature_pos| {
-            occupied_cells.contains(creature_pos)
+            *creature_pos == current_stack.head
                 || r#move::is_applicable(
                     Move {
                         destination: *creature_pos,
@@ -74,45 +78,101 @@ pub fn apply(command: Attack, state: &mut GameState) -> Vec<Event> {
     );
     events.extend(move_events.clone());
 
+    // Ров мог убить отряд по дороге к цели
+    if !state.get_current_stack().is_alive() {
+        return events;
+    }
+
     let defender_handle = state.find_unit_for_cell(command.attack_position).unwrap();
 
     let [attacker, defender] =
         common::map::get_many_mut(&mut state.stacks, [state.current_stack, defender_handle])
             .unwrap();
 
-    deal_damage(&state.heroes, attacker, defender, ATTACK_TYPE);
+    let damage = deal_damage(
+        &state.heroes,
+        attacker,
+        defender,
+        ATTACK_TYPE,
+        &mut state.rng,
+    );
+    let reflected = fire_shield(attacker, defender, damage);
     strikes.push(Strike {
+        damage,
         retaliation: false,
         lethal: !defender.is_alive(),
+        reflected,
+        reflected_lethal: !attacker.is_alive(),
     });
 
+    // Наложенное ударом заклинание достаётся цели после снятия ослепления,
+    // но ослеплённая ударом цель уже не отвечает
+    let mut inflicted = spell_on_hit(attacker, defender, &mut state.rng);
+    let is_stunned = inflicted.is_some_and(|effect| effect.prevents_acting());
+
     if defender.is_alive()
+        && attacker.is_alive()
+        && !is_stunned
         && defender.retaliation_count.has_retaliation()
+        && defender.retaliation_multiplier() > 0.0
         && !attacker.creature.has_ability(Ability::NoRetaliation)
     {
         defender.retaliation_count.decrement();
-        deal_damage(&state.heroes, defender, attacker, ATTACK_TYPE);
+        let damage = deal_damage(
+            &state.heroes,
+            defender,
+            attacker,
+            AttackType::Retaliation,
+            &mut state.rng,
+        );
+        let reflected = fire_shield(defender, attacker, damage);
         strikes.push(Strike {
+            damage,
             retaliation: true,
             lethal: !attacker.is_alive(),
+            reflected,
+            reflected_lethal: !defender.is_alive(),
         });
+
+        if let Some(effect) = spell_on_hit(defender, attacker, &mut state.rng) {
+            attacker.apply_effect(effect);
+        }
     }
 
     if defender.is_alive()
         && attacker.is_alive()
         && attacker.creature.has_ability(Ability::DoubleStrike)
     {
-        println!("Using double strike!");
-        deal_damage(&state.heroes, attacker, defender, ATTACK_TYPE);
+        let damage = deal_damage(
+            &state.heroes,
+            attacker,
+            defender,
+            ATTACK_TYPE,
+            &mut state.rng,
+        );
+        let reflected = fire_shield(attacker, defender, damage);
         strikes.push(Strike {
+            damage,
             retaliation: false,
-            lethal: !attacker.is_alive(),
+            lethal: !defender.is_alive(),
+            reflected,
+            reflected_lethal: !attacker.is_alive(),
         });
+
+        if inflicted.is_none() {
+            inflicted = spell_on_hit(attacker, defender, &mut state.rng);
+        }
+    }
+
+    defender.remove_effects(|e| e.breaks_on_attack());
+    if let Some(effect) = inflicted {
+        defender.apply_effect(effect);
     }
 
     let attack = Event::Attack(AttackEvent {
         attacker: state.current_stack,
         defender: defender_handle,
+        direction: command.attack_direction,
         strikes,
     });
 
@@ -140,3 +200,173 @@ pub fn apply(command: Attack, state: &mut GameState) -> Vec<Event> {
 
     events
 }
+
+// Заклинания существ при ударе не зависят от ответного удара
+fn spell_on_hit(striker: &Stack, target: &Stack, rng: &mut SmallRng) -> Option<StatusEffect> {
+    if !striker.is_alive() || !target.is_alive() {
+        return None;
+    }
+
+    let (spell, percent, rounds) =
+        striker
+            .creature
+            .abilities()
+            .into_iter()
+            .find_map(|ability| match ability {
+                Ability::SpellOnHit {
+                    spell,
+                    percent,
+                    rounds,
+                } => Some((spell, percent, rounds)),
+                _ => None,
+            })?;
+
+    if spells::is_immune(target, spell) || rng.gen_range(0..100) >= percent {
+        return None;
+    }
+
+    let status = Status::from_spell(spell)?;
+    Some(StatusEffect::new(status, None, Some(rounds)))
+}
+
+// Огненный щит цели возвращает бьющему часть нанесённого урона
+fn fire_shield(striker: &mut Stack, target: &Stack, damage: i32) -> i32 {
+    let share = target.fire_shield();
+    if share == 0.0 || !striker.is_alive() {
+        return 0;
+    }
+
+    let health_before = striker.total_health();
+    striker.receive_damage((damage as f32 * share) as i32);
+    health_before - striker.total_health()
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::Creature;
+    use gamedata::heroes::AbilityLevel;
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::command::{Attack, Command};
+    use crate::event::Attack as AttackEvent;
+    use crate::gamestate::Side;
+    use crate::grid::{AttackDirection, GridPos};
+    use crate::testing::{army, battle};
+
+    #[test]
+    fn wide_stack_does_not_attack_from_its_tail() {
+        let mut state = battle(
+            army(&[(Creature::Griffin, 10)]),
+            army(&[(Creature::Pikeman, 10)]),
+        );
+
+        // Грифон стоит хвостом у левого края, враг вплотную под хвостом
+        let griffin = state.current_stack;
+        assert_eq!(
+            state.get_stack(griffin).get_occupied_cells(),
+            [GridPos::new(2, 6), GridPos::new(1, 6)]
+        );
+
+        let pikeman = state.units().into_iter().find(|&h| h != griffin).unwrap();
+        state.edit_stack(pikeman, |stack| stack.head = GridPos::new(1, 7));
+
+        let attacks: Vec<Command> = AttackDirection::iter()
+            .map(|attack_direction| {
+                Command::Attack(Attack {
+                    attack_position: GridPos::new(1, 7),
+                    attack_direction,
+                })
+            })
+            .filter(|&command| state.is_command_applicable(command))
+            .collect();
+
+        // Удар с места головой остаётся доступен
+        assert!(!attacks.is_empty());
+
+        for command in attacks {
+            let mut state = state.clone();
+            state.apply_command(command);
+            assert!(state.get_stack(griffin).head.x >= 1);
+        }
+    }
+
+    // Удар копейщиков по соседнему отряду с наложенным эффектом
+    fn attack_stack_with(status: Status, level: AbilityLevel) -> AttackEvent {
+        let (_, attack) = attack_adjacent(Creature::Pikeman, |stack| {
+            stack.apply_effect(StatusEffect::new(status, Some(level), Some(1)));
+        });
+        attack
+    }
+
+    // Первый отряд бьёт копейщиков, поставленных вплотную справа
+    fn attack_adjacent(
+        creature: Creature,
+        edit: impl FnOnce(&mut Stack),
+    ) -> (GameState, AttackEvent) {
+        let mut state = battle(army(&[(creature, 10)]), army(&[(Creature::Pikeman, 10)]));
+
+        let attacker = state.current_stack;
+        let defender = state.units().into_iter().find(|&h| h != attacker).unwrap();
+        let head = state.get_stack(attacker).head;
+        state.edit_stack(defender, |stack| {
+            stack.head = head.relative(1, 0);
+            edit(stack);
+        });
+
+        let command = AttackDirection::iter()
+            .map(|attack_direction| {
+                Command::Attack(Attack {
+                    attack_position: head.relative(1, 0),
+                    attack_direction,
+                })
+            })
+            .find(|&command| state.is_command_applicable(command))
+            .unwrap();
+
+        let events = state.apply_command(command);
+        let Some(Event::Attack(attack)) = events.last() else {
+            panic!("attack expected");
+        };
+        let attack = attack.clone();
+        (state, attack)
+    }
+
+    #[test]
+    fn fire_shield_burns_attacker() {
+        let attack = attack_stack_with(Status::FireShield, AbilityLevel::Basic);
+
+        // Щит возвращает пятую часть удара, а ответный удар щит не задевает
+        let strike = &attack.strikes[0];
+        assert_eq!(strike.reflected, strike.damage / 5);
+        assert!(strike.reflected > 0);
+        assert_eq!(attack.strikes[1].reflected, 0);
+    }
+
+    #[test]
+    fn expert_blind_prevents_retaliation() {
+        let attack = attack_stack_with(Status::Blind, AbilityLevel::Advanced);
+        assert_eq!(attack.strikes.len(), 2);
+
+        let attack = attack_stack_with(Status::Blind, AbilityLevel::Expert);
+        assert_eq!(attack.strikes.len(), 1);
+        assert!(!attack.strikes[0].retaliation);
+    }
+
+    #[test]
+    fn spell_on_hit_lands_for_creature_duration() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let dragon_fly = Stack::new(Creature::DragonFly, 10, GridPos::new(1, 1), Side::Attacker);
+        let pikeman = Stack::new(Creature::Pikeman, 10, GridPos::new(2, 1), Side::Defender);
+
+        let effect = spell_on_hit(&dragon_fly, &pikeman, &mut rng).unwrap();
+        assert_eq!(effect.status, Status::Weakness);
+        assert_eq!(effect.rounds_left, Some(3));
+
+        let (state, attack) = attack_adjacent(Creature::DragonFly, |_| {});
+        assert!(state
+            .get_stack(attack.defender)
+            .has_status(Status::Weakness));
+    }
+}
@@ -0,0 +1,130 @@
+use gamedata::creatures::Creature;
+use rand::Rng;
+
+use super::Event;
+use crate::command::Bombard;
+use crate::event::Bombard as BombardEvent;
+use crate::gamestate::{siege, GameState};
+
+pub fn is_applicable(command: Bombard, state: &GameState) -> bool {
+    let is_catapult = state.get_current_stack().creature == Creature::Catapult;
+
+    is_catapult
+        && state
+            .siege
+            .as_ref()
+            .is_some_and(|siege| siege.is_standing(command.section))
+}
+
+pub fn apply(command: Bombard, state: &mut GameState) -> Vec<Event> {
+    let side = state.get_current_stack().side;
+    let (shots, hit_chance) = siege::catapult_shots(state, side);
+
+    let mut events = vec![];
+    let mut section = command.section;
+
+    for _ in 0..shots {
+        let hit = state.rng.gen_range(0..100) < hit_chance;
+
+        let siege = state.siege.as_mut().unwrap();
+        let destroyed = hit && siege.damage(section);
+
+        events.push(Event::Bombard(BombardEvent {
+            catapult: state.current_stack,
+            section,
+            hit,
+            destroyed,
+        }));
+
+        // Второй снаряд летит в следующую уцелевшую часть стены
+        if siege.section_health(section) == 0 {
+            match siege.preferred_target() {
+                Some(next) => section = next,
+                None => break,
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::heroes::{Ability, AbilityLevel, Hero as GDHero};
+    use gamedata::towns::{Town, WallSection};
+
+    use super::*;
+    use crate::gamestate::Army;
+    use crate::testing::{army, battle};
+
+    // Осада Замка, ход у катапульты нападающего
+    fn siege(ballistics: Option<AbilityLevel>) -> GameState {
+        let attacker = Army {
+            hero: Some(GDHero::Orrin),
+            hero_abilities: Some(
+                ballistics
+                    .map(|level| (Ability::Ballistics, level))
+                    .into_iter()
+                    .collect(),
+            ),
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let defender = Army {
+            town: Some(Town::Castle),
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let mut state = battle(attacker, defender);
+
+        state.current_stack = state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).creature == Creature::Catapult)
+            .unwrap();
+        state
+    }
+
+    fn bombard(state: &mut GameState, section: WallSection) -> Vec<(bool, bool)> {
+        let command = Bombard { section };
+        assert!(is_applicable(command, state));
+
+        apply(command, state)
+            .into_iter()
+            .map(|event| match event {
+                Event::Bombard(bombard) => (bombard.hit, bombard.destroyed),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ballistics_adds_second_shot() {
+        let mut state = siege(None);
+        assert_eq!(bombard(&mut state, WallSection::UpperWall).len(), 1);
+
+        let mut state = siege(Some(AbilityLevel::Advanced));
+        assert_eq!(bombard(&mut state, WallSection::UpperWall).len(), 2);
+    }
+
+    #[test]
+    fn every_hit_takes_one_wall_health() {
+        let mut state = siege(Some(AbilityLevel::Expert));
+        let section = WallSection::Gate;
+        let mut health = WallSection::MAX_HEALTH;
+
+        while state.siege.as_ref().unwrap().is_standing(section) {
+            let shots = bombard(&mut state, section);
+            let hits = shots.iter().filter(|&&(hit, _)| hit).count() as i32;
+            let new_health = state.siege.as_ref().unwrap().section_health(section);
+
+            assert_eq!(new_health, i32::max(health - hits, 0));
+            assert_eq!(
+                shots.iter().any(|&(_, destroyed)| destroyed),
+                new_health == 0
+            );
+            health = new_health;
+        }
+
+        // Рухнувшую часть стены больше не обстреливают
+        assert!(!is_applicable(Bombard { section }, &state));
+    }
+}
@@ -1,14 +1,66 @@
+use gamedata::artifacts::Artifact;
+use gamedata::spells::{Spell, SpellLevel, SpellType};
+
 use super::Event;
 use crate::command::Cast;
 use crate::event::Cast as EventCast;
-use crate::gamestate::GameState;
+use crate::gamestate::{spells, GameState};
+
+pub fn is_applicable(cast: Cast, state: &GameState) -> bool {
+    let side = state.get_current_stack().side;
+
+    is_available(cast.spell, state)
+        && spells::is_applicable(
+            state,
+            state.heroes[side as usize].as_ref().unwrap(),
+            side,
+            cast,
+        )
+}
+
+// Можно ли выбрать заклинание в книге, не учитывая цель
+pub fn is_available(spell: Spell, state: &GameState) -> bool {
+    let side = state.get_current_stack().side;
+
+    let Some(hero) = state.heroes[side as usize].as_ref() else {
+        return false;
+    };
 
-pub fn is_applicable(_cast: Cast, _state: &GameState) -> bool {
-    true
+    // Плащ отречения действует на обоих героев
+    let is_recanted = spell.level() > SpellLevel::Second
+        && state
+            .heroes
+            .iter()
+            .flatten()
+            .any(|h| h.has_artifact(Artifact::RecantersCloak));
+
+    // Герой колдует не больше одного раза за раунд
+    !is_recanted
+        && matches!(spell.r#type(), SpellType::Battle)
+        && hero.knows_spell(spell)
+        && !hero.has_casted
+        && hero.spell_points >= hero.spell_cost(spell)
+        && spells::is_castable(spell)
 }
-pub fn apply(cast: Cast, _state: &mut GameState) -> Vec<Event> {
+
+pub fn apply(cast: Cast, state: &mut GameState) -> Vec<Event> {
+    let side = state.get_current_stack().side;
+
+    let hero = state.heroes[side as usize].as_mut().unwrap();
+    hero.spell_points -= hero.spell_cost(cast.spell);
+    hero.has_casted = true;
+
+    let (affected, resisted) = spells::apply(state, side, cast);
+
+    if let Some(enemy) = state.heroes[side.other() as usize].as_mut() {
+        enemy.try_learn_spell(cast.spell, &mut state.rng);
+    }
+
     vec![Event::Cast(EventCast {
         spell: cast.spell,
+        side,
         target: cast.target,
+        affected,
+        resisted,
     })]
 }
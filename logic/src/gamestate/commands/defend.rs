@@ -1,12 +1,18 @@
 use super::Event;
+use crate::event::Defend;
 use crate::gamestate::GameState;
 
-pub fn is_applicable(_state: &GameState) -> bool {
-    true
+// Берсерком не управляют, но машине под ним больше нечего делать
+pub fn is_applicable(state: &GameState) -> bool {
+    let current_stack = state.get_current_stack();
+    !current_stack.is_berserk() || current_stack.creature.is_war_machine()
 }
+
 pub fn apply(state: &mut GameState) -> Vec<Event> {
     let current_stack = state.get_current_stack_mut();
     current_stack.defending = true;
 
-    vec![]
+    vec![Event::Defend(Defend {
+        stack: state.current_stack,
+    })]
 }
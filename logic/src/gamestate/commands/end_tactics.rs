@@ -0,0 +1,12 @@
+use super::Event;
+use crate::gamestate::GameState;
+
+pub fn is_applicable(state: &GameState) -> bool {
+    state.turn.tactics.is_some()
+}
+
+// Первый раунд начинается с обычного выбора самого быстрого отряда
+pub fn apply(state: &mut GameState) -> Vec<Event> {
+    state.turn.tactics = None;
+    state.update_current_stack()
+}
@@ -0,0 +1,127 @@
+use gamedata::creatures::Creature;
+use rand::Rng;
+
+use super::Event;
+use crate::command::Heal;
+use crate::event::Heal as HealEvent;
+use crate::gamestate::GameState;
+
+pub fn is_applicable(command: Heal, state: &GameState) -> bool {
+    let healer = state.get_current_stack();
+    let target = state.get_stack(command.target);
+
+    // Лечить имеет смысл только раненое верхнее существо
+    healer.creature == Creature::FirstAidTent
+        && target.is_alive()
+        && target.side == healer.side
+        && !target.creature.is_war_machine()
+        && target.current_health < target.creature.base_stats().health
+}
+
+pub fn apply(command: Heal, state: &mut GameState) -> Vec<Event> {
+    let side = state.get_current_stack().side;
+
+    // Без Первой помощи палатка лечит случайно от 1 до 25 единиц здоровья
+    let amount = match state.heroes[side as usize]
+        .as_ref()
+        .and_then(|hero| hero.first_aid_healing())
+    {
+        Some(amount) => amount,
+        None => state.rng.gen_range(1..=25),
+    };
+
+    state.get_stack_mut(command.target).heal(amount);
+
+    vec![Event::Heal(HealEvent {
+        healer: state.current_stack,
+        target: command.target,
+    })]
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::WarMachine;
+    use gamedata::heroes::{Ability, AbilityLevel, Hero as GDHero};
+
+    use super::*;
+    use crate::gamestate::{Army, StackHandle};
+    use crate::testing::{army, battle};
+
+    fn find(state: &GameState, creature: Creature) -> StackHandle {
+        state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).creature == creature)
+            .unwrap()
+    }
+
+    // Раненый ангел с десятью единицами здоровья, ход у палатки
+    fn wounded_angel(first_aid: Option<AbilityLevel>) -> (GameState, StackHandle) {
+        let attacker = Army {
+            hero: Some(GDHero::Orrin),
+            hero_abilities: Some(
+                first_aid
+                    .map(|level| (Ability::FirstAid, level))
+                    .into_iter()
+                    .collect(),
+            ),
+            war_machines: vec![WarMachine::FirstAidTent],
+            ..army(&[(Creature::Angel, 2)])
+        };
+        let mut state = battle(attacker, army(&[(Creature::Pikeman, 10)]));
+
+        let angel = find(&state, Creature::Angel);
+        state.edit_stack(angel, |stack| stack.current_health = 10);
+        state.current_stack = find(&state, Creature::FirstAidTent);
+
+        (state, angel)
+    }
+
+    fn heal(state: &mut GameState, target: StackHandle) -> i32 {
+        let command = Heal { target };
+        assert!(is_applicable(command, state));
+
+        let health_before = state.get_stack(target).current_health;
+        apply(command, state);
+        state.get_stack(target).current_health - health_before
+    }
+
+    #[test]
+    fn tent_heals_up_to_25_without_first_aid() {
+        let (mut state, angel) = wounded_angel(None);
+
+        for _ in 0..50 {
+            state.edit_stack(angel, |stack| stack.current_health = 10);
+            assert!((1..=25).contains(&heal(&mut state, angel)));
+        }
+    }
+
+    #[test]
+    fn first_aid_sets_healing_amount() {
+        for (level, amount) in [
+            (AbilityLevel::Basic, 50),
+            (AbilityLevel::Advanced, 75),
+            (AbilityLevel::Expert, 100),
+        ] {
+            let (mut state, angel) = wounded_angel(Some(level));
+            assert_eq!(heal(&mut state, angel), amount);
+        }
+
+        // Лечение не поднимает здоровье выше полного
+        let (mut state, angel) = wounded_angel(Some(AbilityLevel::Expert));
+        state.edit_stack(angel, |stack| stack.current_health = 150);
+        assert_eq!(heal(&mut state, angel), 50);
+    }
+
+    #[test]
+    fn tent_heals_only_wounded_own_stacks() {
+        let (mut state, angel) = wounded_angel(None);
+        let pikeman = find(&state, Creature::Pikeman);
+        state.edit_stack(pikeman, |stack| stack.current_health = 1);
+
+        assert!(!is_applicable(Heal { target: pikeman }, &state));
+
+        state.edit_stack(angel, |stack| stack.current_health = 200);
+        assert!(!is_applicable(Heal { target: angel }, &state));
+    }
+}
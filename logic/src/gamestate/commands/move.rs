@@ -1,6 +1,6 @@
 use crate::command::Move;
 use crate::event::{Event, Movement};
-use crate::gamestate::GameState;
+use crate::gamestate::{siege, GameState};
 
 pub fn is_applicable(command: Move, state: &GameState) -> bool {
     let current_stack = state.get_current_stack();
@@ -11,18 +11,19 @@ pub fn is_applicable(command: Move, state: &GameState) -> bool {
         command.destination,
     )
     .map(|cells| {
-        cells
-            .into_iter()
-            .map(|cell| {
-                state
-                    .find_unit_for_cell(cell)
-                    .filter(|&h| h != state.current_stack)
-            })
-            .all(|option| option.is_none())
+        cells.into_iter().all(|cell| {
+            let occupant = state
+                .find_unit_for_cell(cell)
+                .filter(|&h| h != state.current_stack);
+            occupant.is_none() && !state.is_cell_blocked(cell, current_stack.side)
+        })
     })
     .unwrap_or(false);
 
-    is_position_available && state.reachable_cells.contains(&command.destination)
+    // Боевые машины стоят на месте
+    !current_stack.creature.is_war_machine()
+        && is_position_available
+        && state.reachable_cells.contains(&command.destination)
 }
 
 pub fn apply(command: Move, state: &mut GameState) -> Vec<Event> {
@@ -44,5 +45,10 @@ pub fn apply(command: Move, state: &mut GameState) -> Vec<Event> {
         path,
     }));
 
+    // При расстановке ров ещё безопасен
+    if state.turn.tactics.is_none() {
+        events.extend(siege::enter_moat(state));
+    }
+
     events
 }
@@ -0,0 +1,25 @@
+use super::Event;
+use crate::gamestate::{BattleOutcome, GameState};
+
+// Бежать может только армия с героем
+pub fn is_applicable(state: &GameState) -> bool {
+    let side = state.get_current_stack().side;
+    state.heroes[side as usize].is_some()
+}
+
+// Герой спасается, а его армия покидает поле боя
+pub fn apply(state: &mut GameState) -> Vec<Event> {
+    let side = state.get_current_stack().side;
+
+    for stack in state.stacks.values_mut() {
+        if stack.side == side {
+            stack.fled = stack.count;
+            stack.count = 0;
+            stack.current_health = 0;
+        }
+    }
+
+    state.outcome = Some(BattleOutcome::Retreat { side });
+
+    vec![]
+}
@@ -1,16 +1,14 @@
-use gamedata::creatures::Ability;
+use gamedata::creatures::{Ability, Creature};
 
 use crate::command::Shoot;
 use crate::event::{Event, Shot};
 use crate::gamestate::{damage, GameState};
 
-const ATTACK_TYPE: damage::AttackType = damage::AttackType::Shoot;
-
 pub fn is_applicable(command: Shoot, state: &GameState) -> bool {
     let current_stack = state.get_current_stack();
     let target_stack = state.get_stack(command.target);
 
-    let is_enemy = current_stack.side != target_stack.side;
+    let is_enemy = command.target != state.current_stack && current_stack.is_enemy(target_stack);
     let is_alive = state.get_stack(command.target).is_alive();
     let can_shoot = current_stack.can_shoot(state);
 
@@ -19,35 +17,142 @@ pub fn is_applicable(command: Shoot, state: &GameState) -> bool {
 pub fn apply(command: Shoot, state: &mut GameState) -> Vec<Event> {
     let mut events = vec![];
 
+    let current_stack = state.get_current_stack();
+    let side = current_stack.side;
+    let is_ballista = current_stack.creature == Creature::Ballista;
+
+    // Пока цела тележка, стрелки не тратят боеприпасы
+    let spends_ammo = !is_ballista && !state.has_war_machine(side, Creature::AmmoCart);
+
+    let ballista_shoots_twice = is_ballista
+        && state.heroes[side as usize]
+            .as_ref()
+            .is_some_and(|hero| hero.ballista_shots() > 1);
+
+    let attack_type = state.shot_type(current_stack, state.get_stack(command.target));
+
     let [attacker, defender] =
         common::map::get_many_mut(&mut state.stacks, [state.current_stack, command.target])
             .unwrap();
 
-    attacker.current_ammo -= 1;
+    if spends_ammo {
+        attacker.current_ammo -= 1;
+    }
 
-    damage::deal_damage(&state.heroes, attacker, defender, ATTACK_TYPE);
+    let damage = damage::deal_damage(
+        &state.heroes,
+        attacker,
+        defender,
+        attack_type,
+        &mut state.rng,
+    );
 
     events.push(Event::Shot(Shot {
         attacker: state.current_stack,
         target: command.target,
+        damage,
         lethal: !defender.is_alive(),
     }));
 
+    defender.remove_effects(|e| e.breaks_on_attack());
+
     if !defender.is_alive() {
         return events;
     }
 
-    if attacker.current_ammo > 0 && attacker.creature.has_ability(Ability::DoubleShot) {
-        println!("Using double shot!");
+    let has_double_shot =
+        attacker.current_ammo > 0 && attacker.creature.has_ability(Ability::DoubleShot);
 
-        attacker.current_ammo -= 1;
-        damage::deal_damage(&state.heroes, attacker, defender, ATTACK_TYPE);
+    if has_double_shot || ballista_shoots_twice {
+        if spends_ammo {
+            attacker.current_ammo -= 1;
+        }
+        let damage = damage::deal_damage(
+            &state.heroes,
+            attacker,
+            defender,
+            attack_type,
+            &mut state.rng,
+        );
         events.push(Event::Shot(Shot {
             attacker: state.current_stack,
             target: command.target,
+            damage,
             lethal: !defender.is_alive(),
         }));
     }
 
     events
 }
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::WarMachine;
+
+    use super::*;
+    use crate::gamestate::{Army, Side, StackHandle};
+    use crate::testing::{army, battle};
+
+    fn find(state: &GameState, creature: Creature) -> StackHandle {
+        state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).creature == creature)
+            .unwrap()
+    }
+
+    // Лучники делают один выстрел по копейщикам, возвращается остаток стрел
+    fn ammo_after_shot(war_machines: Vec<WarMachine>, edit: impl FnOnce(&mut GameState)) -> i32 {
+        let attacker = Army {
+            war_machines,
+            ..army(&[(Creature::Archer, 10)])
+        };
+        let mut state = battle(attacker, army(&[(Creature::Pikeman, 10)]));
+        edit(&mut state);
+
+        let archer = find(&state, Creature::Archer);
+        let target = find(&state, Creature::Pikeman);
+        state.current_stack = archer;
+
+        let command = Shoot { target };
+        assert!(is_applicable(command, &state));
+        apply(command, &mut state);
+
+        state.get_stack(archer).current_ammo
+    }
+
+    #[test]
+    fn ammo_cart_keeps_shooters_supplied() {
+        let ammo = Creature::Archer.base_stats().ammo_capacity;
+
+        assert_eq!(ammo_after_shot(vec![], |_| {}), ammo - 1);
+        assert_eq!(ammo_after_shot(vec![WarMachine::AmmoCart], |_| {}), ammo);
+
+        // Разбитая тележка больше не подвозит стрелы
+        let destroyed = ammo_after_shot(vec![WarMachine::AmmoCart], |state| {
+            let cart = find(state, Creature::AmmoCart);
+            state.edit_stack(cart, |stack| stack.count = 0);
+        });
+        assert_eq!(destroyed, ammo - 1);
+    }
+
+    #[test]
+    fn ballista_shoots_without_ammo() {
+        let attacker = Army {
+            war_machines: vec![WarMachine::Ballista],
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let mut state = battle(attacker, army(&[(Creature::Pikeman, 10)]));
+
+        let ballista = find(&state, Creature::Ballista);
+        let target = state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).side == Side::Defender)
+            .unwrap();
+
+        state.current_stack = ballista;
+        state.edit_stack(ballista, |stack| stack.current_ammo = 0);
+        assert!(is_applicable(Shoot { target }, &state));
+    }
+}
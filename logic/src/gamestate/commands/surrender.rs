@@ -0,0 +1,40 @@
+use gamedata::heroes::{Ability, AbilityLevel};
+
+use super::Event;
+use crate::gamestate::{BattleOutcome, GameState, Side};
+
+// Сдаться можно только герою другой стороны
+pub fn is_applicable(state: &GameState) -> bool {
+    state.heroes.iter().all(Option::is_some)
+}
+
+pub fn apply(state: &mut GameState) -> Vec<Event> {
+    let side = state.get_current_stack().side;
+    let cost = surrender_cost(state, side);
+
+    state.outcome = Some(BattleOutcome::Surrender { side, cost });
+
+    vec![]
+}
+
+// Цена выживших существ, сниженная Дипломатией
+pub fn surrender_cost(state: &GameState, side: Side) -> i32 {
+    let army_cost: i32 = state
+        .stacks
+        .values()
+        .filter(|stack| stack.side == side && stack.is_alive() && !stack.is_clone)
+        .filter(|stack| !stack.is_summoned && !stack.creature.is_war_machine())
+        .map(|stack| stack.creature.cost() * stack.count)
+        .sum();
+
+    let discount = state.heroes[side as usize]
+        .as_ref()
+        .and_then(|hero| hero.get_ability_level(Ability::Diplomacy))
+        .map_or(0.0, |level| match level {
+            AbilityLevel::Basic => 0.2,
+            AbilityLevel::Advanced => 0.4,
+            AbilityLevel::Expert => 0.6,
+        });
+
+    (army_cost as f32 * (1.0 - discount)).round() as i32
+}
@@ -3,12 +3,15 @@ use crate::gamestate::GameState;
 use crate::turn::Phase;
 
 pub fn is_applicable(state: &GameState) -> bool {
-    state
-        .get_current_stack()
-        .turn_state
-        .map_or(false, |phase| phase == Phase::Fresh)
+    let current_stack = state.get_current_stack();
+    current_stack.turn_state == Some(Phase::Fresh) && !current_stack.is_berserk()
 }
+// При расстановке ожидание просто передаёт выбор следующему отряду
 pub fn apply(state: &mut GameState) -> Vec<Event> {
+    if state.turn.tactics.is_some() {
+        return vec![];
+    }
+
     let current_stack = state.get_current_stack_mut();
     current_stack.turn_state = Some(Phase::Wait);
 
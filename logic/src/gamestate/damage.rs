@@ -1,23 +1,76 @@
+use gamedata::creatures;
+use gamedata::creatures::Creature;
 use gamedata::heroes::{Ability, AbilityLevel};
 use rand::distributions::Uniform;
 use rand::prelude::Distribution;
+use rand::rngs::SmallRng;
+use rand::Rng;
 
-use super::hero::Hero;
+use super::hero::{self, Hero};
 use crate::stack::Stack;
 
+#[derive(Clone, Copy)]
 pub enum AttackType {
     Melee,
-    Shoot,
+    // Ответ на удар в ближнем бою, ослеплённый отряд отвечает слабее
+    Retaliation,
+    // Стена между стрелком и целью вдвое снижает урон
+    Shoot { distance: i32, obstructed: bool },
 }
 
+impl AttackType {
+    fn is_ranged(self) -> bool {
+        matches!(self, Self::Shoot { .. })
+    }
+
+    fn is_retaliation(self) -> bool {
+        matches!(self, Self::Retaliation)
+    }
+}
+
+// Стрельба дальше этого расстояния (в гексах) наносит половину урона
+const RANGE_PENALTY_DISTANCE: i32 = 10;
+
 pub fn deal_damage(
     heroes: &[Option<Hero>; 2],
     attacker: &Stack,
     defender: &mut Stack,
     attack_type: AttackType,
-) {
-    let damage = calculate_damage(heroes, attacker, defender, attack_type);
+    rng: &mut SmallRng,
+) -> i32 {
+    let damage = calculate_damage(heroes, attacker, defender, attack_type, rng);
+
+    // Возвращается только реально снятое здоровье
+    let health_before = defender.total_health();
     defender.receive_damage(damage);
+    health_before - defender.total_health()
+}
+
+// Урон при среднем броске, без случайности
+pub fn expected_damage(
+    heroes: &[Option<Hero>; 2],
+    attacker: &Stack,
+    defender: &Stack,
+    attack_type: AttackType,
+) -> i32 {
+    let attacker_hero = heroes[attacker.side as usize].as_ref();
+
+    let (damage_low, damage_high) = attacker.damage_range();
+    let base = (damage_low + damage_high) as f32 / 2.0 * attacker.count as f32;
+    let base = base * ballista_modifier(attacker_hero, attacker);
+
+    let critical_md = 1.0 + artillery_chance(attacker_hero, attacker) as f32 / 100.0;
+    // Невезение в среднем отнимает половину урона вместо удвоения
+    let luck = luck(attacker_hero, attacker);
+    let luck_md = if luck > 0 {
+        1.0 + luck as f32 / hero::FORTUNE_DICE as f32
+    } else {
+        1.0 + 0.5 * luck as f32 / hero::FORTUNE_DICE as f32
+    };
+
+    let damage =
+        base * damage_multiplier(heroes, attacker, defender, attack_type) * critical_md * luck_md;
+    damage.floor() as i32
 }
 
 fn calculate_damage(
@@ -25,31 +78,71 @@ fn calculate_damage(
     attacker: &Stack,
     defender: &Stack,
     attack_type: AttackType,
+    rng: &mut SmallRng,
 ) -> i32 {
     let attacker_hero = heroes[attacker.side as usize].as_ref();
-    let defender_hero = heroes[defender.side as usize].as_ref();
 
-    let base = base_damage(attacker) as f32;
+    let base = base_damage(attacker, rng) as f32;
+    let base = base * ballista_modifier(attacker_hero, attacker);
+
+    let chance = artillery_chance(attacker_hero, attacker);
+    let critical_md = if chance > 0 && rng.gen_range(0..100) < chance {
+        2.0
+    } else {
+        1.0
+    };
+
+    let luck = luck(attacker_hero, attacker);
+    let luck_md = if hero::roll_fortune(luck, rng) {
+        2.0
+    } else if hero::roll_fortune(-luck, rng) {
+        0.5
+    } else {
+        1.0
+    };
+
+    let damage =
+        base * damage_multiplier(heroes, attacker, defender, attack_type) * critical_md * luck_md;
+    damage.floor() as i32
+}
+
+fn damage_multiplier(
+    heroes: &[Option<Hero>; 2],
+    attacker: &Stack,
+    defender: &Stack,
+    attack_type: AttackType,
+) -> f32 {
+    let attacker_hero = heroes[attacker.side as usize].as_ref();
+    let defender_hero = heroes[defender.side as usize].as_ref();
 
-    let (md1, md2) = primary_damage_modifiers(attacker_hero, attacker, defender_hero, defender);
+    let (md1, md2) = primary_damage_modifiers(
+        attacker_hero,
+        attacker,
+        defender_hero,
+        defender,
+        attack_type,
+    );
 
     let offence_md = offence_modifier(attacker_hero, attack_type);
     let armor_md = armorer_modifier(defender_hero);
+    let penalty_md = penalty_modifier(attacker_hero, attacker, attack_type);
 
-    let damage = base * (1.0 + md1 + offence_md) * md2 * armor_md;
+    let effects_md = attacker
+        .damage_dealt_multiplier(attack_type.is_ranged(), attack_type.is_retaliation())
+        * defender.damage_taken_multiplier(attack_type.is_ranged());
 
-    damage.floor() as i32
+    (1.0 + md1 + offence_md) * md2 * armor_md * penalty_md * effects_md
 }
 
-fn base_damage(stack: &Stack) -> i32 {
-    let (damage_low, damage_high) = stack.creature.base_stats().damage;
+fn base_damage(stack: &Stack, rng: &mut SmallRng) -> i32 {
+    let (damage_low, damage_high) = stack.damage_range();
 
     if damage_low == damage_high {
         return damage_low * stack.count;
     }
 
     let sum: i32 = Uniform::from(damage_low..damage_high)
-        .sample_iter(rand::thread_rng())
+        .sample_iter(rng)
         .take(i32::min(stack.count, 10) as usize)
         .sum();
 
@@ -60,16 +153,48 @@ fn base_damage(stack: &Stack) -> i32 {
     }
 }
 
+// Урон баллисты растёт с атакой героя
+fn ballista_modifier(attacker_hero: Option<&Hero>, attacker: &Stack) -> f32 {
+    if attacker.creature != Creature::Ballista {
+        return 1.0;
+    }
+
+    (attacker_hero.map_or(0, |h| h.stats.attack) + 1) as f32
+}
+
+// Удача героя удваивает урон его существ, но не боевых машин,
+// а невезение от заклинаний вдвое снижает
+fn luck(attacker_hero: Option<&Hero>, attacker: &Stack) -> i32 {
+    if attacker.creature.is_war_machine() {
+        return 0;
+    }
+
+    hero::cap_fortune(attacker_hero.map_or(0, |h| h.luck()) + attacker.luck())
+}
+
+// Шанс двойного урона баллисты под Артиллерией, в процентах
+fn artillery_chance(attacker_hero: Option<&Hero>, attacker: &Stack) -> i32 {
+    if attacker.creature != Creature::Ballista {
+        return 0;
+    }
+
+    attacker_hero.map_or(0, |h| h.artillery_chance())
+}
+
 fn primary_damage_modifiers(
     attacker_hero: Option<&Hero>,
     attacker: &Stack,
     defender_hero: Option<&Hero>,
     defender: &Stack,
+    attack_type: AttackType,
 ) -> (f32, f32) {
-    let attack = attacker.base_stats().attack + attacker_hero.map_or(0, |h| h.stats.attack);
+    let attack = attacker.attack(attack_type.is_ranged())
+        + attacker.slayer_attack(defender.creature)
+        + attacker_hero.map_or(0, |h| h.stats.attack);
     let attack = attack as f32;
 
-    let defence = defender.base_stats().defence + defender_hero.map_or(0, |h| h.stats.defence);
+    let defence = defender.defence() + defender_hero.map_or(0, |h| h.stats.defence);
+    let defence = defence + defending_bonus(defender, defence);
     let defence = defence as f32;
 
     // TODO: bring back ignore defence
@@ -96,6 +221,15 @@ fn primary_damage_modifiers(
     (md1, md2)
 }
 
+// Защищающийся отряд получает 20% к защите с округлением вниз, но хотя бы единицу
+fn defending_bonus(defender: &Stack, defence: i32) -> i32 {
+    if defender.defending && defence > 0 {
+        i32::max(defence / 5, 1)
+    } else {
+        0
+    }
+}
+
 fn offence_modifier(attacker_hero: Option<&Hero>, attack_type: AttackType) -> f32 {
     fn offence(level: AbilityLevel) -> f32 {
         match level {
@@ -115,8 +249,8 @@ fn offence_modifier(attacker_hero: Option<&Hero>, attack_type: AttackType) -> f3
 
     type ModifierFN = fn(AbilityLevel) -> f32;
     let (ability, modifier_fun): (Ability, ModifierFN) = match attack_type {
-        AttackType::Melee => (Ability::Offense, offence),
-        AttackType::Shoot => (Ability::Archery, archery),
+        AttackType::Melee | AttackType::Retaliation => (Ability::Offense, offence),
+        AttackType::Shoot { .. } => (Ability::Archery, archery),
     };
 
     attacker_hero
@@ -135,3 +269,261 @@ fn armorer_modifier(defender_hero: Option<&Hero>) -> f32 {
 
     1.0 - m
 }
+
+// Штрафы за дальность и стену не складываются: урон снижается вдвое один раз
+fn penalty_modifier(
+    attacker_hero: Option<&Hero>,
+    attacker: &Stack,
+    attack_type: AttackType,
+) -> f32 {
+    let creature = attacker.creature;
+
+    let has_penalty = match attack_type {
+        // Стрелки в ближнем бою бьют вполсилы
+        AttackType::Melee | AttackType::Retaliation => {
+            creature.is_shooter() && !creature.has_ability(creatures::Ability::NoMeleePenalty)
+        }
+        AttackType::Shoot {
+            distance,
+            obstructed,
+        } => {
+            // Снайперы и лук героя снимают оба штрафа стрельбы
+            let is_exempt = creature.has_ability(creatures::Ability::NoRangePenalty)
+                || attacker_hero.is_some_and(|h| h.ignores_shooting_penalties());
+            (distance > RANGE_PENALTY_DISTANCE || obstructed) && !is_exempt
+        }
+    };
+
+    if has_penalty {
+        0.5
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::artifacts::Artifact;
+    use gamedata::heroes::{Hero as GDHero, LearnedAbility, Stats};
+
+    use super::*;
+    use crate::effect::{Status, StatusEffect};
+    use crate::gamestate::Side;
+    use crate::grid::GridPos;
+
+    fn stack(creature: Creature, side: Side) -> Stack {
+        Stack::new(creature, 10, GridPos::new(1, 1), side)
+    }
+
+    fn hero(artifacts: Vec<Artifact>) -> Hero {
+        Hero {
+            hero: GDHero::Orrin,
+            level: 1,
+            stats: Stats {
+                attack: 0,
+                defence: 0,
+                power: 1,
+                knowledge: 1,
+            },
+            abilities: vec![],
+            artifacts,
+            spell_book: vec![],
+            spell_points: 0,
+            max_spell_points: 0,
+            has_casted: false,
+            learned_spells: vec![],
+        }
+    }
+
+    fn shot(distance: i32, obstructed: bool) -> AttackType {
+        AttackType::Shoot {
+            distance,
+            obstructed,
+        }
+    }
+
+    #[test]
+    fn shooter_in_melee_deals_half_damage() {
+        let archer = stack(Creature::Archer, Side::Attacker);
+        let mage = stack(Creature::Mage, Side::Attacker);
+        let pikeman = stack(Creature::Pikeman, Side::Attacker);
+
+        assert_eq!(penalty_modifier(None, &archer, AttackType::Melee), 0.5);
+        assert_eq!(penalty_modifier(None, &mage, AttackType::Melee), 1.0);
+        assert_eq!(penalty_modifier(None, &pikeman, AttackType::Melee), 1.0);
+    }
+
+    #[test]
+    fn range_penalty_starts_after_ten_cells() {
+        let archer = stack(Creature::Archer, Side::Attacker);
+
+        assert_eq!(
+            penalty_modifier(None, &archer, shot(RANGE_PENALTY_DISTANCE, false)),
+            1.0
+        );
+        assert_eq!(
+            penalty_modifier(None, &archer, shot(RANGE_PENALTY_DISTANCE + 1, false)),
+            0.5
+        );
+    }
+
+    #[test]
+    fn wall_penalty_does_not_stack_with_range_penalty() {
+        let archer = stack(Creature::Archer, Side::Attacker);
+
+        assert_eq!(penalty_modifier(None, &archer, shot(3, true)), 0.5);
+        assert_eq!(penalty_modifier(None, &archer, shot(12, true)), 0.5);
+    }
+
+    #[test]
+    fn sharpshooter_ignores_range_and_wall() {
+        let sharpshooter = stack(Creature::Sharpshooter, Side::Attacker);
+
+        assert_eq!(penalty_modifier(None, &sharpshooter, shot(12, false)), 1.0);
+        assert_eq!(penalty_modifier(None, &sharpshooter, shot(3, true)), 1.0);
+        assert_eq!(penalty_modifier(None, &sharpshooter, shot(12, true)), 1.0);
+    }
+
+    #[test]
+    fn bows_remove_shooting_penalties_but_not_melee_one() {
+        let archer = stack(Creature::Archer, Side::Attacker);
+
+        for bow in [Artifact::GoldenBow, Artifact::BowOfTheSharpshooter] {
+            let hero = hero(vec![bow]);
+            assert_eq!(penalty_modifier(Some(&hero), &archer, shot(12, true)), 1.0);
+            assert_eq!(
+                penalty_modifier(Some(&hero), &archer, AttackType::Melee),
+                0.5
+            );
+        }
+
+        let hero = hero(vec![Artifact::CentaursAxe]);
+        assert_eq!(penalty_modifier(Some(&hero), &archer, shot(12, false)), 0.5);
+    }
+
+    #[test]
+    fn penalty_halves_expected_damage() {
+        let heroes = [None, None];
+        let archer = stack(Creature::Archer, Side::Attacker);
+        let pikeman = stack(Creature::Pikeman, Side::Defender);
+
+        // 10 лучников по 2-3 урона, атака 6 против защиты 5: 25 * 1.05
+        let near = expected_damage(&heroes, &archer, &pikeman, shot(3, false));
+        let far = expected_damage(&heroes, &archer, &pikeman, shot(12, false));
+        let behind_wall = expected_damage(&heroes, &archer, &pikeman, shot(3, true));
+
+        assert_eq!(near, 26);
+        assert_eq!(far, 13);
+        assert_eq!(behind_wall, 13);
+    }
+
+    #[test]
+    fn defending_adds_fifth_of_defence_but_at_least_one() {
+        let mut pikeman = stack(Creature::Pikeman, Side::Defender);
+        assert_eq!(defending_bonus(&pikeman, 12), 0);
+
+        pikeman.defending = true;
+        assert_eq!(defending_bonus(&pikeman, 12), 2);
+        assert_eq!(defending_bonus(&pikeman, 3), 1);
+        assert_eq!(defending_bonus(&pikeman, 0), 0);
+    }
+
+    #[test]
+    fn defending_reduces_melee_and_ranged_damage() {
+        let heroes = [None, None];
+        let archer = stack(Creature::Archer, Side::Attacker);
+        let mut pikeman = stack(Creature::Pikeman, Side::Defender);
+
+        let melee = expected_damage(&heroes, &archer, &pikeman, AttackType::Melee);
+        let ranged = expected_damage(&heroes, &archer, &pikeman, shot(3, false));
+
+        // Защита 5 + 1: атака 6 уже не даёт прибавки к урону
+        pikeman.defending = true;
+        assert_eq!(
+            expected_damage(&heroes, &archer, &pikeman, AttackType::Melee),
+            12
+        );
+        assert_eq!(
+            expected_damage(&heroes, &archer, &pikeman, shot(3, false)),
+            25
+        );
+        assert_eq!((melee, ranged), (13, 26));
+    }
+
+    fn with_effect(mut stack: Stack, status: Status, level: AbilityLevel) -> Stack {
+        stack.apply_effect(StatusEffect::new(status, Some(level), Some(1)));
+        stack
+    }
+
+    #[test]
+    fn slayer_adds_attack_only_against_strongest_creatures() {
+        let heroes = [None, None];
+        let pikeman = stack(Creature::Pikeman, Side::Attacker);
+        let dragon = stack(Creature::GreenDragon, Side::Defender);
+        let angel = stack(Creature::Angel, Side::Defender);
+
+        let damage = |attacker: &Stack, defender: &Stack| {
+            expected_damage(&heroes, attacker, defender, AttackType::Melee)
+        };
+
+        let basic = with_effect(pikeman.clone(), Status::Slayer, AbilityLevel::Basic);
+        assert!(damage(&basic, &dragon) > damage(&pikeman, &dragon));
+        assert_eq!(damage(&basic, &angel), damage(&pikeman, &angel));
+
+        let advanced = with_effect(pikeman.clone(), Status::Slayer, AbilityLevel::Advanced);
+        assert!(damage(&advanced, &angel) > damage(&pikeman, &angel));
+    }
+
+    #[test]
+    fn fortune_and_misfortune_shift_expected_damage() {
+        let heroes = [None, None];
+        let pikeman = stack(Creature::Pikeman, Side::Attacker);
+        let target = stack(Creature::Pikeman, Side::Defender);
+
+        let damage =
+            |attacker: &Stack| expected_damage(&heroes, attacker, &target, AttackType::Melee);
+
+        let lucky = with_effect(pikeman.clone(), Status::Fortune, AbilityLevel::Advanced);
+        let unlucky = with_effect(pikeman.clone(), Status::Misfortune, AbilityLevel::Advanced);
+
+        // 10 копейщиков по 1-3 урона при атаке 4 против защиты 5: 20 * 0.975,
+        // удача +2 даёт 2/24 шанса удвоения, невезение - столько же шансов на половину
+        assert_eq!(damage(&pikeman), 19);
+        assert_eq!(damage(&lucky), 21);
+        assert_eq!(damage(&unlucky), 18);
+    }
+
+    #[test]
+    fn ballista_damage_grows_with_hero_attack_and_artillery() {
+        let mut ballista = stack(Creature::Ballista, Side::Attacker);
+        ballista.count = 1;
+        let pikeman = stack(Creature::Pikeman, Side::Defender);
+
+        let mut attacker = hero(vec![]);
+        let damage = |attacker: &Hero| {
+            let heroes = [Some(attacker.clone()), None];
+            expected_damage(&heroes, &ballista, &pikeman, shot(3, false))
+        };
+
+        // 2-3 урона, атака 10 против защиты 5
+        assert_eq!(
+            expected_damage(&[None, None], &ballista, &pikeman, shot(3, false)),
+            3
+        );
+
+        // Атака героя умножает урон и прибавляется к атаке: 2.5 * 5 * 1.45
+        attacker.stats.attack = 4;
+        assert_eq!(damage(&attacker), 18);
+
+        // Базовая Артиллерия даёт полшанса на двойной урон
+        attacker.abilities.push(LearnedAbility {
+            ability: Ability::Artillery,
+            level: AbilityLevel::Basic,
+        });
+        assert_eq!(damage(&attacker), 27);
+
+        // Удача героя на машины не действует
+        attacker.artifacts.push(Artifact::CloverOfFortune);
+        assert_eq!(damage(&attacker), 27);
+    }
+}
@@ -1,30 +1,628 @@
-use gamedata::heroes::{Ability, AbilityLevel, Hero as GDHero, LearnedAbility, Stats};
+use common::error::AnyHow;
+use gamedata::artifacts::Artifact;
+use gamedata::heroes::{Ability, AbilityLevel, Class, Hero as GDHero, LearnedAbility, Stats};
+use gamedata::spells::{Spell, SpellLevel, SpellSchool};
+use rand::rngs::SmallRng;
+use rand::Rng;
 
+use super::Army;
+
+#[derive(Clone)]
 pub struct Hero {
     pub hero: GDHero,
+    pub level: u32,
     pub stats: Stats,
-    pub abilities: [Option<LearnedAbility>; 7],
+    pub abilities: Vec<LearnedAbility>,
+    pub artifacts: Vec<Artifact>,
+    pub spell_book: Vec<Spell>,
+    pub spell_points: i32,
+    pub max_spell_points: i32,
+    pub has_casted: bool,
+    // Подсмотренные у врага Орлиным глазом, в книгу попадут после битвы
+    pub learned_spells: Vec<Spell>,
+}
+
+// Боевой дух и удача срабатывают с шансом 1/24 за каждую единицу
+pub const FORTUNE_DICE: i32 = 24;
+
+// Больше вторичных навыков герой выучить не может
+const MAX_ABILITIES: usize = 8;
+
+// Предел боевого духа и удачи
+const MAX_FORTUNE: i32 = 3;
+
+pub fn roll_fortune(value: i32, rng: &mut SmallRng) -> bool {
+    value > 0 && rng.gen_range(0..FORTUNE_DICE) < value
+}
+
+// Заклинания на отряде сдвигают удачу и боевой дух героя в обе стороны
+pub fn cap_fortune(value: i32) -> i32 {
+    value.clamp(-MAX_FORTUNE, MAX_FORTUNE)
 }
 
 impl Hero {
-    pub fn build(hero: GDHero) -> Self {
-        const NONE: Option<LearnedAbility> = None;
-        let mut abilities: [Option<LearnedAbility>; 7] = [NONE; 7];
+    // Незаданные в конфиге армии поля берутся у героя первого уровня
+    pub fn build(hero: GDHero, army: &Army) -> AnyHow<Self> {
+        let abilities: Vec<LearnedAbility> = match &army.hero_abilities {
+            Some(abilities) => abilities
+                .iter()
+                .map(|&(ability, level)| LearnedAbility { ability, level })
+                .collect(),
+            None => hero.starting_abilities().into_iter().flatten().collect(),
+        };
 
-        for (i, item) in hero.starting_abilities().into_iter().enumerate() {
-            abilities[i] = item;
+        if abilities.len() > MAX_ABILITIES {
+            return Err(format!("Hero can't have more than {MAX_ABILITIES} abilities").into());
         }
 
-        Self {
+        for (i, learned) in abilities.iter().enumerate() {
+            if abilities[..i].iter().any(|a| a.ability == learned.ability) {
+                return Err(format!("Hero ability {:?} is listed twice", learned.ability).into());
+            }
+        }
+
+        let level = army.hero_level.unwrap_or(1);
+        if level == 0 {
+            return Err("Hero level starts from 1".into());
+        }
+
+        // Артефакты добавляются к заданным характеристикам, но не ниже минимума
+        let mut stats = army
+            .hero_stats
+            .unwrap_or_else(|| leveled_stats(hero.class(), level));
+        for artifact in &army.hero_artifacts {
+            let bonus = artifact.stats();
+            stats.attack = i32::max(stats.attack + bonus.attack, 0);
+            stats.defence = i32::max(stats.defence + bonus.defence, 0);
+            stats.power = i32::max(stats.power + bonus.power, 1);
+            stats.knowledge = i32::max(stats.knowledge + bonus.knowledge, 1);
+        }
+
+        let spell_book = army
+            .hero_spells
+            .clone()
+            .unwrap_or_else(|| hero.starting_spell().into_iter().collect());
+        let is_spell_book_set = army.hero_spells.is_some();
+
+        let mut hero = Self {
             hero,
-            stats: hero.class().starting_stats(),
+            level,
+            stats,
             abilities,
+            artifacts: army.hero_artifacts.clone(),
+            spell_book,
+            spell_points: 0,
+            max_spell_points: 0,
+            has_casted: false,
+            learned_spells: vec![],
+        };
+
+        // Без Мудрости герой учит только заклинания первых двух уровней,
+        // но своё начальное заклинание знает всегда
+        let max_level = hero.max_spell_level();
+        let starting_spell = hero.hero.starting_spell();
+        if is_spell_book_set {
+            let beyond_wisdom = hero
+                .spell_book
+                .iter()
+                .find(|&&spell| spell.level() > max_level && Some(spell) != starting_spell);
+            if let Some(spell) = beyond_wisdom {
+                return Err(format!("Hero needs more Wisdom to know {spell:?}").into());
+            }
+        }
+
+        // Каждая единица знаний даёт 10 очков заклинаний, Интеллект добавляет к ним
+        let intelligence = hero.ability_value(Ability::Intelligence, [25, 50, 100]);
+        hero.max_spell_points = stats.knowledge * 10 * (100 + intelligence) / 100;
+        hero.spell_points = hero.max_spell_points;
+
+        Ok(hero)
+    }
+
+    // Значение навыка для базового, продвинутого и экспертного уровня
+    fn ability_value(&self, ability: Ability, values: [i32; 3]) -> i32 {
+        self.get_ability_level(ability)
+            .map_or(0, |level| values[level as usize])
+    }
+
+    pub fn has_artifact(&self, artifact: Artifact) -> bool {
+        self.artifacts.contains(&artifact)
+    }
+
+    // Навыков с таким действием в оригинале нет, только луки
+    pub fn ignores_shooting_penalties(&self) -> bool {
+        self.artifacts
+            .iter()
+            .any(|a| a.removes_shooting_penalties())
+    }
+
+    pub fn morale(&self) -> i32 {
+        let artifacts: i32 = self.artifacts.iter().map(|a| a.morale()).sum();
+        i32::min(
+            self.ability_value(Ability::Leadership, [1, 2, 3]) + artifacts,
+            MAX_FORTUNE,
+        )
+    }
+
+    pub fn luck(&self) -> i32 {
+        let artifacts: i32 = self.artifacts.iter().map(|a| a.luck()).sum();
+        i32::min(
+            self.ability_value(Ability::Luck, [1, 2, 3]) + artifacts,
+            MAX_FORTUNE,
+        )
+    }
+
+    // Шанс отряда героя устоять перед вражеским заклинанием, в процентах
+    pub fn magic_resistance(&self) -> i32 {
+        self.ability_value(Ability::Resistance, [5, 10, 20])
+    }
+
+    // Урон заклинания до защиты цели
+    pub fn spell_damage(&self, spell: Spell) -> Option<i32> {
+        let (multiplier, base) = spell.damage(self.school_level(spell))?;
+        let damage = multiplier * self.stats.power + base;
+
+        // Сфера подходит к Волшебной стреле, как к заклинанию любой школы
+        let has_orb = self.artifacts.iter().any(|a| {
+            a.boosted_school()
+                .is_some_and(|school| spell.school().is_none_or(|s| s == school))
+        });
+        let orb = if has_orb { 50 } else { 0 };
+
+        let sorcery = self.ability_value(Ability::Sorcery, [5, 10, 15]);
+        Some(damage * (100 + sorcery + orb) / 100)
+    }
+
+    fn max_spell_level(&self) -> SpellLevel {
+        match self.get_ability_level(Ability::Wisdom) {
+            None => SpellLevel::Second,
+            Some(AbilityLevel::Basic) => SpellLevel::Third,
+            Some(AbilityLevel::Advanced) => SpellLevel::Fourth,
+            Some(AbilityLevel::Expert) => SpellLevel::Fifth,
         }
     }
 
+    // Орлиный глаз позволяет выучить заклинание, применённое врагом
+    pub fn try_learn_spell(&mut self, spell: Spell, rng: &mut SmallRng) {
+        let Some((chance, max_level)) = self.eagle_eye() else {
+            return;
+        };
+
+        let is_learnable = spell.level() <= max_level
+            && spell.level() <= self.max_spell_level()
+            && !self.knows_spell(spell)
+            && !self.learned_spells.contains(&spell);
+
+        if is_learnable && rng.gen_range(0..100) < chance {
+            self.learned_spells.push(spell);
+        }
+    }
+
+    // Шанс выучить заклинание в процентах и его наибольший уровень
+    fn eagle_eye(&self) -> Option<(i32, SpellLevel)> {
+        self.get_ability_level(Ability::EagleEye)
+            .map(|level| match level {
+                AbilityLevel::Basic => (40, SpellLevel::Second),
+                AbilityLevel::Advanced => (50, SpellLevel::Third),
+                AbilityLevel::Expert => (60, SpellLevel::Fourth),
+            })
+    }
+
+    // Доля здоровья павших врагов, поднимаемая Некромантией, в процентах
+    pub fn necromancy(&self) -> i32 {
+        self.ability_value(Ability::Necromancy, [10, 20, 30])
+    }
+
+    // Сколько лечит палатка, без Первой помощи - случайно
+    pub fn first_aid_healing(&self) -> Option<i32> {
+        self.get_ability_level(Ability::FirstAid)
+            .map(|_| self.ability_value(Ability::FirstAid, [50, 75, 100]))
+    }
+
+    // Шанс двойного урона баллисты, в процентах
+    pub fn artillery_chance(&self) -> i32 {
+        self.ability_value(Ability::Artillery, [50, 75, 100])
+    }
+
+    // С продвинутой Артиллерией баллиста стреляет дважды
+    pub fn ballista_shots(&self) -> i32 {
+        self.ability_value(Ability::Artillery, [1, 2, 2]).max(1)
+    }
+
+    // Мистицизм и артефакты восстанавливают ману раз в день, а не в битве
+    pub fn daily_mana_regeneration(&self) -> i32 {
+        let artifacts: i32 = self.artifacts.iter().map(|a| a.mana_regeneration()).sum();
+        self.ability_value(Ability::Mysticism, [2, 3, 4]) + artifacts
+    }
+
+    // Прибавка Обучения к опыту за битву, в процентах
+    pub fn learning(&self) -> i32 {
+        self.ability_value(Ability::Learning, [5, 10, 15])
+    }
+
     pub fn get_ability_level(&self, ability: Ability) -> Option<AbilityLevel> {
         self.abilities
+            .iter()
+            .find(|learned| learned.ability == ability)
+            .map(|learned| learned.level)
+    }
+
+    // Заклинания без школы (Волшебная стрела) используют лучшую из школ
+    pub fn school_level(&self, spell: Spell) -> Option<AbilityLevel> {
+        let schools = match spell.school() {
+            Some(school) => vec![school],
+            None => vec![
+                SpellSchool::Air,
+                SpellSchool::Earth,
+                SpellSchool::Fire,
+                SpellSchool::Water,
+            ],
+        };
+
+        schools
             .into_iter()
-            .find_map(|v| v.filter(|ha| ha.ability == ability).map(|ha| ha.level))
+            .filter_map(|school| self.get_ability_level(school_ability(school)))
+            .max()
+    }
+
+    pub fn knows_spell(&self, spell: Spell) -> bool {
+        self.spell_book.contains(&spell)
+    }
+
+    // Владение школой магии снижает стоимость её заклинаний
+    pub fn spell_cost(&self, spell: Spell) -> i32 {
+        spell.cost(self.school_level(spell))
+    }
+
+    pub fn refresh_for_next_turn(&mut self) {
+        self.has_casted = false;
+    }
+}
+
+// Характеристики без заданных в конфиге: каждый уровень после первого даёт
+// единицу навыка, распределённую по шансам класса без случайности
+fn leveled_stats(class: Class, level: u32) -> Stats {
+    let start = class.starting_stats();
+    let mut totals = [0; 4];
+    for level in 2..=level {
+        let chances = class.level_up_chances(level);
+        for (total, chance) in totals.iter_mut().zip(chances) {
+            *total += chance;
+        }
+    }
+
+    let mut points = totals.map(|total| total / 100);
+    let mut left = (level - 1) as i32 - points.iter().sum::<i32>();
+
+    // Оставшиеся единицы достаются навыкам с наибольшими остатками
+    let mut order = [0, 1, 2, 3];
+    order.sort_by_key(|&i| -(totals[i] % 100));
+    for i in order {
+        if left == 0 {
+            break;
+        }
+        points[i] += 1;
+        left -= 1;
+    }
+
+    Stats {
+        attack: start.attack + points[0],
+        defence: start.defence + points[1],
+        power: start.power + points[2],
+        knowledge: start.knowledge + points[3],
+    }
+}
+
+fn school_ability(school: SpellSchool) -> Ability {
+    match school {
+        SpellSchool::Air => Ability::AirMagic,
+        SpellSchool::Earth => Ability::EarthMagic,
+        SpellSchool::Fire => Ability::FireMagic,
+        SpellSchool::Water => Ability::WaterMagic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::Creature;
+    use gamedata::heroes::Hero as GDHero;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::command::{Cast, Command};
+    use crate::testing::{army, battle};
+
+    fn hero(abilities: &[(Ability, AbilityLevel)], artifacts: &[Artifact]) -> Hero {
+        let army = Army {
+            hero_abilities: Some(abilities.to_vec()),
+            hero_artifacts: artifacts.to_vec(),
+            ..army(&[])
+        };
+        Hero::build(GDHero::Orrin, &army).unwrap()
+    }
+
+    // Значение без навыка и на базовом, продвинутом и экспертном уровне
+    fn values<T>(ability: Ability, value: impl Fn(&Hero) -> T) -> Vec<T> {
+        let mut values = vec![value(&hero(&[], &[]))];
+        for level in [
+            AbilityLevel::Basic,
+            AbilityLevel::Advanced,
+            AbilityLevel::Expert,
+        ] {
+            values.push(value(&hero(&[(ability, level)], &[])));
+        }
+        values
+    }
+
+    #[test]
+    fn level_adds_primary_stats() {
+        let level = |level| {
+            let army = Army {
+                hero_level: Some(level),
+                ..army(&[])
+            };
+            Hero::build(GDHero::Orrin, &army).unwrap().stats
+        };
+
+        let first = level(1);
+        assert_eq!(
+            [first.attack, first.defence, first.power, first.knowledge],
+            [2, 2, 1, 1]
+        );
+        let tenth = level(10);
+        assert_eq!(
+            [tenth.attack, tenth.defence, tenth.power, tenth.knowledge],
+            [5, 6, 2, 2]
+        );
+    }
+
+    #[test]
+    fn spells_above_wisdom_are_rejected() {
+        let army = |abilities: &[(Ability, AbilityLevel)]| Army {
+            hero_abilities: Some(abilities.to_vec()),
+            hero_spells: Some(vec![Spell::Fireball]),
+            ..army(&[])
+        };
+
+        assert!(Hero::build(GDHero::Orrin, &army(&[])).is_err());
+        let hero = Hero::build(
+            GDHero::Orrin,
+            &army(&[(Ability::Wisdom, AbilityLevel::Basic)]),
+        )
+        .unwrap();
+        assert_eq!(hero.spell_book, [Spell::Fireball]);
+    }
+
+    #[test]
+    fn starting_spell_ignores_wisdom() {
+        let army = Army {
+            hero: Some(GDHero::Solmyr),
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let hero = Hero::build(GDHero::Solmyr, &army).unwrap();
+        assert!(hero.max_spell_level() < Spell::ChainLightning.level());
+        assert_eq!(hero.spell_book, [Spell::ChainLightning]);
+
+        let mut state = battle(army.clone(), army);
+        let side = state.get_current_stack().side;
+        let enemy = state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).side != side)
+            .unwrap();
+        let health = state.get_stack(enemy).total_health();
+
+        let cast = Command::Cast(Cast {
+            spell: Spell::ChainLightning,
+            target: Some(state.get_stack(enemy).head),
+            destination: None,
+        });
+        assert!(state.is_command_applicable(cast));
+        state.apply_command(cast);
+        assert!(state.get_stack(enemy).total_health() < health);
+    }
+
+    #[test]
+    fn artifacts_add_to_primary_stats() {
+        let stats = |artifacts: &[Artifact]| {
+            let army = Army {
+                hero_stats: Some(Stats {
+                    attack: 2,
+                    defence: 2,
+                    power: 1,
+                    knowledge: 1,
+                }),
+                hero_artifacts: artifacts.to_vec(),
+                ..army(&[])
+            };
+            let stats = Hero::build(GDHero::Orrin, &army).unwrap().stats;
+            (stats.attack, stats.defence, stats.power, stats.knowledge)
+        };
+
+        assert_eq!(stats(&[]), (2, 2, 1, 1));
+        assert_eq!(
+            stats(&[Artifact::CentaursAxe, Artifact::ShieldOfTheDwarvenLords]),
+            (4, 4, 1, 1)
+        );
+        assert_eq!(
+            stats(&[Artifact::HelmOfHeavenlyEnlightenment]),
+            (8, 8, 7, 7)
+        );
+
+        // Штрафы артефактов не опускают навыки ниже минимума
+        assert_eq!(
+            stats(&[Artifact::SentinelsShield, Artifact::ThunderHelmet]),
+            (0, 14, 1, 11)
+        );
+    }
+
+    #[test]
+    fn equipped_artifacts_raise_shooter_damage() {
+        let damage = |artifacts: &[Artifact]| {
+            let attacker = Army {
+                hero: Some(GDHero::Orrin),
+                hero_artifacts: artifacts.to_vec(),
+                ..army(&[(Creature::Archer, 10)])
+            };
+            let state = battle(attacker, army(&[(Creature::Pikeman, 10)]));
+            let find = |creature| {
+                state
+                    .units()
+                    .into_iter()
+                    .find(|&handle| state.get_stack(handle).creature == creature)
+                    .unwrap()
+            };
+            state.get_expected_damage(find(Creature::Archer), find(Creature::Pikeman), true)
+        };
+
+        let base = damage(&[]);
+        assert!(damage(&[Artifact::CentaursAxe]) > base);
+
+        // Через всё поле лучники бьют вполсилы, пока лук не снимет штраф
+        let with_bow = damage(&[Artifact::GoldenBow]);
+        assert!(
+            (2 * base - 1..=2 * base + 1).contains(&with_bow),
+            "{base} {with_bow}"
+        );
+    }
+
+    #[test]
+    fn leadership_raises_morale_up_to_cap() {
+        assert_eq!(values(Ability::Leadership, |h| h.morale()), [0, 1, 2, 3]);
+
+        let hero = hero(
+            &[(Ability::Leadership, AbilityLevel::Expert)],
+            &[Artifact::BadgeOfCourage],
+        );
+        assert_eq!(hero.morale(), MAX_FORTUNE);
+    }
+
+    #[test]
+    fn luck_raises_luck_up_to_cap() {
+        assert_eq!(values(Ability::Luck, |h| h.luck()), [0, 1, 2, 3]);
+
+        let hero = hero(
+            &[(Ability::Luck, AbilityLevel::Advanced)],
+            &[Artifact::CloverOfFortune, Artifact::LadybirdOfLuck],
+        );
+        assert_eq!(hero.luck(), MAX_FORTUNE);
+    }
+
+    #[test]
+    fn resistance_gives_magic_resistance() {
+        assert_eq!(
+            values(Ability::Resistance, |h| h.magic_resistance()),
+            [0, 5, 10, 20]
+        );
+    }
+
+    #[test]
+    fn sorcery_boosts_spell_damage() {
+        // Волшебная стрела без школ магии: 10 * сила 1 + 10
+        assert_eq!(
+            values(Ability::Sorcery, |h| h.spell_damage(Spell::MagicArrow)),
+            [Some(20), Some(21), Some(22), Some(23)]
+        );
+    }
+
+    #[test]
+    fn mysticism_restores_mana_once_a_day() {
+        assert_eq!(
+            values(Ability::Mysticism, |h| h.daily_mana_regeneration()),
+            [0, 2, 3, 4]
+        );
+
+        let mut hero = hero(
+            &[(Ability::Mysticism, AbilityLevel::Expert)],
+            &[Artifact::CharmOfMana],
+        );
+        assert_eq!(hero.daily_mana_regeneration(), 5);
+
+        // Между раундами битвы мана не восстанавливается
+        hero.spell_points = 0;
+        hero.refresh_for_next_turn();
+        assert_eq!(hero.spell_points, 0);
+    }
+
+    #[test]
+    fn eagle_eye_learns_spells_up_to_its_level() {
+        assert_eq!(
+            values(Ability::EagleEye, |h| h.eagle_eye()),
+            [
+                None,
+                Some((40, SpellLevel::Second)),
+                Some((50, SpellLevel::Third)),
+                Some((60, SpellLevel::Fourth))
+            ]
+        );
+
+        let mut hero = hero(
+            &[
+                (Ability::EagleEye, AbilityLevel::Basic),
+                (Ability::Wisdom, AbilityLevel::Expert),
+            ],
+            &[],
+        );
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..100 {
+            hero.try_learn_spell(Spell::Slow, &mut rng);
+            hero.try_learn_spell(Spell::Fireball, &mut rng);
+        }
+        assert_eq!(hero.learned_spells, [Spell::Slow]);
+    }
+
+    #[test]
+    fn necromancy_raises_share_of_killed() {
+        assert_eq!(
+            values(Ability::Necromancy, |h| h.necromancy()),
+            [0, 10, 20, 30]
+        );
+    }
+
+    #[test]
+    fn learning_adds_experience() {
+        assert_eq!(values(Ability::Learning, |h| h.learning()), [0, 5, 10, 15]);
+    }
+
+    #[test]
+    fn first_aid_fixes_tent_healing() {
+        assert_eq!(
+            values(Ability::FirstAid, |h| h.first_aid_healing()),
+            [None, Some(50), Some(75), Some(100)]
+        );
+    }
+
+    #[test]
+    fn artillery_boosts_ballista() {
+        assert_eq!(
+            values(Ability::Artillery, |h| h.artillery_chance()),
+            [0, 50, 75, 100]
+        );
+        assert_eq!(
+            values(Ability::Artillery, |h| h.ballista_shots()),
+            [1, 1, 2, 2]
+        );
+    }
+
+    #[test]
+    fn intelligence_raises_max_spell_points() {
+        // У рыцаря одна единица знаний
+        assert_eq!(
+            values(Ability::Intelligence, |h| h.max_spell_points),
+            [10, 12, 15, 20]
+        );
+    }
+
+    #[test]
+    fn wisdom_opens_higher_spell_levels() {
+        assert_eq!(
+            values(Ability::Wisdom, |h| h.max_spell_level()),
+            [
+                SpellLevel::Second,
+                SpellLevel::Third,
+                SpellLevel::Fourth,
+                SpellLevel::Fifth
+            ]
+        );
     }
 }
@@ -0,0 +1,225 @@
+use gamedata::creatures::Creature;
+
+use super::{BattleOutcome, BattleReport, GameState, Side, SideReport};
+use crate::stack::Stack;
+
+// Опыт за победу над вражеским героем сверх здоровья убитых существ
+const HERO_DEFEAT_EXPERIENCE: i32 = 500;
+
+pub fn build(state: &GameState) -> Option<BattleReport> {
+    let outcome = state.outcome?;
+    let sides = [Side::Attacker, Side::Defender].map(|side| side_report(state, outcome, side));
+
+    Some(BattleReport { outcome, sides })
+}
+
+// Здоровье павших существ стороны без клонов, призванных и боевых машин
+pub fn killed_health(state: &GameState, side: Side) -> i32 {
+    state
+        .stacks
+        .values()
+        .filter(|stack| stack.side == side && is_counted(stack))
+        .filter(|stack| !stack.creature.is_war_machine())
+        .map(|stack| losses(stack) * stack.base_stats().health)
+        .sum()
+}
+
+fn side_report(state: &GameState, outcome: BattleOutcome, side: Side) -> SideReport {
+    SideReport {
+        hero: state.heroes[side as usize].as_ref().map(|hero| hero.hero),
+        casualties: count_by_creature(state, side, losses),
+        experience: experience(state, outcome, side),
+        raised: state.get_raised_creatures(side),
+        resurrected: count_by_creature(state, side, |stack| stack.resurrected),
+        learned_spells: state.get_learned_spells(side).to_vec(),
+    }
+}
+
+// Бежавшие с героем существа не погибли
+fn losses(stack: &Stack) -> i32 {
+    i32::max(stack.initial_count - stack.count - stack.fled, 0)
+}
+
+fn is_counted(stack: &Stack) -> bool {
+    !stack.is_clone && !stack.is_summoned
+}
+
+// Отряды одного вида складываются, порядок - как у отрядов в армии
+fn count_by_creature(
+    state: &GameState,
+    side: Side,
+    count: impl Fn(&Stack) -> i32,
+) -> Vec<(Creature, i32)> {
+    let mut counts: Vec<(Creature, i32)> = vec![];
+
+    let stacks = state
+        .units()
+        .into_iter()
+        .map(|handle| state.get_stack(handle))
+        .filter(|stack| stack.side == side && is_counted(stack));
+
+    for stack in stacks {
+        let amount = count(stack);
+        if amount == 0 {
+            continue;
+        }
+
+        match counts
+            .iter_mut()
+            .find(|(creature, _)| *creature == stack.creature)
+        {
+            Some((_, total)) => *total += amount,
+            None => counts.push((stack.creature, amount)),
+        }
+    }
+
+    counts
+}
+
+// Бежавший или откупившийся герой отдаёт победу противнику
+pub fn winner(outcome: BattleOutcome) -> Option<Side> {
+    match outcome {
+        BattleOutcome::Victory { winner } => Some(winner),
+        BattleOutcome::Tie => None,
+        BattleOutcome::Retreat { side } | BattleOutcome::Surrender { side, .. } => {
+            Some(side.other())
+        }
+    }
+}
+
+// Как в оригинале: здоровье убитых врагов, бонус за героя и прибавка Обучения
+fn experience(state: &GameState, outcome: BattleOutcome, side: Side) -> i32 {
+    let Some(hero) = state.heroes[side as usize].as_ref() else {
+        return 0;
+    };
+
+    if winner(outcome) != Some(side) {
+        return 0;
+    }
+
+    let enemy = side.other();
+    let hero_bonus = if state.heroes[enemy as usize].is_some() {
+        HERO_DEFEAT_EXPERIENCE
+    } else {
+        0
+    };
+
+    let experience = killed_health(state, enemy) + hero_bonus;
+    experience * (100 + hero.learning()) / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::heroes::{Ability, AbilityLevel, Hero as GDHero};
+
+    use super::*;
+    use crate::command::Command;
+    use crate::gamestate::{Army, StackHandle};
+    use crate::grid::GridPos;
+    use crate::testing::{army, battle};
+
+    // Обе армии с героями; у стороны, которая ходит первой, погибло 4 копейщика
+    // и есть ополовиненный отряд призванных элементалей
+    fn battle_with_losses() -> (GameState, Side) {
+        let hero_army = || Army {
+            hero: Some(GDHero::Orrin),
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let mut state = battle(hero_army(), hero_army());
+        let side = state.get_current_stack().side;
+
+        let pikemen = find_stack(&state, side, Creature::Pikeman);
+        state.edit_stack(pikemen, |stack| stack.count = 6);
+
+        let mut elementals = Stack::new(Creature::AirElemental, 10, GridPos::new(5, 5), side);
+        elementals.is_summoned = true;
+        elementals.count = 5;
+        state.add_stack(elementals);
+
+        (state, side)
+    }
+
+    fn find_stack(state: &GameState, side: Side, creature: Creature) -> StackHandle {
+        state
+            .units()
+            .into_iter()
+            .find(|&handle| {
+                let stack = state.get_stack(handle);
+                stack.side == side && stack.creature == creature
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn retreat_counts_only_killed_creatures() {
+        let (mut state, side) = battle_with_losses();
+        state.apply_command(Command::Retreat);
+
+        let report = state.get_battle_report().unwrap();
+        assert_eq!(report.outcome, BattleOutcome::Retreat { side });
+        assert_eq!(
+            report.sides[side as usize].casualties,
+            [(Creature::Pikeman, 4)]
+        );
+        assert_eq!(report.sides[side as usize].experience, 0);
+        assert_eq!(
+            report.sides[side.other() as usize].experience,
+            4 * Creature::Pikeman.base_stats().health + HERO_DEFEAT_EXPERIENCE
+        );
+    }
+
+    #[test]
+    fn surrender_counts_only_killed_creatures() {
+        let (mut state, side) = battle_with_losses();
+        state.apply_command(Command::Surrender);
+
+        let report = state.get_battle_report().unwrap();
+        assert_eq!(
+            report.outcome,
+            BattleOutcome::Surrender {
+                side,
+                cost: 6 * Creature::Pikeman.cost()
+            }
+        );
+        assert_eq!(
+            report.sides[side as usize].casualties,
+            [(Creature::Pikeman, 4)]
+        );
+        assert_eq!(
+            report.sides[side.other() as usize].experience,
+            4 * Creature::Pikeman.base_stats().health + HERO_DEFEAT_EXPERIENCE
+        );
+    }
+
+    #[test]
+    fn necromancy_raises_after_enemy_leaves_battle() {
+        for command in [Command::Retreat, Command::Surrender] {
+            let necromancer = || Army {
+                hero: Some(GDHero::Orrin),
+                hero_abilities: Some(vec![(Ability::Necromancy, AbilityLevel::Expert)]),
+                ..army(&[(Creature::Pikeman, 10)])
+            };
+            let mut state = battle(necromancer(), necromancer());
+            let side = state.get_current_stack().side;
+
+            let pikemen = find_stack(&state, side, Creature::Pikeman);
+            state.edit_stack(pikemen, |stack| stack.count = 4);
+            state.apply_command(command);
+
+            let percent = state.heroes[side.other() as usize]
+                .as_ref()
+                .unwrap()
+                .necromancy();
+            let raised = 6 * Creature::Pikeman.base_stats().health * percent
+                / 100
+                / Creature::Skeleton.base_stats().health;
+            assert!(raised > 0);
+
+            assert_eq!(
+                state.get_raised_creatures(side.other()),
+                Some((Creature::Skeleton, raised))
+            );
+            assert_eq!(state.get_raised_creatures(side), None);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use gamedata::heroes::{Ability, AbilityLevel};
+use gamedata::towns::{Town, WallSection, WallState};
+use rand::Rng;
+use strum::{EnumCount, IntoEnumIterator};
+
+use super::{GameState, Side, StackHandle};
+use crate::event::{Event, MoatDamage, TowerShot};
+use crate::grid::GridPos;
+
+#[derive(Clone)]
+pub struct Siege {
+    pub town: Town,
+    // Оставшиеся попадания катапульты для каждой части стены
+    health: [i32; WallSection::COUNT],
+}
+
+impl Siege {
+    pub fn new(town: Town) -> Self {
+        Self {
+            town,
+            health: [WallSection::MAX_HEALTH; WallSection::COUNT],
+        }
+    }
+
+    pub fn section_health(&self, section: WallSection) -> i32 {
+        self.health[section as usize]
+    }
+
+    pub fn is_standing(&self, section: WallSection) -> bool {
+        self.section_health(section) > 0
+    }
+
+    pub fn wall_state(&self, section: WallSection) -> WallState {
+        match self.section_health(section) {
+            0 => WallState::Destroyed,
+            WallSection::MAX_HEALTH => WallState::Intact,
+            _ => WallState::Damaged,
+        }
+    }
+
+    // Возвращает true, если часть стены рухнула
+    pub fn damage(&mut self, section: WallSection) -> bool {
+        let health = &mut self.health[section as usize];
+        *health = i32::max(*health - 1, 0);
+        *health == 0
+    }
+
+    // Клетка закрыта стеной, а целые ворота пропускают только защитников
+    pub fn is_blocked(&self, cell: GridPos, side: Side) -> bool {
+        match section_for_cell(cell) {
+            Some(WallSection::Gate) => {
+                self.is_standing(WallSection::Gate) && side == Side::Attacker
+            }
+            Some(section) => self.is_standing(section),
+            None => false,
+        }
+    }
+
+    // Сначала пробиваем ворота, затем стены, и только потом башни
+    pub fn preferred_target(&self) -> Option<WallSection> {
+        [
+            WallSection::Gate,
+            WallSection::UpperMiddleWall,
+            WallSection::LowerMiddleWall,
+            WallSection::UpperWall,
+            WallSection::LowerWall,
+            WallSection::UpperTower,
+            WallSection::LowerTower,
+            WallSection::Keep,
+        ]
+        .into_iter()
+        .find(|&section| self.is_standing(section))
+    }
+}
+
+// Клетки стены сверху вниз, по одной в каждом ряду поля
+// Упрощение: в игре контур стены немного отличается от города к городу,
+// здесь у всех городов одна и та же раскладка
+const WALL_CELLS: [(i32, i32, WallSection); 11] = [
+    (12, 1, WallSection::UpperTower),
+    (12, 2, WallSection::UpperWall),
+    (11, 3, WallSection::UpperWall),
+    (11, 4, WallSection::UpperMiddleWall),
+    (10, 5, WallSection::UpperMiddleWall),
+    (10, 6, WallSection::Gate),
+    (10, 7, WallSection::LowerMiddleWall),
+    (11, 8, WallSection::LowerMiddleWall),
+    (11, 9, WallSection::LowerWall),
+    (12, 10, WallSection::LowerWall),
+    (12, 11, WallSection::LowerTower),
+];
+
+// Центральная башня стоит за стеной и клеток не занимает
+pub fn section_cells(section: WallSection) -> Vec<GridPos> {
+    WALL_CELLS
+        .iter()
+        .filter(|&&(_, _, s)| s == section)
+        .map(|&(x, y, _)| GridPos::new(x, y))
+        .collect()
+}
+
+pub fn section_for_cell(cell: GridPos) -> Option<WallSection> {
+    WALL_CELLS
+        .iter()
+        .find(|&&(x, y, _)| GridPos::new(x, y) == cell)
+        .map(|&(_, _, section)| section)
+}
+
+fn wall_x(y: i32) -> i32 {
+    WALL_CELLS[(y - 1) as usize].0
+}
+
+// Ров тянется вдоль стены, перед воротами перекинут мост
+pub fn is_moat(cell: GridPos) -> bool {
+    cell.x == wall_x(cell.y) - 1 && section_for_cell(cell.relative(1, 0)) != Some(WallSection::Gate)
+}
+
+pub fn is_inside(cell: GridPos) -> bool {
+    cell.x > wall_x(cell.y)
+}
+
+// Телепорт не переносит отряды через стену, даже разрушенную
+pub fn is_across_wall(state: &GameState, from: GridPos, to: GridPos) -> bool {
+    state.siege.is_some() && is_inside(from) != is_inside(to)
+}
+
+// Стрелок снаружи бьёт вполсилы по цели за уцелевшей стеной
+pub fn is_shot_obstructed(siege: &Siege, from: GridPos, to: GridPos) -> bool {
+    let section = WALL_CELLS[(to.y - 1) as usize].2;
+    !is_inside(from) && is_inside(to) && siege.is_standing(section)
+}
+
+// Отряд, закончивший движение во рву, получает урон города
+pub fn enter_moat(state: &mut GameState) -> Option<Event> {
+    let town = state.siege.as_ref()?.town;
+    let stack = state.get_current_stack();
+
+    if !stack.get_occupied_cells().into_iter().any(is_moat) {
+        return None;
+    }
+
+    let handle = state.current_stack;
+    let stack = state.get_stack_mut(handle);
+    let health_before = stack.total_health();
+    stack.receive_damage(town.moat_damage());
+
+    Some(Event::MoatDamage(MoatDamage {
+        stack: handle,
+        damage: health_before - stack.total_health(),
+        lethal: !stack.is_alive(),
+    }))
+}
+
+// В конце раунда каждая уцелевшая башня стреляет по самому ценному отряду нападающих
+// Упрощение: в игре башни стоят в очереди ходов как отдельные стрелки,
+// здесь они стреляют залпом при смене раунда
+pub fn tower_volley(state: &mut GameState) -> Vec<Event> {
+    let Some(siege) = state.siege.as_ref() else {
+        return vec![];
+    };
+
+    let towers: Vec<WallSection> = WallSection::iter()
+        .filter(|&section| section.tower_damage().is_some() && siege.is_standing(section))
+        .collect();
+
+    let mut events = vec![];
+
+    for section in towers {
+        let Some(target) = tower_target(state) else {
+            break;
+        };
+
+        let (low, high) = section.tower_damage().unwrap();
+        let damage = state.rng.gen_range(low..=high);
+
+        let stack = state.get_stack_mut(target);
+        let health_before = stack.total_health();
+        stack.receive_damage(damage);
+
+        events.push(Event::TowerShot(TowerShot {
+            section,
+            target,
+            damage: health_before - stack.total_health(),
+            lethal: !stack.is_alive(),
+        }));
+    }
+
+    events
+}
+
+// Землетрясение рушит стены и ворота, но не башни
+fn quake_targets(siege: &Siege) -> Vec<WallSection> {
+    WallSection::iter()
+        .filter(|&section| section.tower_damage().is_none() && siege.is_standing(section))
+        .collect()
+}
+
+pub fn can_quake(state: &GameState) -> bool {
+    state
+        .siege
+        .as_ref()
+        .is_some_and(|siege| !quake_targets(siege).is_empty())
+}
+
+// Каждый толчок снимает одно попадание со случайной уцелевшей части стены
+pub fn earthquake(state: &mut GameState, shocks: usize) {
+    let Some(siege) = state.siege.as_mut() else {
+        return;
+    };
+
+    for _ in 0..shocks {
+        let targets = quake_targets(siege);
+        if targets.is_empty() {
+            break;
+        }
+
+        let section = targets[state.rng.gen_range(0..targets.len())];
+        siege.damage(section);
+    }
+}
+
+fn tower_target(state: &GameState) -> Option<StackHandle> {
+    state
+        .units()
+        .into_iter()
+        .filter(|&handle| {
+            let stack = state.get_stack(handle);
+            stack.side == Side::Attacker && stack.is_alive() && !stack.creature.is_war_machine()
+        })
+        .max_by_key(|&handle| {
+            let stack = state.get_stack(handle);
+            stack.count * stack.creature.cost()
+        })
+}
+
+// Число выстрелов катапульты и шанс попадания в процентах
+pub fn catapult_shots(state: &GameState, side: Side) -> (usize, i32) {
+    let ballistics = state.heroes[side as usize]
+        .as_ref()
+        .and_then(|hero| hero.get_ability_level(Ability::Ballistics));
+
+    match ballistics {
+        None => (1, 50),
+        Some(AbilityLevel::Basic) => (1, 70),
+        Some(AbilityLevel::Advanced) => (2, 80),
+        Some(AbilityLevel::Expert) => (2, 90),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::Creature;
+
+    use super::*;
+    use crate::gamestate::Army;
+    use crate::pathfinding::NavigationArray;
+    use crate::testing::{army, battle};
+
+    #[test]
+    fn destroyed_wall_cell_becomes_passable() {
+        let town = Army {
+            town: Some(Town::Castle),
+            ..army(&[(Creature::Pikeman, 10)])
+        };
+        let mut state = battle(army(&[(Creature::Pikeman, 10)]), town);
+
+        let wall = section_cells(WallSection::UpperWall)[0];
+        let moat = wall.relative(-1, 0);
+        assert!(is_moat(moat));
+
+        let reaches_wall = |state: &GameState| {
+            NavigationArray::new(moat, state, Side::Attacker, false)
+                .get_shortest_path(wall)
+                .is_some()
+        };
+        assert!(state.is_cell_blocked(wall, Side::Attacker));
+        assert!(!reaches_wall(&state));
+
+        let siege = state.siege.as_mut().unwrap();
+        while !siege.damage(WallSection::UpperWall) {}
+
+        assert!(!state.is_cell_blocked(wall, Side::Attacker));
+        assert!(reaches_wall(&state));
+    }
+}
@@ -0,0 +1,815 @@
+use gamedata::creatures::Creature;
+use gamedata::heroes::AbilityLevel;
+use gamedata::spells::{Spell, SpellTarget, TargetSide};
+
+use super::hero::Hero;
+use super::{siege, GameState, Side, StackHandle};
+use crate::command::Cast;
+use crate::effect::{Status, StatusEffect};
+use crate::event::SpellHit;
+use crate::grid::GridPos;
+use crate::pathfinding;
+use crate::stack::Stack;
+
+mod resistance;
+
+pub use resistance::is_immune;
+use resistance::Resolution;
+
+#[derive(Clone, Copy)]
+enum Effect {
+    Damage,
+    Heal,
+    Resurrect,
+    Sacrifice,
+    Teleport,
+    Clone,
+    Summon(Creature),
+    Status(Status),
+    Dispel,
+    Earthquake,
+}
+
+fn effect(spell: Spell) -> Option<Effect> {
+    if spell.damage(None).is_some() {
+        return Some(Effect::Damage);
+    }
+
+    let effect = match spell {
+        Spell::Cure => Effect::Heal,
+        Spell::Resurrection | Spell::AnimateDead => Effect::Resurrect,
+        Spell::Sacrifice => Effect::Sacrifice,
+        Spell::Teleport => Effect::Teleport,
+        Spell::Clone => Effect::Clone,
+        Spell::SummonAir => Effect::Summon(Creature::AirElemental),
+        Spell::SummonEarth => Effect::Summon(Creature::EarthElemental),
+        Spell::SummonFire => Effect::Summon(Creature::FireElemental),
+        Spell::SummonWater => Effect::Summon(Creature::WaterElemental),
+        Spell::Dispel => Effect::Dispel,
+        Spell::Earthquake => Effect::Earthquake,
+        // Препятствий на поле боя нет, а отряды не переходят под чужое управление
+        Spell::Quicksand
+        | Spell::LandMine
+        | Spell::ForceField
+        | Spell::FireWall
+        | Spell::RemoveObstacle
+        | Spell::Hypnotize => return None,
+        _ => return Status::from_spell(spell).map(Effect::Status),
+    };
+
+    Some(effect)
+}
+
+pub fn is_castable(spell: Spell) -> bool {
+    effect(spell).is_some()
+}
+
+pub fn is_applicable(state: &GameState, hero: &Hero, side: Side, cast: Cast) -> bool {
+    let Some(effect) = effect(cast.spell) else {
+        return false;
+    };
+
+    let school_level = hero.school_level(cast.spell);
+
+    let is_target_valid = match cast.spell.target(school_level) {
+        SpellTarget::Stack(_) => cast
+            .target
+            .and_then(|cell| find_target(state, cast.spell, side, school_level, cell))
+            .is_some(),
+        SpellTarget::Area { .. } => cast.target.is_some(),
+        SpellTarget::Global(_) | SpellTarget::None => true,
+        SpellTarget::Hex => false,
+    };
+
+    if !is_target_valid {
+        return false;
+    }
+
+    match effect {
+        Effect::Sacrifice => {
+            let target = cast
+                .target
+                .and_then(|cell| find_target(state, cast.spell, side, school_level, cell));
+            let victim = cast
+                .destination
+                .and_then(|cell| state.find_unit_for_cell(cell))
+                .filter(|&handle| state.get_stack(handle).side == side);
+
+            matches!((target, victim), (Some(target), Some(victim)) if target != victim)
+        }
+        Effect::Teleport => {
+            let target = cast
+                .target
+                .and_then(|cell| find_target(state, cast.spell, side, school_level, cell));
+
+            match (target, cast.destination) {
+                (Some(handle), Some(destination)) => {
+                    let stack = state.get_stack(handle);
+                    is_position_free(state, stack.creature, side, destination, Some(handle))
+                        && !siege::is_across_wall(state, stack.head, destination)
+                }
+                _ => false,
+            }
+        }
+        Effect::Clone => cast
+            .target
+            .and_then(|cell| state.find_unit_for_cell(cell))
+            .map(|handle| state.get_stack(handle))
+            .is_some_and(|stack| {
+                find_free_position_near(state, stack.creature, side, stack.head).is_some()
+            }),
+        Effect::Summon(creature) => find_free_position(state, creature, side).is_some(),
+        Effect::Earthquake => siege::can_quake(state),
+        Effect::Damage | Effect::Heal | Effect::Resurrect | Effect::Status(_) | Effect::Dispel => {
+            true
+        }
+    }
+}
+
+// Возвращает задетые отряды и отряды, устоявшие перед заклинанием
+pub fn apply(state: &mut GameState, side: Side, cast: Cast) -> (Vec<SpellHit>, Vec<StackHandle>) {
+    let hero = state.heroes[side as usize].as_ref().unwrap();
+    let power = hero.stats.power;
+    let school_level = hero.school_level(cast.spell);
+    let hero_damage = hero.spell_damage(cast.spell);
+
+    let effect = effect(cast.spell).unwrap();
+    let targets = affected_stacks(state, cast.spell, side, school_level, cast.target);
+
+    let mut hits = vec![];
+    let mut resisted = vec![];
+
+    let targets: Vec<StackHandle> = targets
+        .into_iter()
+        .filter_map(|handle| {
+            match resistance::resolve(state, cast.spell, school_level, side, handle) {
+                Resolution::Affected(target) => Some(target),
+                Resolution::Resisted => {
+                    resisted.push(handle);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let has_targets =
+        matches!(effect, Effect::Summon(_) | Effect::Earthquake) || !targets.is_empty();
+    if !has_targets {
+        return (hits, resisted);
+    }
+
+    match effect {
+        Effect::Damage if cast.spell == Spell::ChainLightning => {
+            let mut damage = hero_damage.unwrap();
+
+            let jumps = if school_level == Some(AbilityLevel::Expert) {
+                5
+            } else {
+                4
+            };
+
+            let mut current = targets[0];
+            let mut visited = vec![];
+            for jump in 0..jumps {
+                visited.push(current);
+
+                // Первую цель уже проверили, остальные сопротивляются сами
+                let stack = state.stacks.get_mut(&current).unwrap();
+                let resists = jump > 0
+                    && resistance::resists(stack, cast.spell, side, &state.heroes, &mut state.rng);
+                if resists {
+                    resisted.push(current);
+                } else {
+                    let health_before = stack.total_health();
+                    stack.receive_damage(spell_damage(stack, cast.spell, damage));
+                    hits.push(SpellHit {
+                        stack: current,
+                        damage: health_before - stack.total_health(),
+                        lethal: !stack.is_alive(),
+                    });
+                }
+
+                // Молния перескакивает на ближайший ещё не задетый отряд
+                // и с каждым прыжком теряет половину силы
+                damage /= 2;
+
+                let from = state.get_stack(current);
+                let next = state
+                    .units()
+                    .into_iter()
+                    .filter(|handle| !visited.contains(handle))
+                    .filter(|&handle| state.get_stack(handle).is_alive())
+                    .min_by_key(|&handle| state.get_stack(handle).distance_to(from));
+
+                match next {
+                    Some(handle) => current = handle,
+                    None => break,
+                }
+            }
+        }
+        Effect::Damage => {
+            let damage = hero_damage.unwrap();
+
+            for handle in targets {
+                let stack = state.get_stack_mut(handle);
+                let health_before = stack.total_health();
+                stack.receive_damage(spell_damage(stack, cast.spell, damage));
+                hits.push(SpellHit {
+                    stack: handle,
+                    damage: health_before - stack.total_health(),
+                    lethal: !stack.is_alive(),
+                });
+            }
+        }
+        Effect::Heal | Effect::Resurrect => {
+            let (multiplier, base) = cast.spell.healing(school_level).unwrap();
+            let amount = multiplier * power + base;
+
+            for handle in targets {
+                let stack = state.get_stack_mut(handle);
+                match effect {
+                    Effect::Heal => {
+                        stack.heal(amount);
+                        stack.remove_effects(|e| e.status.is_negative());
+                    }
+                    _ => stack.resurrect(amount),
+                }
+                hits.push(SpellHit {
+                    stack: handle,
+                    damage: 0,
+                    lethal: false,
+                });
+            }
+        }
+        Effect::Sacrifice => {
+            let victim_handle = state.find_unit_for_cell(cast.destination.unwrap()).unwrap();
+            let victim = state.get_stack_mut(victim_handle);
+
+            let bonus = match school_level {
+                None | Some(AbilityLevel::Basic) => 3,
+                Some(AbilityLevel::Advanced) => 6,
+                Some(AbilityLevel::Expert) => 10,
+            };
+            let amount = (power + victim.base_stats().health + bonus) * victim.count;
+
+            let damage = victim.total_health();
+            victim.receive_damage(damage);
+            hits.push(SpellHit {
+                stack: victim_handle,
+                damage,
+                lethal: true,
+            });
+
+            state.get_stack_mut(targets[0]).resurrect(amount);
+            hits.push(SpellHit {
+                stack: targets[0],
+                damage: 0,
+                lethal: false,
+            });
+        }
+        Effect::Teleport => {
+            let stack = state.get_stack_mut(targets[0]);
+            stack.head = cast.destination.unwrap();
+            hits.push(SpellHit {
+                stack: targets[0],
+                damage: 0,
+                lethal: false,
+            });
+        }
+        Effect::Clone => {
+            let original = state.get_stack(targets[0]);
+            let head = find_free_position_near(state, original.creature, side, original.head);
+
+            let mut clone = Stack::new(original.creature, original.count, head.unwrap(), side);
+            clone.is_clone = true;
+
+            let handle = state.add_stack(clone);
+            hits.push(SpellHit {
+                stack: handle,
+                damage: 0,
+                lethal: false,
+            });
+        }
+        Effect::Summon(creature) => {
+            let multiplier = match school_level {
+                None | Some(AbilityLevel::Basic) => 2,
+                Some(AbilityLevel::Advanced) => 3,
+                Some(AbilityLevel::Expert) => 4,
+            };
+            let head = find_free_position(state, creature, side).unwrap();
+            let mut stack = Stack::new(creature, multiplier * power, head, side);
+            stack.is_summoned = true;
+
+            let handle = state.add_stack(stack);
+            hits.push(SpellHit {
+                stack: handle,
+                damage: 0,
+                lethal: false,
+            });
+        }
+        Effect::Status(status) => {
+            // Длительность в раундах равна силе героя
+            let rounds = i32::max(power, 1);
+            let effect = StatusEffect::new(status, school_level, Some(rounds));
+
+            for handle in targets {
+                state.get_stack_mut(handle).apply_effect(effect);
+                hits.push(SpellHit {
+                    stack: handle,
+                    damage: 0,
+                    lethal: false,
+                });
+            }
+        }
+        Effect::Earthquake => {
+            let shocks = match school_level {
+                None | Some(AbilityLevel::Basic) => 2,
+                Some(AbilityLevel::Advanced) => 3,
+                Some(AbilityLevel::Expert) => 4,
+            };
+            siege::earthquake(state, shocks);
+        }
+        Effect::Dispel => {
+            for handle in targets {
+                state.get_stack_mut(handle).remove_effects(|_| true);
+                hits.push(SpellHit {
+                    stack: handle,
+                    damage: 0,
+                    lethal: false,
+                });
+            }
+        }
+    }
+
+    (hits, resisted)
+}
+
+// Урон по задетым отрядам без учёта сопротивления и отражения
+// Цепная молния оценивается только по первой цели
+pub fn expected_damage(
+    state: &GameState,
+    spell: Spell,
+    side: Side,
+    target: Option<GridPos>,
+) -> Vec<(StackHandle, i32)> {
+    let Some(hero) = state.heroes[side as usize].as_ref() else {
+        return vec![];
+    };
+    let school_level = hero.school_level(spell);
+    let Some(damage) = hero.spell_damage(spell) else {
+        return vec![];
+    };
+
+    let mut targets = affected_stacks(state, spell, side, school_level, target);
+    if spell == Spell::ChainLightning {
+        targets.truncate(1);
+    }
+
+    targets
+        .into_iter()
+        .map(|handle| {
+            let stack = state.get_stack(handle);
+            (handle, spell_damage(stack, spell, damage))
+        })
+        .collect()
+}
+
+fn spell_damage(stack: &Stack, spell: Spell, damage: i32) -> i32 {
+    let protection: f32 = stack
+        .effects
+        .iter()
+        .map(|e| 1.0 - e.spell_damage_reduction(spell.school()))
+        .product();
+    let reduction = 1.0 - stack.creature.spell_damage_reduction() as f32 / 100.0;
+    let multiplier = protection * reduction;
+
+    (damage as f32 * multiplier) as i32
+}
+
+pub fn affected_stacks(
+    state: &GameState,
+    spell: Spell,
+    side: Side,
+    school_level: Option<AbilityLevel>,
+    target: Option<GridPos>,
+) -> Vec<StackHandle> {
+    let handles = match spell.target(school_level) {
+        SpellTarget::Stack(_) => target
+            .and_then(|cell| find_target(state, spell, side, school_level, cell))
+            .into_iter()
+            .collect(),
+        SpellTarget::Area { radius, ring } => {
+            let Some(center) = target else {
+                return vec![];
+            };
+
+            state
+                .units()
+                .into_iter()
+                .filter(|&handle| state.get_stack(handle).is_alive())
+                .filter(|&handle| {
+                    state
+                        .get_stack(handle)
+                        .get_occupied_cells()
+                        .into_iter()
+                        .any(|cell| is_in_area(cell, center, radius, ring))
+                })
+                .collect()
+        }
+        SpellTarget::Global(target_side) => state
+            .units()
+            .into_iter()
+            .filter(|&handle| {
+                let stack = state.get_stack(handle);
+                stack.is_alive() && is_side_matching(target_side, side, stack.side)
+            })
+            .collect(),
+        SpellTarget::Hex | SpellTarget::None => vec![],
+    };
+
+    handles
+        .into_iter()
+        .filter(|&handle| is_affected(spell, school_level, state.get_stack(handle)))
+        .collect()
+}
+
+// Клетки, которые можно выбрать целью заклинания
+// Для Телепорта и Жертвы это первая из двух клеток
+pub fn target_cells(
+    state: &GameState,
+    spell: Spell,
+    side: Side,
+    school_level: Option<AbilityLevel>,
+) -> Vec<GridPos> {
+    let all_cells =
+        GridPos::X_RANGE.flat_map(|x| GridPos::Y_RANGE.map(move |y| GridPos::new(x, y)));
+
+    match spell.target(school_level) {
+        SpellTarget::Stack(_) => all_cells
+            .filter(|&cell| find_target(state, spell, side, school_level, cell).is_some())
+            .collect(),
+        SpellTarget::Area { .. } => all_cells.collect(),
+        SpellTarget::Global(_) | SpellTarget::Hex | SpellTarget::None => vec![],
+    }
+}
+
+// Клетки, которые накроет заклинание по области с центром в указанной клетке
+pub fn area_cells(
+    spell: Spell,
+    school_level: Option<AbilityLevel>,
+    center: GridPos,
+) -> Vec<GridPos> {
+    let SpellTarget::Area { radius, ring } = spell.target(school_level) else {
+        return vec![];
+    };
+
+    GridPos::X_RANGE
+        .flat_map(|x| GridPos::Y_RANGE.map(move |y| GridPos::new(x, y)))
+        .filter(|&cell| is_in_area(cell, center, radius, ring))
+        .collect()
+}
+
+fn is_in_area(cell: GridPos, center: GridPos, radius: i32, ring: bool) -> bool {
+    let distance = cell.distance(center);
+    if ring {
+        distance == radius
+    } else {
+        distance <= radius
+    }
+}
+
+fn find_target(
+    state: &GameState,
+    spell: Spell,
+    side: Side,
+    school_level: Option<AbilityLevel>,
+    cell: GridPos,
+) -> Option<StackHandle> {
+    let SpellTarget::Stack(target_side) = spell.target(school_level) else {
+        return None;
+    };
+
+    // Воскрешать можно и полностью погибшие отряды
+    let can_target_dead = matches!(
+        spell,
+        Spell::Resurrection | Spell::AnimateDead | Spell::Sacrifice
+    );
+
+    state
+        .find_unit_for_cell(cell)
+        .or_else(|| {
+            if can_target_dead {
+                state.find_corpse_for_cell(cell)
+            } else {
+                None
+            }
+        })
+        .filter(|&handle| is_side_matching(target_side, side, state.get_stack(handle).side))
+        .filter(|&handle| is_affected(spell, school_level, state.get_stack(handle)))
+        .filter(|&handle| !resistance::is_immune(state.get_stack(handle), spell))
+}
+
+fn is_side_matching(target_side: TargetSide, caster_side: Side, stack_side: Side) -> bool {
+    match target_side {
+        TargetSide::Friendly => stack_side == caster_side,
+        TargetSide::Enemy => stack_side != caster_side,
+        TargetSide::Any => true,
+    }
+}
+
+// Ограничения заклинаний на тип существ
+fn is_affected(spell: Spell, school_level: Option<AbilityLevel>, stack: &Stack) -> bool {
+    let creature = stack.creature;
+
+    match spell {
+        Spell::DestroyUndead => creature.is_undead(),
+        Spell::DeathRipple => !creature.is_undead(),
+        Spell::AnimateDead => creature.is_undead() && !stack.is_clone && is_wounded(stack),
+        Spell::Resurrection | Spell::Sacrifice => {
+            !creature.is_undead() && !stack.is_clone && is_wounded(stack)
+        }
+        Spell::Clone => {
+            let max_level = match school_level {
+                None | Some(AbilityLevel::Basic) => 5,
+                Some(AbilityLevel::Advanced) => 6,
+                Some(AbilityLevel::Expert) => 7,
+            };
+            !stack.is_clone && creature.base_stats().level <= max_level
+        }
+        _ => true,
+    }
+}
+
+fn is_wounded(stack: &Stack) -> bool {
+    stack.total_health() < stack.initial_count * stack.base_stats().health
+}
+
+fn is_position_free(
+    state: &GameState,
+    creature: Creature,
+    side: Side,
+    head: GridPos,
+    ignore: Option<StackHandle>,
+) -> bool {
+    pathfinding::get_occupied_cells_for(creature, side, head).is_some_and(|cells| {
+        cells.into_iter().all(|cell| {
+            let occupant = state
+                .find_unit_for_cell(cell)
+                .filter(|&handle| Some(handle) != ignore);
+            occupant.is_none() && !state.is_cell_blocked(cell, side)
+        })
+    })
+}
+
+// Призванные существа встают ближе к краю поля своей стороны
+fn find_free_position(state: &GameState, creature: Creature, side: Side) -> Option<GridPos> {
+    let columns: Vec<i32> = match side {
+        Side::Attacker => GridPos::X_RANGE.collect(),
+        Side::Defender => GridPos::X_RANGE.rev().collect(),
+    };
+
+    columns
+        .into_iter()
+        .flat_map(|x| GridPos::Y_RANGE.map(move |y| GridPos::new(x, y)))
+        .find(|&head| is_position_free(state, creature, side, head, None))
+}
+
+fn find_free_position_near(
+    state: &GameState,
+    creature: Creature,
+    side: Side,
+    position: GridPos,
+) -> Option<GridPos> {
+    GridPos::X_RANGE
+        .flat_map(|x| GridPos::Y_RANGE.map(move |y| GridPos::new(x, y)))
+        .filter(|&head| is_position_free(state, creature, side, head, None))
+        .min_by_key(|head| head.distance(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::heroes::{Ability, Hero as GDHero, Stats};
+    use gamedata::spells::SpellType;
+    use gamedata::towns::{Town, WallSection};
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::command::Command;
+    use crate::gamestate::Army;
+    use crate::testing::{army, battle};
+
+    // Герой с мудростью эксперта и запасом маны знает только нужные заклинания
+    fn caster(spells: &[Spell]) -> Army {
+        Army {
+            hero: Some(GDHero::Orrin),
+            hero_stats: Some(Stats {
+                attack: 0,
+                defence: 0,
+                power: 5,
+                knowledge: 10,
+            }),
+            hero_abilities: Some(vec![(Ability::Wisdom, AbilityLevel::Expert)]),
+            hero_spells: Some(spells.to_vec()),
+            ..army(&[(Creature::Pikeman, 10)])
+        }
+    }
+
+    fn find_stack(state: &GameState, side: Side) -> StackHandle {
+        state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).side == side)
+            .unwrap()
+    }
+
+    fn cast(spell: Spell, target: Option<GridPos>) -> Command {
+        Command::Cast(Cast {
+            spell,
+            target,
+            destination: None,
+        })
+    }
+
+    #[test]
+    fn cast_spends_spell_points_once_per_round() {
+        let spells = [Spell::MagicArrow, Spell::Bless];
+        let mut state = battle(caster(&spells), caster(&spells));
+        let side = state.get_current_stack().side;
+
+        let enemy = find_stack(&state, side.other());
+        let enemy_cell = state.get_stack(enemy).head;
+        let health = state.get_stack(enemy).total_health();
+        let points = state.get_hero_spell_points(side).unwrap();
+
+        state.apply_command(cast(Spell::MagicArrow, Some(enemy_cell)));
+
+        // Волшебная стрела без школ магии: 10 за единицу силы и 10 сверху
+        assert_eq!(health - state.get_stack(enemy).total_health(), 60);
+        assert_eq!(
+            state.get_hero_spell_points(side).unwrap(),
+            points - state.get_hero_spell_cost(side, Spell::MagicArrow).unwrap()
+        );
+
+        // Колдовство не тратит ход отряда, но второй раз за раунд герой не колдует
+        assert_eq!(state.get_current_stack().side, side);
+        let own_cell = state.get_stack(find_stack(&state, side)).head;
+        assert!(!state.is_spell_available(Spell::Bless));
+        assert!(!state.is_command_applicable(cast(Spell::Bless, Some(own_cell))));
+    }
+
+    #[test]
+    fn cast_needs_enough_spell_points() {
+        let army = Army {
+            hero_stats: Some(Stats {
+                attack: 0,
+                defence: 0,
+                power: 1,
+                knowledge: 0,
+            }),
+            ..caster(&[Spell::MagicArrow])
+        };
+        let state = battle(army.clone(), army);
+
+        assert!(!state.is_spell_available(Spell::MagicArrow));
+    }
+
+    #[test]
+    fn stack_spells_target_only_their_side() {
+        let spells = [Spell::MagicArrow, Spell::Bless];
+        let state = battle(caster(&spells), caster(&spells));
+        let side = state.get_current_stack().side;
+
+        let own_cell = state.get_stack(find_stack(&state, side)).head;
+        let enemy_cell = state.get_stack(find_stack(&state, side.other())).head;
+
+        assert!(state.is_command_applicable(cast(Spell::Bless, Some(own_cell))));
+        assert!(!state.is_command_applicable(cast(Spell::Bless, Some(enemy_cell))));
+        assert!(state.is_command_applicable(cast(Spell::MagicArrow, Some(enemy_cell))));
+        assert!(!state.is_command_applicable(cast(Spell::MagicArrow, Some(own_cell))));
+        assert!(!state.is_command_applicable(cast(Spell::MagicArrow, None)));
+
+        let cells = state.get_spell_target_cells(Spell::Bless);
+        assert!(cells.contains(&own_cell));
+        assert!(!cells.contains(&enemy_cell));
+    }
+
+    #[test]
+    fn prayer_and_sorrow_are_mass_only_at_expert() {
+        let spells = [Spell::Prayer, Spell::Sorrow];
+        let state = battle(caster(&spells), caster(&spells));
+        let side = state.get_current_stack().side;
+
+        let own_cell = state.get_stack(find_stack(&state, side)).head;
+        let enemy_cell = state.get_stack(find_stack(&state, side.other())).head;
+
+        assert!(state.is_command_applicable(cast(Spell::Prayer, Some(own_cell))));
+        assert!(!state.is_command_applicable(cast(Spell::Prayer, None)));
+        assert!(state.is_command_applicable(cast(Spell::Sorrow, Some(enemy_cell))));
+        assert!(!state.is_command_applicable(cast(Spell::Sorrow, None)));
+
+        let expert = Some(AbilityLevel::Expert);
+        assert_eq!(
+            Spell::Prayer.target(expert),
+            SpellTarget::Global(TargetSide::Friendly)
+        );
+        assert_eq!(
+            Spell::Sorrow.target(expert),
+            SpellTarget::Global(TargetSide::Enemy)
+        );
+    }
+
+    #[test]
+    fn area_spell_hits_every_stack_in_radius() {
+        let spells = [Spell::Fireball];
+        let mut state = battle(caster(&spells), caster(&spells));
+        let side = state.get_current_stack().side;
+
+        let enemy = find_stack(&state, side.other());
+        let center = state.get_stack(enemy).head;
+
+        let expected = state.get_expected_spell_damage(Spell::Fireball, Some(center));
+        assert_eq!(expected.len(), 1);
+
+        let health = state.get_stack(enemy).total_health();
+        state.apply_command(cast(Spell::Fireball, Some(center)));
+        assert_eq!(
+            health - state.get_stack(enemy).total_health(),
+            expected[0].1
+        );
+    }
+
+    #[test]
+    fn only_obstacle_and_control_spells_are_not_castable() {
+        let unsupported: Vec<Spell> = Spell::iter()
+            .filter(|spell| matches!(spell.r#type(), SpellType::Battle))
+            .filter(|&spell| !is_castable(spell))
+            .collect();
+
+        assert_eq!(
+            unsupported,
+            [
+                Spell::Quicksand,
+                Spell::LandMine,
+                Spell::ForceField,
+                Spell::FireWall,
+                Spell::Hypnotize,
+                Spell::RemoveObstacle,
+            ]
+        );
+    }
+
+    #[test]
+    fn teleport_does_not_cross_or_land_on_siege_wall() {
+        let spells = [Spell::Teleport];
+        let town = Army {
+            town: Some(Town::Castle),
+            ..caster(&spells)
+        };
+        let state = battle(caster(&spells), town);
+        assert_eq!(state.get_current_stack().side, Side::Attacker);
+
+        let pikeman = find_stack(&state, Side::Attacker);
+        let head = state.get_stack(pikeman).head;
+        let teleport = |destination| {
+            Command::Cast(Cast {
+                spell: Spell::Teleport,
+                target: Some(head),
+                destination: Some(destination),
+            })
+        };
+
+        let wall = state.get_wall_section_cells(WallSection::UpperWall)[0];
+        let inside = GridPos::new(14, 3);
+        assert!(siege::is_inside(inside));
+
+        assert!(state.is_command_applicable(teleport(GridPos::new(5, 3))));
+        assert!(!state.is_command_applicable(teleport(wall)));
+        assert!(!state.is_command_applicable(teleport(inside)));
+    }
+
+    #[test]
+    fn earthquake_needs_siege_walls() {
+        let spells = [Spell::Earthquake];
+        let state = battle(caster(&spells), caster(&spells));
+        assert!(!state.is_command_applicable(cast(Spell::Earthquake, None)));
+
+        let town = Army {
+            town: Some(Town::Castle),
+            ..caster(&spells)
+        };
+        let mut state = battle(caster(&spells), town);
+        assert!(state.is_command_applicable(cast(Spell::Earthquake, None)));
+
+        let wall_health = |state: &GameState| -> i32 {
+            WallSection::iter()
+                .map(|section| state.get_wall_health(section).unwrap())
+                .sum()
+        };
+        let before = wall_health(&state);
+        state.apply_command(cast(Spell::Earthquake, None));
+        let after = wall_health(&state);
+
+        // Без школы Земли два толчка
+        assert_eq!(before - after, 2);
+    }
+}
@@ -0,0 +1,260 @@
+use gamedata::artifacts::Artifact;
+use gamedata::heroes::AbilityLevel;
+use gamedata::spells::{Spell, SpellTarget, TargetSide};
+use rand::rngs::SmallRng;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+use crate::gamestate::hero::Hero;
+use crate::gamestate::{GameState, Side, StackHandle};
+use crate::stack::Stack;
+
+pub enum Resolution {
+    // Заклинание подействует на указанный отряд
+    // Отражённое зеркалом попадает не в ту цель, что выбрал герой
+    Affected(StackHandle),
+    Resisted,
+}
+
+// Невосприимчивые отряды нельзя выбрать целью заклинания
+pub fn is_immune(stack: &Stack, spell: Spell) -> bool {
+    stack.creature.is_immune_to(spell) || stack.effects.iter().any(|e| e.blocks_spell(spell))
+}
+
+pub fn resolve(
+    state: &mut GameState,
+    spell: Spell,
+    school_level: Option<AbilityLevel>,
+    side: Side,
+    handle: StackHandle,
+) -> Resolution {
+    let stack = &state.stacks[&handle];
+
+    if resists(stack, spell, side, &state.heroes, &mut state.rng) {
+        return Resolution::Resisted;
+    }
+
+    if !is_mirrorable(spell, school_level) || stack.side == side {
+        return Resolution::Affected(handle);
+    }
+
+    let mirror_chance = stack
+        .effects
+        .iter()
+        .map(|e| e.magic_mirror())
+        .chain([stack.creature.magic_mirror()])
+        .max()
+        .unwrap();
+
+    if !roll(mirror_chance, &mut state.rng) {
+        return Resolution::Affected(handle);
+    }
+
+    // Отражённое заклинание летит в случайный отряд колдующего
+    let candidates: Vec<StackHandle> = state
+        .units()
+        .into_iter()
+        .filter(|h| {
+            let stack = &state.stacks[h];
+            stack.is_alive()
+                && stack.side == side
+                && !resists(
+                    stack,
+                    spell,
+                    stack.side.other(),
+                    &state.heroes,
+                    &mut state.rng,
+                )
+        })
+        .collect();
+    let reflected = candidates.into_iter().choose(&mut state.rng);
+
+    match reflected {
+        Some(reflected) => Resolution::Affected(reflected),
+        None => Resolution::Resisted,
+    }
+}
+
+// Сопротивление срабатывает только против вражеских заклинаний
+// Навык Сопротивления героя даёт отрядам отдельный бросок
+pub fn resists(
+    stack: &Stack,
+    spell: Spell,
+    side: Side,
+    heroes: &[Option<Hero>; 2],
+    rng: &mut SmallRng,
+) -> bool {
+    if is_immune(stack, spell) {
+        return true;
+    }
+
+    // Сфера уязвимости отменяет сопротивление на всём поле
+    let is_vulnerable = heroes
+        .iter()
+        .flatten()
+        .any(|hero| hero.has_artifact(Artifact::OrbOfVulnerability));
+    if is_vulnerable {
+        return false;
+    }
+
+    let hero_resistance = heroes[stack.side as usize]
+        .as_ref()
+        .map_or(0, |hero| hero.magic_resistance());
+
+    stack.side != side
+        && (roll(stack.creature.magic_resistance(), rng) || roll(hero_resistance, rng))
+}
+
+// Зеркало отражает только заклинания, направленные в один вражеский отряд,
+// массовые версии на эксперте оно пропускает
+fn is_mirrorable(spell: Spell, school_level: Option<AbilityLevel>) -> bool {
+    spell.target(school_level) == SpellTarget::Stack(TargetSide::Enemy)
+}
+
+fn roll(percent: i32, rng: &mut SmallRng) -> bool {
+    percent > 0 && rng.gen_range(0..100) < percent
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::Creature;
+
+    use super::*;
+    use crate::effect::{Status, StatusEffect};
+    use crate::testing::{army, battle};
+
+    fn find(state: &GameState, creature: Creature) -> StackHandle {
+        state
+            .units()
+            .into_iter()
+            .find(|&handle| state.get_stack(handle).creature == creature)
+            .unwrap()
+    }
+
+    fn cast_many(
+        state: &mut GameState,
+        spell: Spell,
+        side: Side,
+        handle: StackHandle,
+        casts: usize,
+    ) -> Vec<Option<StackHandle>> {
+        (0..casts)
+            .map(|_| match resolve(state, spell, None, side, handle) {
+                Resolution::Affected(target) => Some(target),
+                Resolution::Resisted => None,
+            })
+            .collect()
+    }
+
+    fn with_mirror(state: &mut GameState, handle: StackHandle) {
+        state.edit_stack(handle, |stack| {
+            let mirror = StatusEffect::new(Status::MagicMirror, Some(AbilityLevel::Expert), None);
+            stack.apply_effect(mirror);
+        });
+    }
+
+    #[test]
+    fn dwarves_resist_only_enemy_spells() {
+        let mut state = battle(
+            army(&[(Creature::Pikeman, 10)]),
+            army(&[(Creature::Dwarf, 10)]),
+        );
+        let dwarf = find(&state, Creature::Dwarf);
+
+        // 20% сопротивления на тысячу бросков
+        let resisted = cast_many(&mut state, Spell::Slow, Side::Attacker, dwarf, 1000)
+            .into_iter()
+            .filter(Option::is_none)
+            .count();
+        assert!((150..250).contains(&resisted), "{resisted}");
+
+        let haste = cast_many(&mut state, Spell::Haste, Side::Defender, dwarf, 100);
+        assert!(haste.iter().all(|&target| target == Some(dwarf)));
+    }
+
+    #[test]
+    fn black_dragons_are_immune_to_any_spell() {
+        let mut state = battle(
+            army(&[(Creature::BlackDragon, 1)]),
+            army(&[(Creature::Pikeman, 10)]),
+        );
+        let dragon = find(&state, Creature::BlackDragon);
+        let pikeman = find(&state, Creature::Pikeman);
+
+        for (spell, side) in [
+            (Spell::MagicArrow, Side::Defender),
+            (Spell::Implosion, Side::Defender),
+            (Spell::Haste, Side::Attacker),
+        ] {
+            assert!(is_immune(state.get_stack(dragon), spell));
+            let casts = cast_many(&mut state, spell, side, dragon, 10);
+            assert!(casts.iter().all(Option::is_none));
+        }
+
+        assert!(!is_immune(state.get_stack(pikeman), Spell::Implosion));
+    }
+
+    #[test]
+    fn magic_mirror_redirects_to_caster_stacks() {
+        let mut state = battle(
+            army(&[(Creature::Pikeman, 10), (Creature::Archer, 10)]),
+            army(&[(Creature::Swordsman, 10)]),
+        );
+        let swordsman = find(&state, Creature::Swordsman);
+        with_mirror(&mut state, swordsman);
+
+        let targets: Vec<StackHandle> =
+            cast_many(&mut state, Spell::Slow, Side::Attacker, swordsman, 200)
+                .into_iter()
+                .flatten()
+                .collect();
+        let reflected: Vec<StackHandle> = targets
+            .iter()
+            .copied()
+            .filter(|&target| target != swordsman)
+            .collect();
+
+        // Эксперт отражает 40% заклинаний, и только в отряды колдующего
+        assert_eq!(targets.len(), 200);
+        assert!((50..110).contains(&reflected.len()), "{}", reflected.len());
+        assert!(reflected
+            .iter()
+            .all(|&target| state.get_stack(target).side == Side::Attacker));
+    }
+
+    #[test]
+    fn friendly_spells_bypass_magic_mirror() {
+        let mut state = battle(
+            army(&[(Creature::Pikeman, 10)]),
+            army(&[(Creature::Swordsman, 10)]),
+        );
+        let swordsman = find(&state, Creature::Swordsman);
+        with_mirror(&mut state, swordsman);
+
+        let casts = cast_many(&mut state, Spell::Haste, Side::Defender, swordsman, 100);
+        assert!(casts.iter().all(|&target| target == Some(swordsman)));
+    }
+
+    #[test]
+    fn magic_mirror_skips_mass_spells() {
+        for spell in [Spell::Curse, Spell::Slow] {
+            assert!(is_mirrorable(spell, None));
+            assert!(is_mirrorable(spell, Some(AbilityLevel::Advanced)));
+            assert!(!is_mirrorable(spell, Some(AbilityLevel::Expert)));
+        }
+        assert!(!is_mirrorable(Spell::Haste, None));
+    }
+
+    #[test]
+    fn anti_magic_blocks_spells_up_to_its_level() {
+        let blocks =
+            |level, spell| StatusEffect::new(Status::AntiMagic, level, Some(1)).blocks_spell(spell);
+
+        // Огненный шар третьего уровня, Цепная молния четвёртого, Взрыв пятого
+        assert!(blocks(None, Spell::Fireball));
+        assert!(!blocks(Some(AbilityLevel::Basic), Spell::ChainLightning));
+        assert!(blocks(Some(AbilityLevel::Advanced), Spell::ChainLightning));
+        assert!(!blocks(Some(AbilityLevel::Advanced), Spell::Implosion));
+        assert!(blocks(Some(AbilityLevel::Expert), Spell::Implosion));
+    }
+}
@@ -1,12 +1,14 @@
 use std::ops::RangeInclusive;
 
+use strum_macros::EnumIter;
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct GridPos {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
 pub enum AttackDirection {
     Left,
     TopLeft,
@@ -51,6 +53,20 @@ impl GridPos {
         self.y % 2 == 0
     }
 
+    // Расстояние в гексах между двумя клетками
+    // Переводим координаты со сдвигом рядов в осевые (q, r)
+    pub fn distance(self, other: Self) -> i32 {
+        fn axial(pos: GridPos) -> (i32, i32) {
+            (pos.x - pos.y / 2, pos.y)
+        }
+
+        let (q1, r1) = axial(self);
+        let (q2, r2) = axial(other);
+        let (dq, dr) = (q1 - q2, r1 - r2);
+
+        (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+    }
+
     pub fn get_successors_positional(self) -> [Option<Self>; 6] {
         let Self { x, y } = self;
 
@@ -1,7 +1,12 @@
+pub mod ai;
 pub mod command;
+pub mod effect;
 pub mod event;
 pub mod gamestate;
 pub mod grid;
 pub mod pathfinding;
 pub mod stack;
 mod turn;
+
+#[cfg(test)]
+mod testing;
@@ -15,6 +15,7 @@ struct VisitedCell {
 const X_MAX: usize = *GridPos::X_RANGE.end() as usize;
 const Y_MAX: usize = *GridPos::Y_RANGE.end() as usize;
 
+#[derive(Clone)]
 pub struct NavigationArray([Option<VisitedCell>; X_MAX * Y_MAX]);
 
 impl NavigationArray {
@@ -22,7 +23,7 @@ impl NavigationArray {
         Self([None; X_MAX * Y_MAX])
     }
 
-    pub fn new(cell: GridPos, state: &GameState, is_flying: bool) -> Self {
+    pub fn new(cell: GridPos, state: &GameState, side: Side, is_flying: bool) -> Self {
         let mut map = Self([None; X_MAX * Y_MAX]);
         map.put_cell(cell, cell, 0);
 
@@ -32,6 +33,11 @@ impl NavigationArray {
         while let Some((cell, cost_to_here)) = to_see.pop_front() {
             let new_cost = cost_to_here + 1;
 
+            // Вошедший в ров пешеход дальше в этот ход не идёт
+            if !is_flying && cost_to_here > 0 && state.is_moat(cell) {
+                continue;
+            }
+
             let successors = cell.get_successors();
 
             let successors = if is_flying {
@@ -40,6 +46,7 @@ impl NavigationArray {
                 successors
                     .into_iter()
                     .filter(|x| state.find_unit_for_cell(*x).is_none())
+                    .filter(|x| !state.is_cell_blocked(*x, side))
                     .collect()
             };
 
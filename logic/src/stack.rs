@@ -5,6 +5,7 @@ use gamedata::creatures::Creature;
 
 use super::gamestate::{GameState, Side};
 use super::pathfinding;
+use crate::effect::{Status, StatusEffect};
 use crate::grid::GridPos;
 use crate::turn::Phase;
 
@@ -12,6 +13,7 @@ use crate::turn::Phase;
 pub struct Stack {
     pub creature: Creature,
     pub count: i32,
+    pub initial_count: i32,
 
     pub current_health: i32,
     pub current_ammo: i32,
@@ -23,6 +25,18 @@ pub struct Stack {
     pub defending: bool,
 
     pub retaliation_count: RetaliationCount,
+
+    // Копия отряда, созданная заклинанием Клон
+    // Погибает от любого урона
+    pub is_clone: bool,
+    // Элементали, вызванные заклинанием, в итоги битвы не входят
+    pub is_summoned: bool,
+    // Существа, покинувшие поле боя вместе с бежавшим героем
+    pub fled: i32,
+    // Поднятые Воскрешением и Жертвой существа, для итогов битвы
+    pub resurrected: i32,
+
+    pub effects: Vec<StatusEffect>,
 }
 
 #[derive(Clone, Debug)]
@@ -33,6 +47,11 @@ pub enum RetaliationCount {
 
 impl RetaliationCount {
     pub fn from_creature(creature: Creature) -> Self {
+        // Боевые машины не отвечают на удары
+        if creature.is_war_machine() {
+            return Self::Finite(0);
+        }
+
         creature
             .abilities()
             .into_iter()
@@ -48,6 +67,12 @@ impl RetaliationCount {
         !matches!(self, RetaliationCount::Finite(0))
     }
 
+    pub fn increase(&mut self, amount: i32) {
+        if let RetaliationCount::Finite(n) = self {
+            *n += amount;
+        }
+    }
+
     pub fn decrement(&mut self) {
         if let RetaliationCount::Finite(n) = self {
             assert!(*n != 0);
@@ -61,6 +86,7 @@ impl Stack {
         Stack {
             creature,
             count,
+            initial_count: count,
             current_health: creature.base_stats().health,
             current_ammo: creature.base_stats().ammo_capacity,
             head,
@@ -68,12 +94,24 @@ impl Stack {
             turn_state: Some(Phase::Fresh),
             defending: false,
             retaliation_count: RetaliationCount::from_creature(creature),
+            is_clone: false,
+            is_summoned: false,
+            fled: 0,
+            resurrected: 0,
+            effects: vec![],
         }
     }
 
     pub fn refresh_for_next_turn(&mut self) {
+        // Обычно защита снимается, когда отряд снова получает ход,
+        // а ослеплённый отряд хода не получит
+        if !self.can_act() {
+            self.defending = false;
+        }
+
         self.turn_state = Some(Phase::Fresh);
         self.retaliation_count = RetaliationCount::from_creature(self.creature);
+        self.retaliation_count.increase(self.extra_retaliations());
     }
 
     pub fn base_stats(&self) -> creatures::Stats {
@@ -81,10 +119,162 @@ impl Stack {
     }
 
     pub fn speed(&self) -> i32 {
-        self.base_stats().speed
+        let speed = self.base_stats().speed;
+        let bonus: i32 = self.effects.iter().map(|e| e.speed(speed)).sum();
+        i32::max(speed + bonus, 1)
+    }
+
+    pub fn attack(&self, is_ranged: bool) -> i32 {
+        let bonus: i32 = self.effects.iter().map(|e| e.attack(is_ranged)).sum();
+        let attack = self.base_stats().attack + bonus;
+
+        match self.frenzy_multiplier() {
+            Some(multiplier) => attack + (self.defence_with_effects() as f32 * multiplier) as i32,
+            None => attack,
+        }
+    }
+
+    pub fn defence(&self) -> i32 {
+        if self.frenzy_multiplier().is_some() {
+            0
+        } else {
+            i32::max(self.defence_with_effects(), 0)
+        }
+    }
+
+    fn defence_with_effects(&self) -> i32 {
+        let bonus: i32 = self.effects.iter().map(|e| e.defence()).sum();
+        self.base_stats().defence + bonus
+    }
+
+    fn frenzy_multiplier(&self) -> Option<f32> {
+        self.effects.iter().find_map(|e| e.frenzy_multiplier())
+    }
+
+    pub fn damage_range(&self) -> (i32, i32) {
+        self.effects
+            .iter()
+            .fold(self.base_stats().damage, |range, e| e.damage_range(range))
+    }
+
+    // Доля получаемого урона после щитов
+    pub fn damage_taken_multiplier(&self, is_ranged: bool) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| 1.0 - e.damage_reduction(is_ranged))
+            .product()
+    }
+
+    // Доля наносимого урона после ослабляющих эффектов
+    // Ослепление снижает только урон ответного удара
+    pub fn damage_dealt_multiplier(&self, is_ranged: bool, is_retaliation: bool) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| {
+                let ranged = if is_ranged {
+                    e.ranged_multiplier()
+                } else {
+                    1.0
+                };
+                let retaliation = if is_retaliation {
+                    e.retaliation_multiplier()
+                } else {
+                    1.0
+                };
+                ranged * retaliation
+            })
+            .product()
+    }
+
+    // Ослеплённый на эксперте отряд совсем не отвечает на удары
+    pub fn retaliation_multiplier(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| e.retaliation_multiplier())
+            .product()
+    }
+
+    pub fn slayer_attack(&self, target: Creature) -> i32 {
+        self.effects.iter().map(|e| e.slayer_attack(target)).sum()
+    }
+
+    // Удача и боевой дух от заклинаний, прибавляются к показателям героя
+    pub fn luck(&self) -> i32 {
+        self.effects.iter().map(|e| e.luck()).sum()
+    }
+
+    pub fn morale(&self) -> i32 {
+        self.effects.iter().map(|e| e.morale()).sum()
+    }
+
+    pub fn fire_shield(&self) -> f32 {
+        self.effects.iter().map(|e| e.fire_shield()).sum()
+    }
+
+    fn extra_retaliations(&self) -> i32 {
+        self.effects.iter().map(|e| e.extra_retaliations()).sum()
+    }
+
+    pub fn has_status(&self, status: Status) -> bool {
+        self.effects.iter().any(|e| e.status == status)
+    }
+
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        if effect.status.is_cumulative() {
+            self.effects.push(effect);
+        } else {
+            self.remove_effects(|e| e.status == effect.status);
+            self.effects.push(effect);
+        }
+
+        self.retaliation_count.increase(effect.extra_retaliations());
+    }
+
+    pub fn remove_effects(&mut self, predicate: impl Fn(&StatusEffect) -> bool) {
+        self.effects.retain(|e| !predicate(e));
+    }
+
+    // Отсчитывает раунд и возвращает истёкшие эффекты
+    pub fn tick_effects(&mut self) -> Vec<Status> {
+        for effect in self.effects.iter_mut() {
+            effect.tick();
+        }
+
+        let expired = self
+            .effects
+            .iter()
+            .filter(|e| e.is_expired())
+            .map(|e| e.status)
+            .collect();
+        self.remove_effects(|e| e.is_expired());
+
+        expired
+    }
+
+    // Тележка с боеприпасами своего хода не получает
+    pub fn can_act(&self) -> bool {
+        self.creature != Creature::AmmoCart && !self.effects.iter().any(|e| e.prevents_acting())
+    }
+
+    pub fn is_berserk(&self) -> bool {
+        self.effects.iter().any(|e| e.is_berserk())
+    }
+
+    // Берсерк атакует любой отряд кроме себя
+    pub fn is_enemy(&self, other: &Stack) -> bool {
+        self.side != other.side || self.is_berserk()
     }
 
     pub fn can_shoot(&self, state: &GameState) -> bool {
+        if self.effects.iter().any(|e| e.prevents_shooting()) {
+            return false;
+        }
+
+        // Баллиста стреляет без ограничений, даже когда враг рядом
+        if self.creature == Creature::Ballista {
+            return true;
+        }
+
         let has_ammo = self.current_ammo != 0;
         let has_enemies_around = self
             .get_adjacent_cells()
@@ -102,6 +292,16 @@ impl Stack {
         pathfinding::get_occupied_cells_for(self.creature, self.side, self.head).unwrap()
     }
 
+    pub fn distance_to(&self, other: &Stack) -> i32 {
+        let other_cells = other.get_occupied_cells();
+
+        self.get_occupied_cells()
+            .into_iter()
+            .flat_map(|cell| other_cells.iter().map(move |&other| cell.distance(other)))
+            .min()
+            .unwrap()
+    }
+
     fn get_adjacent_cells(&self) -> Vec<GridPos> {
         self.get_occupied_cells()
             .iter()
@@ -112,16 +312,51 @@ impl Stack {
     }
 
     pub fn receive_damage(&mut self, damage: i32) {
+        if self.is_clone && damage > 0 {
+            self.current_health = 0;
+            self.count = 0;
+            return;
+        }
+
+        self.set_total_health(self.total_health() - damage);
+    }
+
+    // Лечит только верхнее существо в отряде
+    pub fn heal(&mut self, amount: i32) {
+        if !self.is_alive() {
+            return;
+        }
+
         let creature_health = self.creature.base_stats().health;
+        self.current_health = i32::min(self.current_health + amount, creature_health);
+    }
 
-        let total_health = (self.count - 1) * creature_health + self.current_health - damage;
+    // Поднимает павших существ, но не больше чем было в начале боя
+    pub fn resurrect(&mut self, amount: i32) {
+        let max_health = self.initial_count * self.creature.base_stats().health;
+        let count_before = self.count;
+        self.set_total_health(i32::min(self.total_health() + amount, max_health));
+        self.resurrected += self.count - count_before;
+    }
+
+    pub fn total_health(&self) -> i32 {
+        if !self.is_alive() {
+            return 0;
+        }
 
+        let creature_health = self.creature.base_stats().health;
+        (self.count - 1) * creature_health + self.current_health
+    }
+
+    fn set_total_health(&mut self, total_health: i32) {
         if total_health <= 0 {
             self.current_health = 0;
             self.count = 0;
             return;
         }
 
+        let creature_health = self.creature.base_stats().health;
+
         let div = total_health / creature_health;
         let rem = total_health % creature_health;
         if rem == 0 {
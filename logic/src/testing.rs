@@ -0,0 +1,27 @@
+use gamedata::creatures::Creature;
+
+use crate::gamestate::{Army, GameState};
+
+// Армия без героя, отряды встают в обычную расстановку
+pub fn army(stacks: &[(Creature, i32)]) -> Army {
+    let mut slots = [None; 7];
+    for (slot, &stack) in slots.iter_mut().zip(stacks) {
+        *slot = Some(stack);
+    }
+
+    Army {
+        hero: None,
+        hero_level: None,
+        hero_stats: None,
+        hero_abilities: None,
+        hero_spells: None,
+        hero_artifacts: vec![],
+        stacks: slots,
+        war_machines: vec![],
+        town: None,
+    }
+}
+
+pub fn battle(attacker: Army, defender: Army) -> GameState {
+    GameState::with_seed(&[attacker, defender], 0).unwrap()
+}
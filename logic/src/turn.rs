@@ -1,46 +1,83 @@
-use crate::gamestate::Side;
+use std::collections::HashMap;
 
+use crate::gamestate::{QueuedTurn, Side, StackHandle, TurnOrder};
+use crate::grid::GridPos;
+use crate::stack::Stack;
+
+#[derive(Clone)]
 pub struct Turn {
+    // Номер раунда, начиная с первого
+    pub round: u32,
+    // Расстановка перед первым раундом, пока она не закончена
+    pub tactics: Option<Tactics>,
+    // Сторона, чей отряд пойдёт первым при равенстве скоростей
     pub priority_side: Side,
     pub current_phase: Phase,
     pub phases: PhaseIterator,
+    // Отряды, уже получившие ход от боевого духа в этом раунде
+    pub morale_stacks: Vec<StackHandle>,
 }
 
 impl Turn {
     const PRIORITY_SIDE: Side = Side::Attacker;
 
-    pub fn new() -> Self {
-        Self::build(Self::PRIORITY_SIDE)
+    pub fn new(tactics: Option<Tactics>) -> Self {
+        Self {
+            tactics,
+            ..Self::build(Self::PRIORITY_SIDE, 1)
+        }
     }
 
     pub fn next(&self) -> Self {
-        println!("New turn!");
-        Self::build(self.priority_side.other())
+        Self::build(self.priority_side, self.round + 1)
     }
 
-    fn build(priority_side: Side) -> Self {
+    fn build(priority_side: Side, round: u32) -> Self {
         let mut phases = Phase::iter();
         let current_phase = phases.next().unwrap();
 
         Self {
+            round,
+            tactics: None,
             priority_side,
             current_phase,
             phases,
+            morale_stacks: vec![],
         }
     }
 
+    // Стороны чередуются: после хода одной преимущество переходит к другой
+    pub fn record_move(&mut self, side: Side) {
+        self.priority_side = side.other();
+    }
+
     pub fn try_advance_phase(&mut self) -> bool {
         let next = self.phases.next();
 
         if let Some(phase) = next {
             self.current_phase = phase;
-            println!("New turn phase: {:?}!", self.current_phase);
         }
 
         next.is_some()
     }
 }
 
+// Сторона с лучшей Тактикой переставляет отряды в нескольких ближних к ней колонках
+#[derive(Clone, Copy, Debug)]
+pub struct Tactics {
+    pub side: Side,
+    pub columns: i32,
+}
+
+impl Tactics {
+    pub fn contains(&self, cell: GridPos) -> bool {
+        match self.side {
+            Side::Attacker => cell.x < GridPos::X_RANGE.start() + self.columns,
+            Side::Defender => cell.x > GridPos::X_RANGE.end() - self.columns,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Phase {
     Fresh,
@@ -54,3 +91,246 @@ impl Phase {
         [Self::Fresh, Self::Wait].into_iter()
     }
 }
+
+// Следующий отряд, который ходит в указанной фазе
+pub fn pick_next<'a>(
+    stacks: impl IntoIterator<Item = (&'a StackHandle, &'a Stack)>,
+    phase: Phase,
+    priority_side: Side,
+) -> Option<StackHandle> {
+    stacks
+        .into_iter()
+        .filter(|(_, stack)| stack.is_alive() && stack.can_act())
+        .filter(|(_, stack)| stack.turn_state == Some(phase))
+        .min_by_key(|&(&handle, stack)| {
+            // Ожидавшие отряды ходят в обратном порядке: сначала самые медленные
+            let speed = match phase {
+                Phase::Fresh => -stack.speed(),
+                Phase::Wait => stack.speed(),
+            };
+
+            // При равенстве скоростей ходит сторона, не ходившая последней
+            (speed, stack.side != priority_side, handle)
+        })
+        .map(|(&handle, _)| handle)
+}
+
+// Предсказание считает, что никто больше не будет ждать
+// Дополнительные ходы от боевого духа случайны, поэтому только отмечаются
+pub fn predict(
+    stacks: &HashMap<StackHandle, Stack>,
+    current: StackHandle,
+    turn: &Turn,
+    morale: [i32; 2],
+) -> TurnOrder {
+    let mut stacks = stacks.clone();
+    let mut priority_side = turn.priority_side;
+
+    let mut current_round = vec![];
+
+    if let Some(stack) = stacks.get_mut(&current) {
+        if stack.is_alive() && stack.turn_state.is_some() {
+            current_round.push(current);
+            stack.turn_state = None;
+            priority_side = stack.side.other();
+        }
+    }
+
+    let phases = Phase::iter().skip_while(|&phase| phase != turn.current_phase);
+    current_round.extend(simulate_round(&mut stacks, phases, &mut priority_side));
+
+    // Эффекты, закончившиеся к новому раунду, уже не влияют на скорость
+    for stack in stacks.values_mut() {
+        stack.tick_effects();
+        stack.refresh_for_next_turn();
+    }
+
+    let next_round = simulate_round(&mut stacks, Phase::iter(), &mut priority_side);
+
+    // Ход от боевого духа даётся не чаще раза за раунд
+    let queue = |handles: Vec<StackHandle>, used: &[StackHandle]| -> Vec<QueuedTurn> {
+        handles
+            .into_iter()
+            .map(|handle| {
+                let stack = &stacks[&handle];
+                let morale_chance = morale[stack.side as usize] + stack.morale() > 0
+                    && stack.can_act()
+                    && !stack.creature.is_war_machine()
+                    && !used.contains(&handle);
+
+                QueuedTurn {
+                    stack: handle,
+                    morale_chance,
+                }
+            })
+            .collect()
+    };
+
+    TurnOrder {
+        current_round: queue(current_round, &turn.morale_stacks),
+        next_round: queue(next_round, &[]),
+    }
+}
+
+fn simulate_round(
+    stacks: &mut HashMap<StackHandle, Stack>,
+    phases: impl Iterator<Item = Phase>,
+    priority_side: &mut Side,
+) -> Vec<StackHandle> {
+    let mut order = vec![];
+
+    for phase in phases {
+        while let Some(handle) = pick_next(stacks.iter(), phase, *priority_side) {
+            let stack = stacks.get_mut(&handle).unwrap();
+            stack.turn_state = None;
+            *priority_side = stack.side.other();
+            order.push(handle);
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use gamedata::creatures::Creature;
+
+    use super::*;
+
+    fn stacks(list: &[(Creature, Side, Phase)]) -> HashMap<StackHandle, Stack> {
+        list.iter()
+            .enumerate()
+            .map(|(i, &(creature, side, phase))| {
+                let mut stack = Stack::new(creature, 1, GridPos::new(1, 1), side);
+                stack.turn_state = Some(phase);
+                (StackHandle::new(i as u32), stack)
+            })
+            .collect()
+    }
+
+    fn round(list: &[(Creature, Side, Phase)], priority_side: Side) -> Vec<StackHandle> {
+        let mut stacks = stacks(list);
+        let mut priority_side = priority_side;
+        simulate_round(&mut stacks, Phase::iter(), &mut priority_side)
+    }
+
+    fn handles(ids: &[u32]) -> Vec<StackHandle> {
+        ids.iter().map(|&id| StackHandle::new(id)).collect()
+    }
+
+    #[test]
+    fn fastest_stack_goes_first() {
+        let list = [
+            (Creature::Pikeman, Side::Attacker, Phase::Fresh),
+            (Creature::Angel, Side::Defender, Phase::Fresh),
+            (Creature::Swordsman, Side::Attacker, Phase::Fresh),
+            (Creature::Cavalier, Side::Defender, Phase::Fresh),
+        ];
+
+        assert_eq!(round(&list, Side::Attacker), handles(&[1, 3, 2, 0]));
+    }
+
+    #[test]
+    fn tie_goes_to_priority_side() {
+        let list = [
+            (Creature::Pikeman, Side::Attacker, Phase::Fresh),
+            (Creature::Archer, Side::Defender, Phase::Fresh),
+        ];
+        let stacks = stacks(&list);
+
+        let first = pick_next(&stacks, Phase::Fresh, Side::Attacker);
+        assert_eq!(first, Some(StackHandle::new(0)));
+
+        let first = pick_next(&stacks, Phase::Fresh, Side::Defender);
+        assert_eq!(first, Some(StackHandle::new(1)));
+    }
+
+    #[test]
+    fn ties_alternate_between_sides() {
+        let list = [
+            (Creature::Pikeman, Side::Attacker, Phase::Fresh),
+            (Creature::Pikeman, Side::Attacker, Phase::Fresh),
+            (Creature::Archer, Side::Defender, Phase::Fresh),
+            (Creature::Archer, Side::Defender, Phase::Fresh),
+        ];
+
+        assert_eq!(round(&list, Side::Defender), handles(&[2, 0, 3, 1]));
+    }
+
+    #[test]
+    fn waiting_stacks_go_last_slowest_first() {
+        let list = [
+            (Creature::Angel, Side::Attacker, Phase::Wait),
+            (Creature::Pikeman, Side::Defender, Phase::Wait),
+            (Creature::Swordsman, Side::Defender, Phase::Fresh),
+            (Creature::Griffin, Side::Attacker, Phase::Wait),
+        ];
+
+        assert_eq!(round(&list, Side::Attacker), handles(&[2, 1, 3, 0]));
+    }
+
+    #[test]
+    fn waiting_ties_alternate_between_sides() {
+        let list = [
+            (Creature::Archer, Side::Defender, Phase::Wait),
+            (Creature::Pikeman, Side::Attacker, Phase::Wait),
+            (Creature::Archer, Side::Defender, Phase::Wait),
+        ];
+
+        assert_eq!(round(&list, Side::Defender), handles(&[0, 1, 2]));
+        assert_eq!(round(&list, Side::Attacker), handles(&[1, 0, 2]));
+    }
+
+    #[test]
+    fn dead_and_finished_stacks_are_skipped() {
+        let list = [
+            (Creature::Angel, Side::Attacker, Phase::Fresh),
+            (Creature::Pikeman, Side::Defender, Phase::Fresh),
+            (Creature::Cavalier, Side::Defender, Phase::Fresh),
+        ];
+        let mut stacks = stacks(&list);
+        stacks.get_mut(&StackHandle::new(0)).unwrap().count = 0;
+        stacks.get_mut(&StackHandle::new(2)).unwrap().turn_state = None;
+
+        let next = pick_next(&stacks, Phase::Fresh, Side::Attacker);
+        assert_eq!(next, Some(StackHandle::new(1)));
+    }
+
+    #[test]
+    fn prediction_marks_possible_morale_turns() {
+        let list = [
+            (Creature::Angel, Side::Attacker, Phase::Fresh),
+            (Creature::Pikeman, Side::Defender, Phase::Fresh),
+        ];
+        let stacks = stacks(&list);
+        let mut turn = Turn::new(None);
+
+        let chances = |order: &[QueuedTurn]| -> Vec<(StackHandle, bool)> {
+            order.iter().map(|q| (q.stack, q.morale_chance)).collect()
+        };
+        let angel = StackHandle::new(0);
+        let pikeman = StackHandle::new(1);
+
+        let order = predict(&stacks, angel, &turn, [1, 0]);
+        assert_eq!(
+            chances(&order.current_round),
+            [(angel, true), (pikeman, false)]
+        );
+        assert_eq!(
+            chances(&order.next_round),
+            [(angel, true), (pikeman, false)]
+        );
+
+        // Ход от боевого духа в этом раунде уже был
+        turn.morale_stacks.push(angel);
+        let order = predict(&stacks, angel, &turn, [1, 0]);
+        assert_eq!(
+            chances(&order.current_round),
+            [(angel, false), (pikeman, false)]
+        );
+        assert_eq!(
+            chances(&order.next_round),
+            [(angel, true), (pikeman, false)]
+        );
+    }
+}
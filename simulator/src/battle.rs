@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use common::error::AnyHow;
+use logic::event::Event;
+use logic::gamestate::{Army, BattleOutcome, GameState, Side, StackHandle};
+
+use crate::policy::Policy;
+
+// Битва, в которой никто не может добраться до врага, не закончится сама
+const MAX_COMMANDS: usize = 10_000;
+
+// Кто снял здоровье: отряд, заклинания героя или укрепления города
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageSource {
+    Stack(StackHandle),
+    Spells(Side),
+    Towers,
+    Moat,
+}
+
+pub struct BattleResult {
+    // None, если битва упёрлась в ограничение на число команд
+    pub outcome: Option<BattleOutcome>,
+    pub rounds: u32,
+    pub survivors: HashMap<StackHandle, i32>,
+    // Снятое здоровье по источникам, у отрядов включая ответные удары
+    pub damage_dealt: HashMap<DamageSource, i32>,
+}
+
+pub fn run(
+    armies: &[Army; 2],
+    seed: u64,
+    policies: &mut [Box<dyn Policy>; 2],
+) -> AnyHow<BattleResult> {
+    let mut state = GameState::with_seed(armies, seed)?;
+    let mut damage_dealt = HashMap::new();
+
+    for _ in 0..MAX_COMMANDS {
+        if state.get_outcome().is_some() {
+            break;
+        }
+
+        let side = state.get_current_stack().side;
+        let Some(command) = policies[side as usize].choose_command(&state) else {
+            return Err(format!("Policy has no command for {side:?}").into());
+        };
+
+        if !state.is_command_applicable(command) {
+            return Err(format!("Policy chose inapplicable command {command:?}").into());
+        }
+
+        for event in state.apply_command(command) {
+            record_damage(&event, &mut damage_dealt);
+        }
+    }
+
+    let survivors = state
+        .units()
+        .into_iter()
+        .map(|handle| (handle, state.get_stack(handle).count))
+        .collect();
+
+    Ok(BattleResult {
+        outcome: state.get_outcome(),
+        rounds: state.get_round(),
+        survivors,
+        damage_dealt,
+    })
+}
+
+fn record_damage(event: &Event, damage_dealt: &mut HashMap<DamageSource, i32>) {
+    let mut add = |source, damage| *damage_dealt.entry(source).or_default() += damage;
+
+    match event {
+        Event::Attack(attack) => {
+            for strike in &attack.strikes {
+                let (striker, target) = if strike.retaliation {
+                    (attack.defender, attack.attacker)
+                } else {
+                    (attack.attacker, attack.defender)
+                };
+                add(DamageSource::Stack(striker), strike.damage);
+                add(DamageSource::Stack(target), strike.reflected);
+            }
+        }
+        Event::Shot(shot) => add(DamageSource::Stack(shot.attacker), shot.damage),
+        Event::Cast(cast) => {
+            let damage = cast.affected.iter().map(|hit| hit.damage).sum();
+            add(DamageSource::Spells(cast.side), damage);
+        }
+        Event::TowerShot(shot) => add(DamageSource::Towers, shot.damage),
+        Event::MoatDamage(moat) => add(DamageSource::Moat, moat.damage),
+        _ => {}
+    }
+}
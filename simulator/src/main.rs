@@ -0,0 +1,158 @@
+use std::env;
+use std::fs::File;
+use std::panic::{self, AssertUnwindSafe};
+
+use common::error::{AnyHow, AnyWay};
+use logic::gamestate::{Army, BattleOutcome, GameState, Side};
+
+mod battle;
+mod policy;
+
+use battle::{BattleResult, DamageSource};
+use policy::{Policy, Scripted};
+
+const USAGE: &str = "Usage: simulator <attacker.ron> <defender.ron> [battles] [seed]";
+
+const DEFAULT_BATTLES: u64 = 100;
+
+fn main() -> AnyWay {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let [attacker, defender, rest @ ..] = args.as_slice() else {
+        return Err(USAGE.into());
+    };
+
+    let armies = [load_army(attacker)?, load_army(defender)?];
+    let battles = match rest.first() {
+        Some(battles) => battles.parse()?,
+        None => DEFAULT_BATTLES,
+    };
+    let seed = match rest.get(1) {
+        Some(seed) => seed.parse()?,
+        None => 0,
+    };
+
+    let mut policies: [Box<dyn Policy>; 2] = [Box::new(Scripted), Box::new(Scripted)];
+
+    // Каждая битва получает своё зерно, так что прогон целиком воспроизводим,
+    // а упавшая битва не останавливает остальные
+    let mut results = vec![];
+    let mut failures = 0;
+
+    for seed in seed..seed + battles {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            battle::run(&armies, seed, &mut policies)
+        }));
+
+        match result {
+            Ok(Ok(result)) => results.push(result),
+            Ok(Err(error)) => {
+                eprintln!("Battle with seed {seed} failed: {error}");
+                failures += 1;
+            }
+            Err(_) => {
+                eprintln!("Battle with seed {seed} panicked");
+                failures += 1;
+            }
+        }
+    }
+
+    if results.is_empty() {
+        return Err(format!("All {failures} battles failed").into());
+    }
+
+    print_report(&armies, &results, failures)
+}
+
+fn load_army(path: &str) -> AnyHow<Army> {
+    let reader = File::open(path).map_err(|e| format!("{path}: {e}"))?;
+    let army = ron::de::from_reader(reader).map_err(|e| format!("{path}: {e}"))?;
+
+    Ok(army)
+}
+
+fn print_report(armies: &[Army; 2], results: &[BattleResult], failures: usize) -> AnyWay {
+    let battles = results.len() as f32;
+    let share = |predicate: fn(Option<BattleOutcome>) -> bool| {
+        let count = results.iter().filter(|r| predicate(r.outcome)).count();
+        100.0 * count as f32 / battles
+    };
+
+    println!("Battles: {}", results.len());
+    println!("Failed: {failures}");
+    println!(
+        "Attacker wins: {:.1}%",
+        share(|o| o
+            == Some(BattleOutcome::Victory {
+                winner: Side::Attacker
+            }))
+    );
+    println!(
+        "Defender wins: {:.1}%",
+        share(|o| o
+            == Some(BattleOutcome::Victory {
+                winner: Side::Defender
+            }))
+    );
+    println!("Ties: {:.1}%", share(|o| o == Some(BattleOutcome::Tie)));
+    println!("Unfinished: {:.1}%", share(|o| o.is_none()));
+
+    let rounds: u32 = results.iter().map(|r| r.rounds).sum();
+    println!("Average rounds: {:.1}", rounds as f32 / battles);
+    println!();
+
+    // Отряды из начальной расстановки, призванные и клоны в отчёт не попадают
+    let initial = GameState::with_seed(armies, 0)?;
+
+    println!(
+        "{:<10}{:<20}{:>8}{:>12}{:>16}",
+        "Side", "Creature", "Count", "Survivors", "Damage dealt"
+    );
+    let average_damage = |source| {
+        let damage: i32 = results
+            .iter()
+            .map(|r| r.damage_dealt.get(&source).copied().unwrap_or(0))
+            .sum();
+        damage as f32 / battles
+    };
+
+    for handle in initial.units() {
+        let stack = initial.get_stack(handle);
+
+        let survivors: i32 = results.iter().map(|r| r.survivors[&handle]).sum();
+
+        println!(
+            "{:<10}{:<20}{:>8}{:>12.1}{:>16.1}",
+            format!("{:?}", stack.side),
+            format!("{:?}", stack.creature),
+            stack.count,
+            survivors as f32 / battles,
+            average_damage(DamageSource::Stack(handle)),
+        );
+    }
+
+    // Урон не от отрядов: заклинания героев, башни и ров осады
+    let mut other_sources = vec![];
+    for side in [Side::Attacker, Side::Defender] {
+        if armies[side as usize].hero.is_some() {
+            other_sources.push((side, "Hero spells", DamageSource::Spells(side)));
+        }
+    }
+    if armies[1].town.is_some() {
+        other_sources.push((Side::Defender, "Arrow towers", DamageSource::Towers));
+        other_sources.push((Side::Defender, "Moat", DamageSource::Moat));
+    }
+
+    for (side, name, source) in other_sources {
+        println!(
+            "{:<10}{:<20}{:>8}{:>12}{:>16.1}",
+            format!("{:?}", side),
+            name,
+            "-",
+            "-",
+            average_damage(source),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use logic::ai;
+use logic::command::Command;
+use logic::gamestate::GameState;
+
+// Кто выбирает команды за сторону в симуляции
+pub trait Policy {
+    fn choose_command(&mut self, state: &GameState) -> Option<Command>;
+}
+
+// Встроенный компьютерный противник из logic
+pub struct Scripted;
+
+impl Policy for Scripted {
+    fn choose_command(&mut self, state: &GameState) -> Option<Command> {
+        ai::choose_command(state)
+    }
+}